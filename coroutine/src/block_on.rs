@@ -0,0 +1,86 @@
+use std::{
+    pin::pin,
+    task::{Context, Poll, Waker},
+};
+
+use crate::sync::blocker::Blocker;
+
+/// Runs `fut` to completion on the current coroutine and returns its output
+///
+/// The future is polled with a `Waker` which unparks the current coroutine, which is parked while
+/// the future is pending so that the worker keeps running the other coroutines. Outside of
+/// coroutines the current thread is blocked instead. This lets async libraries run on the
+/// coroutine runtime without an executor of their own, as long as they don't need one of a
+/// specific runtime.
+///
+/// A cancelled coroutine unwinds while parked, dropping the future.
+pub fn block_on<F: Future>(fut: F) -> F::Output {
+    let mut fut = pin!(fut);
+    let blocker = Blocker::current();
+    let waker = Waker::from(blocker.clone());
+    let mut cx = Context::from_waker(&waker);
+
+    loop {
+        if let Poll::Ready(t) = fut.as_mut().poll(&mut cx) {
+            return t;
+        }
+
+        // A wake up during the poll was kept by the blocker, the future is polled again right away
+        blocker.park(None).ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{future, thread, time::Duration};
+
+    use super::*;
+    use crate::{sleep, spawn, spawn_future, sync::mpsc};
+
+    #[test]
+    fn awaits_coroutines_from_a_thread() {
+        let (tx, rx) = mpsc::channel();
+
+        let handle = unsafe { spawn(move || rx.recv().unwrap() * 2) };
+
+        let sender = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(10));
+
+            tx.send(21).unwrap();
+        });
+
+        assert_eq!(block_on(handle).unwrap(), 42);
+
+        sender.join().unwrap();
+    }
+
+    #[test]
+    fn spawned_future_is_woken_by_reference() {
+        let mut polls = 0;
+
+        // Pending a few times, waking itself up before returning
+        let fut = future::poll_fn(move |cx| {
+            polls += 1;
+
+            if polls < 3 {
+                cx.waker().wake_by_ref();
+
+                return Poll::Pending;
+            }
+
+            Poll::Ready(polls)
+        });
+
+        let handle = unsafe { spawn_future(fut) };
+
+        let chained = unsafe {
+            spawn_future(async move {
+                sleep(Duration::from_millis(1));
+
+                handle.await.unwrap() + 1
+            })
+        };
+
+        assert_eq!(chained.join().unwrap(), 4);
+    }
+}
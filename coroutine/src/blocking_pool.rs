@@ -0,0 +1,147 @@
+use std::{
+    collections::VecDeque,
+    io, ptr,
+    sync::{Condvar, Mutex, OnceLock},
+    thread,
+    time::Duration,
+};
+
+use log::error;
+
+use crate::config::config;
+
+/// A call queued for a blocking thread
+type Task = Box<dyn FnOnce() + Send>;
+
+/// Elastic pool of threads running the blocking calls of `spawn_blocking`
+///
+/// A thread is started whenever more calls are queued than threads wait for them, up to
+/// `Config::get_blocking_threads`. Beyond that the calls wait in the queue. A thread that got no
+/// call during `Config::get_blocking_idle_timeout` exits.
+pub(crate) struct BlockingPool {
+    state: Mutex<PoolState>,
+    condvar: Condvar,
+    max_threads: usize,
+    idle_timeout: Duration,
+}
+
+struct PoolState {
+    queue: VecDeque<Task>,
+
+    /// Number of running threads, busy or idle
+    threads: usize,
+
+    /// Number of threads waiting for a call
+    idle: usize,
+}
+
+/// Get the blocking pool, created on first use
+pub(crate) fn get_blocking_pool() -> &'static BlockingPool {
+    static POOL: OnceLock<BlockingPool> = OnceLock::new();
+
+    POOL.get_or_init(|| BlockingPool {
+        state: Mutex::new(PoolState {
+            queue: VecDeque::new(),
+            threads: 0,
+            idle: 0,
+        }),
+        condvar: Condvar::new(),
+        max_threads: config().get_blocking_threads(),
+        idle_timeout: config().get_blocking_idle_timeout(),
+    })
+}
+
+impl BlockingPool {
+    /// Queue `task` for a blocking thread
+    /// Fails if no thread can be started to run it, the task is dropped then
+    pub(crate) fn execute(&'static self, task: Task) -> io::Result<()> {
+        let key = &*task as *const (dyn FnOnce() + Send);
+        let mut state = self.state.lock().unwrap();
+
+        state.queue.push_back(task);
+
+        if state.idle > 0 {
+            self.condvar.notify_one();
+        }
+
+        // The idle threads may already be taken by the calls queued before
+        let spawn = state.queue.len() > state.idle && state.threads < self.max_threads;
+
+        if !spawn {
+            return Ok(());
+        }
+
+        // Counted before the spawn so the concurrent calls don't start too many threads
+        state.threads += 1;
+
+        drop(state);
+
+        let err = match thread::Builder::new()
+            .name("coroutine-blocking".to_string())
+            .spawn(move || self.run())
+        {
+            Ok(_) => return Ok(()),
+            Err(err) => err,
+        };
+
+        let mut state = self.state.lock().unwrap();
+
+        state.threads -= 1;
+
+        // The queued call is left to the running threads
+        if state.threads > 0 {
+            error!("Failed to spawn blocking thread: {}", err);
+
+            return Ok(());
+        }
+
+        // No thread would ever run the call. The calls are told apart by address, those of
+        // `spawn_blocking` capture their result slot so they're never zero sized
+        let pos = state
+            .queue
+            .iter()
+            .position(|queued| ptr::addr_eq(&**queued, key));
+
+        if let Some(pos) = pos {
+            state.queue.remove(pos);
+        }
+
+        Err(err)
+    }
+
+    /// The home loop of a blocking thread
+    fn run(&self) {
+        let mut state = self.state.lock().unwrap();
+
+        loop {
+            if let Some(task) = state.queue.pop_front() {
+                drop(state);
+
+                task();
+
+                state = self.state.lock().unwrap();
+
+                continue;
+            }
+
+            state.idle += 1;
+
+            let (guard, result) = self.condvar.wait_timeout(state, self.idle_timeout).unwrap();
+
+            state = guard;
+            state.idle -= 1;
+
+            if result.timed_out() && state.queue.is_empty() {
+                state.threads -= 1;
+
+                return;
+            }
+        }
+    }
+
+    /// Number of running threads, busy or idle
+    #[cfg(test)]
+    pub(crate) fn threads(&self) -> usize {
+        self.state.lock().unwrap().threads
+    }
+}
@@ -1,10 +1,22 @@
-use std::{borrow::Cow, io, sync::Arc, thread::JoinHandle};
+use std::{
+    borrow::Cow,
+    io, mem,
+    panic::{self, AssertUnwindSafe},
+    sync::Arc,
+};
 
 use crate::{
-    Coroutine,
+    Coroutine, CoroutineImpl,
+    config::config,
+    coroutine_local::{CoroutineLocal, current_context},
     done::Done,
     event::{EventSource, EventSubscriber},
     join::Join,
+    join_handle::{JoinHandle, make_join_handle},
+    scheduler::get_scheduler,
+    scope::CoroutineScope,
+    scoped_join_handle::{Packet, ScopedJoinHandle},
+    stack_profile,
     sync::AtomicOption,
 };
 
@@ -16,6 +28,14 @@ pub struct CoroutineBuilder {
     stack_size: Option<usize>,
     /// Identifier for the coroutine
     id: Option<usize>,
+    /// Size the stack from the profile of the name
+    auto_stack_size: bool,
+}
+
+impl Default for CoroutineBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl CoroutineBuilder {
@@ -25,6 +45,7 @@ impl CoroutineBuilder {
             name: None,
             stack_size: None,
             id: None,
+            auto_stack_size: false,
         }
     }
 
@@ -42,6 +63,17 @@ impl CoroutineBuilder {
         self
     }
 
+    /// Size the stack from the usage measured for the coroutines of the same name
+    ///
+    /// The highest usage in the stack profile plus headroom is rounded up to a pooled size class.
+    /// Until a coroutine of the name was measured, or when the coroutine is unnamed, the stack
+    /// size set with `stack_size` or the default one is used.
+    pub fn auto_stack_size(mut self) -> Self {
+        self.auto_stack_size = true;
+
+        self
+    }
+
     /// Set the id of the coroutine
     pub fn id(mut self, id: usize) -> Self {
         self.id = Some(id);
@@ -53,32 +85,97 @@ impl CoroutineBuilder {
     /// `io::Result` to it's `JoinHandle`
     /// Spawned coroutine may outlive the caller. The join handle method can be used to block on
     /// termination of the child thread, including recovering it's panics.
+    ///
+    /// # Safety
+    ///
+    /// Thread local storage accessed from the coroutine may be shared with other coroutines
+    /// running on the same worker thread, and a blocking call blocks the whole worker.
     pub unsafe fn spawn<F, T>(self, f: F) -> io::Result<JoinHandle<T>>
     where
         T: Send + 'static,
         F: FnOnce() -> T + Send + 'static,
     {
-        unsafe {
-            let name = self.name;
-            let id = self.id;
-            let (coroutine, handle) = self.spawn_impl(f)?;
-            let scheduler = get_scheduler();
-
-            match id {
-                None => scheduler.schedule_global(coroutine),
-                Some(id) => scheduler.schedule_global_with_id(coroutine, id),
+        let id = self.id;
+        let (coroutine, handle) = self.spawn_impl(f)?;
+        let scheduler = get_scheduler();
+
+        match id {
+            None => scheduler.schedule_global(coroutine),
+            Some(id) => scheduler.schedule_global_with_id(coroutine, id),
+        }
+
+        Ok(handle)
+    }
+
+    /// Spawns a new coroutine in `scope`, it may borrow non-`'static` data from outside the scope
+    /// The scope joins the coroutine before it ends.
+    ///
+    /// # Safety
+    ///
+    /// Same as `spawn`.
+    pub unsafe fn spawn_scoped<'scope, F, T>(
+        self,
+        scope: &'scope CoroutineScope<'scope, '_>,
+        f: F,
+    ) -> io::Result<ScopedJoinHandle<'scope, T>>
+    where
+        T: Send + 'scope,
+        F: FnOnce() -> T + Send + 'scope,
+    {
+        let packet = Arc::new(Packet::new(scope.data().clone()));
+        let their_packet = packet.clone();
+
+        // The panic is kept with the result, so that the scope can tell if it was handled
+        let main: Box<dyn FnOnce() + Send + 'scope> = Box::new(move || {
+            their_packet.set(panic::catch_unwind(AssertUnwindSafe(f)));
+        });
+
+        // The scope waits for the packet to be dropped before it ends, so the borrowed data
+        // outlives the coroutine
+        let main = unsafe {
+            mem::transmute::<Box<dyn FnOnce() + Send + 'scope>, Box<dyn FnOnce() + Send + 'static>>(
+                main,
+            )
+        };
+
+        let handle = unsafe { self.spawn(main) }?;
+
+        Ok(ScopedJoinHandle::new(handle, packet))
+    }
+
+    /// Stack size in words of the coroutine to spawn
+    /// While profiling, the size is made odd so that the stack usage is measured
+    fn resolve_stack_size(&self) -> usize {
+        let mut stack_size = self.stack_size.unwrap_or_else(|| config().get_stack_size());
+
+        if self.auto_stack_size {
+            let suggested = self
+                .name
+                .as_deref()
+                .and_then(stack_profile::suggested_stack_size);
+
+            if let Some(suggested) = suggested {
+                stack_size = get_scheduler().pool.class_size(suggested);
             }
+        }
 
-            Ok(handle)
+        if stack_profile::is_enabled() {
+            stack_size |= 1;
         }
+
+        stack_size
     }
 
-    fn spawn_impl<F, T>(self, f: F) -> io::Result<(CoroutineImpl, JoinHandle<T>)> {
+    fn spawn_impl<F, T>(self, f: F) -> io::Result<(CoroutineImpl, JoinHandle<T>)>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
         static DONE: Done = Done {};
 
         let scheduler = get_scheduler();
+        let stack_size = self.resolve_stack_size();
         let name = self.name;
-        let stack_size = self.stack_size.unwrap_or_else(|| config().get_stack_size());
 
         // Create a join resource, shared by waited coroutine and *this* coroutine
         let panic = Arc::new(AtomicOption::none());
@@ -98,24 +195,18 @@ impl CoroutineBuilder {
             subscriber
         };
 
-        let mut coroutine = if stack_size == config().get_stack_size() {
-            let mut coroutine = scheduler.pool.get();
-
-            coroutine.init_code(closure);
+        let mut coroutine = scheduler.pool.get(stack_size);
 
-            coroutine
-        };
+        coroutine.init_code(closure);
 
-        let handle = Coroutine::new(name.into(), stack_size);
+        let handle = Coroutine::new(name, stack_size);
 
-        // Create the local storage
-        let local = CoroutineLocal::new(handle.clone(), join.clone());
+        // Create the local storage, the context is inherited from the spawner
+        let local = CoroutineLocal::new(handle.clone(), join.clone(), current_context());
 
         // Attach the local storage to the coroutine
         coroutine.set_local_data(Box::into_raw(local) as *mut u8);
 
-        scheduler.schedule(coroutine, id);
-
         Ok((coroutine, make_join_handle(handle, join, packet, panic)))
     }
 }
@@ -1,19 +1,22 @@
 use std::{
-    io,
+    io, panic,
     sync::{
         Arc,
         atomic::{AtomicUsize, Ordering},
     },
 };
 
-use crate::{sync::AtomicOption, unlikely::unlikely, yield_now::get_coroutine_para};
+use crate::{
+    CoroutineImpl, current_cancel_data, error::Error, io::IoWaitHandle, is_coroutine,
+    scheduler::get_scheduler, sync::AtomicOption, unlikely::unlikely,
+    yield_now::get_coroutine_para,
+};
 
-pub trait CancelIo {
+pub(crate) trait CancelIo {
     type Data;
 
     fn new() -> Self;
 
-    #[allow(dead_code)]
     fn set(&self, io_data: Self::Data);
 
     fn clear(&self);
@@ -21,21 +24,31 @@ pub trait CancelIo {
     unsafe fn cancel(&self) -> Option<io::Result<()>>;
 }
 
-pub struct CancelIoImpl;
+/// The descriptor wait of a suspended coroutine
+pub(crate) struct CancelIoImpl {
+    io: AtomicOption<IoWaitHandle>,
+}
 
 impl CancelIo for CancelIoImpl {
-    type Data = ();
+    type Data = IoWaitHandle;
 
     fn new() -> CancelIoImpl {
-        CancelIoImpl
+        CancelIoImpl {
+            io: AtomicOption::none(),
+        }
     }
 
-    fn set(&self, _: Self::Data) {}
+    fn set(&self, io_data: Self::Data) {
+        self.io.store(io_data);
+    }
 
-    fn clear(&self) {}
+    fn clear(&self) {
+        self.io.take();
+    }
 
+    // Take the coroutine out of the descriptor and resume it with an error
     unsafe fn cancel(&self) -> Option<io::Result<()>> {
-        None
+        Some(self.io.take()?.cancel())
     }
 }
 
@@ -74,11 +87,6 @@ impl<T: CancelIo> CancelImpl<T> {
         self.state.load(Ordering::Acquire) == 1
     }
 
-    // Check if the coroutine cancel is disabled
-    pub fn is_disabled(&self) -> bool {
-        self.state.load(Ordering::Acquire) >= 2
-    }
-
     // Disabled the cancel bit
     pub fn disable_cancel(&self) {
         self.state.fetch_add(2, Ordering::Release);
@@ -95,14 +103,38 @@ impl<T: CancelIo> CancelImpl<T> {
             // Before panic clear the last coroutine error
             // This would affect future new coroutine that reuse the instance
             get_coroutine_para();
+
+            panic::panic_any(Error::Cancel);
         }
     }
 
+    // Register the descriptor wait of the suspended coroutine
+    pub fn set_io(&self, io_data: T::Data) {
+        self.io.set(io_data);
+    }
+
+    // Register the park based wait of the suspended coroutine
+    pub fn set_coroutine(&self, coroutine: Arc<AtomicOption<CoroutineImpl>>) {
+        self.coroutine.store(coroutine);
+    }
+
+    // Clear the registered wait once the coroutine is running again
+    // Both kinds are cleared, whichever was registered and even if a wake resumed the coroutine
+    // before its wait could be cancelled
+    pub fn clear(&self) {
+        self.coroutine.take();
+        self.io.clear();
+    }
+
     // Cancel for coroutine
+    // While the cancel is disabled the flag is only recorded, the coroutine unwinds at its first
+    // suspension after the cancel is enabled again
     #[cold]
     pub unsafe fn cancel(&self) {
         unsafe {
-            self.state.fetch_or(1, Ordering::Release);
+            if self.state.fetch_or(1, Ordering::AcqRel) >= 2 {
+                return;
+            }
 
             if let Some(Ok(())) = self.io.cancel() {
                 // Successfully cancelled
@@ -113,10 +145,7 @@ impl<T: CancelIo> CancelImpl<T> {
                 if let Some(mut coroutine) = coroutine.take() {
                     // This is not safe. Kernel may still need to use the overlapped
                     // Set the Cancel result for the coroutine
-                    set_coroutine_parameter(
-                        &mut coroutine,
-                        io::Error::new(io::ErrorKind::Other, "Cancelled"),
-                    );
+                    coroutine.set_para(io::Error::other("Cancelled"));
                     get_scheduler().schedule(coroutine);
                 }
             }
@@ -125,3 +154,65 @@ impl<T: CancelIo> CancelImpl<T> {
 }
 
 pub type Cancel = CancelImpl<CancelIoImpl>;
+
+/// Runs `f` with the cancel of the running coroutine disabled
+///
+/// A cancel requested meanwhile doesn't interrupt `f`, the coroutine unwinds once `f` returns.
+/// Outside of coroutines `f` just runs.
+pub fn disable_cancel<F, R>(f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    if !is_coroutine() {
+        return f();
+    }
+
+    struct Enable(&'static Cancel);
+
+    impl Drop for Enable {
+        fn drop(&mut self) {
+            self.0.enable_cancel();
+        }
+    }
+
+    let cancel = current_cancel_data();
+
+    cancel.disable_cancel();
+
+    let ret = {
+        let _enable = Enable(cancel);
+
+        f()
+    };
+
+    cancel.check_cancel();
+
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Read, time::Duration};
+
+    use crate::{net::UnixStream, sleep, spawn};
+
+    #[test]
+    fn cancel_interrupts_blocked_read() {
+        let (mut a, _b) = UnixStream::pair().unwrap();
+
+        let handle = unsafe {
+            spawn(move || {
+                let mut buf = [0; 8];
+
+                a.read(&mut buf)
+            })
+        };
+
+        // Let the coroutine block in the read
+        sleep(Duration::from_millis(20));
+
+        unsafe { handle.coroutine().cancel() };
+
+        assert!(handle.join().unwrap_err().is_cancelled());
+    }
+}
@@ -0,0 +1,462 @@
+use std::{env, mem, sync::OnceLock, thread, time::Duration};
+
+use crate::{config_error::ConfigError, stack::max_stack_size};
+
+/// Default coroutine stack size in words, 32 KiB on 64 bit targets
+const DEFAULT_STACK_SIZE: usize = 0x1000;
+
+/// Default number of idle coroutines kept for reuse
+const DEFAULT_POOL_CAPACITY: usize = 1000;
+
+/// Default upper bound of a single reactor poll
+const DEFAULT_IO_POLL_TIMEOUT: Duration = Duration::from_millis(10);
+
+/// Default number of protected pages below every coroutine stack
+const DEFAULT_STACK_GUARD_PAGES: usize = 1;
+
+/// Default maximum number of threads running blocking calls
+const DEFAULT_BLOCKING_THREADS: usize = 512;
+
+/// Default time a blocking thread waits for a new call before it exits
+const DEFAULT_BLOCKING_IDLE_TIMEOUT: Duration = Duration::from_secs(10);
+
+const MAX_WORKERS: usize = 1024;
+const MIN_STACK_SIZE: usize = 0x100;
+const MAX_POOL_CAPACITY: usize = 1 << 20;
+const MIN_IO_POLL_TIMEOUT: Duration = Duration::from_millis(1);
+const MAX_IO_POLL_TIMEOUT: Duration = Duration::from_secs(60);
+const MAX_STACK_GUARD_PAGES: usize = 64;
+const MAX_BLOCKING_THREADS: usize = 1 << 16;
+const MIN_BLOCKING_IDLE_TIMEOUT: Duration = Duration::from_millis(1);
+const MAX_BLOCKING_IDLE_TIMEOUT: Duration = Duration::from_secs(3600);
+
+const WORKERS_ENV: &str = "COROUTINE_WORKERS";
+const STACK_SIZE_ENV: &str = "COROUTINE_STACK_SIZE";
+const POOL_CAPACITY_ENV: &str = "COROUTINE_POOL_CAPACITY";
+const IO_POLL_TIMEOUT_ENV: &str = "COROUTINE_IO_POLL_TIMEOUT_MS";
+const STACK_GUARD_PAGES_ENV: &str = "COROUTINE_STACK_GUARD_PAGES";
+const REGISTRY_ENV: &str = "COROUTINE_REGISTRY";
+const STACK_PROFILING_ENV: &str = "COROUTINE_STACK_PROFILING";
+const BLOCKING_THREADS_ENV: &str = "COROUTINE_BLOCKING_THREADS";
+const BLOCKING_IDLE_TIMEOUT_ENV: &str = "COROUTINE_BLOCKING_IDLE_TIMEOUT_MS";
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Get the runtime configuration
+/// When no configuration was installed with `ConfigBuilder::init`, the defaults with the
+/// environment overrides are used. An invalid environment panics here, call
+/// `ConfigBuilder::init` first to handle it as an error instead.
+#[inline]
+pub fn config() -> &'static Config {
+    CONFIG.get_or_init(|| match ConfigBuilder::new().build() {
+        Ok(config) => config,
+        Err(err) => panic!("Invalid coroutine runtime configuration: {}", err),
+    })
+}
+
+/// Runtime configuration
+///
+/// The configuration is fixed the first time the runtime reads it, which at the latest happens
+/// when the first coroutine is spawned. Every setting can be overridden with an environment
+/// variable, which takes precedence over the value set in code:
+///
+/// | Setting                 | Environment variable                 |
+/// |-------------------------|--------------------------------------|
+/// | `workers`               | `COROUTINE_WORKERS`                  |
+/// | `stack_size`            | `COROUTINE_STACK_SIZE`               |
+/// | `pool_capacity`         | `COROUTINE_POOL_CAPACITY`            |
+/// | `io_poll_timeout`       | `COROUTINE_IO_POLL_TIMEOUT_MS`       |
+/// | `stack_guard_pages`     | `COROUTINE_STACK_GUARD_PAGES`        |
+/// | `registry`              | `COROUTINE_REGISTRY`                 |
+/// | `stack_profiling`       | `COROUTINE_STACK_PROFILING`          |
+/// | `blocking_threads`      | `COROUTINE_BLOCKING_THREADS`         |
+/// | `blocking_idle_timeout` | `COROUTINE_BLOCKING_IDLE_TIMEOUT_MS` |
+///
+/// Numbers can be written in decimal or in hexadecimal with a `0x` prefix, flags are 0 or 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    workers: usize,
+    stack_size: usize,
+    pool_capacity: usize,
+    io_poll_timeout: Duration,
+    stack_guard_pages: usize,
+    registry: bool,
+    stack_profiling: bool,
+    blocking_threads: usize,
+    blocking_idle_timeout: Duration,
+}
+
+impl Config {
+    /// Creates a builder to configure the runtime
+    pub fn builder() -> ConfigBuilder {
+        ConfigBuilder::new()
+    }
+
+    /// Number of worker threads running coroutines
+    #[inline]
+    pub fn get_workers(&self) -> usize {
+        self.workers
+    }
+
+    /// Default coroutine stack size in words
+    #[inline]
+    pub fn get_stack_size(&self) -> usize {
+        self.stack_size
+    }
+
+    /// Maximum number of idle coroutines kept for reuse
+    #[inline]
+    pub fn get_pool_capacity(&self) -> usize {
+        self.pool_capacity
+    }
+
+    /// Maximum time a worker blocks waiting for I/O events
+    #[inline]
+    pub fn get_io_poll_timeout(&self) -> Duration {
+        self.io_poll_timeout
+    }
+
+    /// Number of protected pages below every coroutine stack
+    #[inline]
+    pub fn get_stack_guard_pages(&self) -> usize {
+        self.stack_guard_pages
+    }
+
+    /// The live coroutines are recorded in the registry, see `registry::dump`
+    #[inline]
+    pub fn is_registry_enabled(&self) -> bool {
+        self.registry
+    }
+
+    /// The stack usage of every coroutine is measured, see `stack_profile`
+    #[inline]
+    pub fn is_stack_profiling_enabled(&self) -> bool {
+        self.stack_profiling
+    }
+
+    /// Maximum number of threads running the calls of `spawn_blocking`
+    #[inline]
+    pub fn get_blocking_threads(&self) -> usize {
+        self.blocking_threads
+    }
+
+    /// Time a blocking thread waits for a new call before it exits
+    #[inline]
+    pub fn get_blocking_idle_timeout(&self) -> Duration {
+        self.blocking_idle_timeout
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        let max_stack_size = max_stack_size() / mem::size_of::<usize>();
+
+        check_range("workers", self.workers as u64, 1, MAX_WORKERS as u64)?;
+        check_range(
+            "stack_size",
+            self.stack_size as u64,
+            MIN_STACK_SIZE as u64,
+            max_stack_size as u64,
+        )?;
+        check_range(
+            "pool_capacity",
+            self.pool_capacity as u64,
+            0,
+            MAX_POOL_CAPACITY as u64,
+        )?;
+        check_range(
+            "io_poll_timeout_ms",
+            self.io_poll_timeout.as_millis() as u64,
+            MIN_IO_POLL_TIMEOUT.as_millis() as u64,
+            MAX_IO_POLL_TIMEOUT.as_millis() as u64,
+        )?;
+        check_range(
+            "stack_guard_pages",
+            self.stack_guard_pages as u64,
+            1,
+            MAX_STACK_GUARD_PAGES as u64,
+        )?;
+        check_range(
+            "blocking_threads",
+            self.blocking_threads as u64,
+            1,
+            MAX_BLOCKING_THREADS as u64,
+        )?;
+        check_range(
+            "blocking_idle_timeout_ms",
+            self.blocking_idle_timeout.as_millis() as u64,
+            MIN_BLOCKING_IDLE_TIMEOUT.as_millis() as u64,
+            MAX_BLOCKING_IDLE_TIMEOUT.as_millis() as u64,
+        )
+    }
+}
+
+/// Runtime configuration builder, used to configure the runtime before it starts
+#[derive(Debug, Clone, Default)]
+pub struct ConfigBuilder {
+    workers: Option<usize>,
+    stack_size: Option<usize>,
+    pool_capacity: Option<usize>,
+    io_poll_timeout: Option<Duration>,
+    stack_guard_pages: Option<usize>,
+    registry: Option<bool>,
+    stack_profiling: Option<bool>,
+    blocking_threads: Option<usize>,
+    blocking_idle_timeout: Option<Duration>,
+}
+
+impl ConfigBuilder {
+    /// Generates a base configuration, every unset value uses its default
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the number of worker threads, defaults to the available parallelism
+    pub fn workers(mut self, workers: usize) -> Self {
+        self.workers = Some(workers);
+
+        self
+    }
+
+    /// Set the default coroutine stack size in words
+    /// An odd size makes every coroutine measure its actual stack usage
+    pub fn stack_size(mut self, stack_size: usize) -> Self {
+        self.stack_size = Some(stack_size);
+
+        self
+    }
+
+    /// Set the maximum number of idle coroutines kept for reuse
+    pub fn pool_capacity(mut self, pool_capacity: usize) -> Self {
+        self.pool_capacity = Some(pool_capacity);
+
+        self
+    }
+
+    /// Set the maximum time a worker blocks waiting for I/O events
+    pub fn io_poll_timeout(mut self, io_poll_timeout: Duration) -> Self {
+        self.io_poll_timeout = Some(io_poll_timeout);
+
+        self
+    }
+
+    /// Set the number of protected pages below every coroutine stack
+    pub fn stack_guard_pages(mut self, stack_guard_pages: usize) -> Self {
+        self.stack_guard_pages = Some(stack_guard_pages);
+
+        self
+    }
+
+    /// Record the live coroutines in the registry, disabled by default
+    pub fn registry(mut self, registry: bool) -> Self {
+        self.registry = Some(registry);
+
+        self
+    }
+
+    /// Measure the stack usage of every coroutine, disabled by default
+    /// The stacks are filled when they are allocated or reused, which makes spawning slower
+    pub fn stack_profiling(mut self, stack_profiling: bool) -> Self {
+        self.stack_profiling = Some(stack_profiling);
+
+        self
+    }
+
+    /// Set the maximum number of threads running blocking calls
+    pub fn blocking_threads(mut self, blocking_threads: usize) -> Self {
+        self.blocking_threads = Some(blocking_threads);
+
+        self
+    }
+
+    /// Set the time a blocking thread waits for a new call before it exits
+    pub fn blocking_idle_timeout(mut self, blocking_idle_timeout: Duration) -> Self {
+        self.blocking_idle_timeout = Some(blocking_idle_timeout);
+
+        self
+    }
+
+    /// Apply the environment overrides and validate the configuration
+    pub fn build(self) -> Result<Config, ConfigError> {
+        self.build_with_env(|var| env::var(var).ok())
+    }
+
+    /// Build the configuration and install it for the runtime
+    /// Fails if the runtime already read its configuration
+    pub fn init(self) -> Result<(), ConfigError> {
+        let config = self.build()?;
+
+        CONFIG
+            .set(config)
+            .map_err(|_| ConfigError::AlreadyInitialized)
+    }
+
+    fn build_with_env<F>(mut self, lookup: F) -> Result<Config, ConfigError>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        if let Some(workers) = env_value(&lookup, WORKERS_ENV)? {
+            self.workers = Some(workers as usize);
+        }
+
+        if let Some(stack_size) = env_value(&lookup, STACK_SIZE_ENV)? {
+            self.stack_size = Some(stack_size as usize);
+        }
+
+        if let Some(pool_capacity) = env_value(&lookup, POOL_CAPACITY_ENV)? {
+            self.pool_capacity = Some(pool_capacity as usize);
+        }
+
+        if let Some(ms) = env_value(&lookup, IO_POLL_TIMEOUT_ENV)? {
+            self.io_poll_timeout = Some(Duration::from_millis(ms));
+        }
+
+        if let Some(pages) = env_value(&lookup, STACK_GUARD_PAGES_ENV)? {
+            self.stack_guard_pages = Some(pages as usize);
+        }
+
+        if let Some(registry) = env_value(&lookup, REGISTRY_ENV)? {
+            check_range("registry", registry, 0, 1)?;
+
+            self.registry = Some(registry == 1);
+        }
+
+        if let Some(profiling) = env_value(&lookup, STACK_PROFILING_ENV)? {
+            check_range("stack_profiling", profiling, 0, 1)?;
+
+            self.stack_profiling = Some(profiling == 1);
+        }
+
+        if let Some(threads) = env_value(&lookup, BLOCKING_THREADS_ENV)? {
+            self.blocking_threads = Some(threads as usize);
+        }
+
+        if let Some(ms) = env_value(&lookup, BLOCKING_IDLE_TIMEOUT_ENV)? {
+            self.blocking_idle_timeout = Some(Duration::from_millis(ms));
+        }
+
+        let config = Config {
+            workers: self
+                .workers
+                .unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get())),
+            stack_size: self.stack_size.unwrap_or(DEFAULT_STACK_SIZE),
+            pool_capacity: self.pool_capacity.unwrap_or(DEFAULT_POOL_CAPACITY),
+            io_poll_timeout: self.io_poll_timeout.unwrap_or(DEFAULT_IO_POLL_TIMEOUT),
+            stack_guard_pages: self.stack_guard_pages.unwrap_or(DEFAULT_STACK_GUARD_PAGES),
+            registry: self.registry.unwrap_or(false),
+            stack_profiling: self.stack_profiling.unwrap_or(false),
+            blocking_threads: self.blocking_threads.unwrap_or(DEFAULT_BLOCKING_THREADS),
+            blocking_idle_timeout: self
+                .blocking_idle_timeout
+                .unwrap_or(DEFAULT_BLOCKING_IDLE_TIMEOUT),
+        };
+
+        config.validate()?;
+
+        Ok(config)
+    }
+}
+
+fn env_value<F>(lookup: &F, var: &'static str) -> Result<Option<u64>, ConfigError>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let Some(value) = lookup(var) else {
+        return Ok(None);
+    };
+
+    let trimmed = value.trim();
+
+    let number = match trimmed
+        .strip_prefix("0x")
+        .or_else(|| trimmed.strip_prefix("0X"))
+    {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => trimmed.parse().ok(),
+    };
+
+    match number {
+        Some(number) => Ok(Some(number)),
+        None => Err(ConfigError::InvalidEnv { var, value }),
+    }
+}
+
+fn check_range(name: &'static str, value: u64, min: u64, max: u64) -> Result<(), ConfigError> {
+    if (min..=max).contains(&value) {
+        Ok(())
+    } else {
+        Err(ConfigError::OutOfRange {
+            name,
+            value,
+            min,
+            max,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn no_env(_: &str) -> Option<String> {
+        None
+    }
+
+    #[test]
+    fn defaults_are_valid() {
+        let config = ConfigBuilder::new().build_with_env(no_env).unwrap();
+
+        assert_eq!(config.get_stack_size(), DEFAULT_STACK_SIZE);
+        assert_eq!(config.get_pool_capacity(), DEFAULT_POOL_CAPACITY);
+        assert_eq!(config.get_stack_guard_pages(), DEFAULT_STACK_GUARD_PAGES);
+        assert!(config.get_workers() >= 1);
+    }
+
+    #[test]
+    fn env_overrides_programmatic_values() {
+        let config = ConfigBuilder::new()
+            .workers(2)
+            .stack_size(0x2000)
+            .build_with_env(|var| match var {
+                WORKERS_ENV => Some("8".to_string()),
+                STACK_SIZE_ENV => Some(" 0x4000 ".to_string()),
+                _ => None,
+            })
+            .unwrap();
+
+        assert_eq!(config.get_workers(), 8);
+        assert_eq!(config.get_stack_size(), 0x4000);
+
+        let config = ConfigBuilder::new()
+            .build_with_env(|var| (var == REGISTRY_ENV).then(|| "1".to_string()))
+            .unwrap();
+
+        assert!(config.is_registry_enabled());
+    }
+
+    #[test]
+    fn invalid_values_are_rejected() {
+        let err = ConfigBuilder::new()
+            .workers(0)
+            .build_with_env(no_env)
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            ConfigError::OutOfRange {
+                name: "workers",
+                ..
+            }
+        ));
+
+        let err = ConfigBuilder::new()
+            .build_with_env(|var| (var == IO_POLL_TIMEOUT_ENV).then(|| "ten".to_string()))
+            .unwrap_err();
+
+        assert_eq!(
+            err,
+            ConfigError::InvalidEnv {
+                var: IO_POLL_TIMEOUT_ENV,
+                value: "ten".to_string()
+            }
+        );
+    }
+}
@@ -0,0 +1,50 @@
+use std::{error::Error, fmt, io};
+
+/// Error returned when the runtime configuration can't be applied
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    /// The configuration was already read by the runtime and can no longer change
+    AlreadyInitialized,
+
+    /// A setting is outside of its accepted range
+    OutOfRange {
+        name: &'static str,
+        value: u64,
+        min: u64,
+        max: u64,
+    },
+
+    /// An environment variable holds a value that is not a valid number
+    InvalidEnv { var: &'static str, value: String },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            ConfigError::AlreadyInitialized => {
+                f.write_str("Configuration is already in use by the runtime")
+            }
+            ConfigError::OutOfRange {
+                name,
+                value,
+                min,
+                max,
+            } => write!(
+                f,
+                "Invalid {} = {}, expected a value between {} and {}",
+                name, value, min, max
+            ),
+            ConfigError::InvalidEnv { var, ref value } => {
+                write!(f, "Invalid environment variable {} = {:?}", var, value)
+            }
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+impl From<ConfigError> for io::Error {
+    fn from(err: ConfigError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidInput, err)
+    }
+}
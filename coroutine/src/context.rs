@@ -0,0 +1,111 @@
+//! Values inherited by the spawned coroutines
+//!
+//! Each coroutine carries a context map, keyed by the type of the values. A spawned coroutine
+//! starts with the context of the coroutine or the thread spawning it, so that request ids,
+//! deadlines or logging fields follow the work into the children. The map is shared immutably, a
+//! value is only overridden for the duration of a `scope`.
+
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    hash::BuildHasherDefault,
+    sync::Arc,
+};
+
+use crate::{coroutine_local, id_hasher::IdHasher};
+
+/// The context values of a coroutine or a thread
+#[derive(Clone, Default)]
+pub(crate) struct ContextMap {
+    values: HashMap<TypeId, Arc<dyn Any + Send + Sync>, BuildHasherDefault<IdHasher>>,
+}
+
+impl ContextMap {
+    fn get<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.values
+            .get(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_ref())
+    }
+
+    fn insert<T: Send + Sync + 'static>(&mut self, value: T) {
+        self.values.insert(TypeId::of::<T>(), Arc::new(value));
+    }
+}
+
+/// Restores the overridden context at the end of a `scope`, even when it unwinds
+struct Restore(Option<Arc<ContextMap>>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        if let Some(context) = self.0.take() {
+            coroutine_local::replace_context(context);
+        }
+    }
+}
+
+/// Returns a clone of the context value of type `T`
+pub fn get<T: Clone + Send + Sync + 'static>() -> Option<T> {
+    with(|value: Option<&T>| value.cloned())
+}
+
+/// Runs `f` with a reference to the context value of type `T`
+pub fn with<T, F, R>(f: F) -> R
+where
+    T: Send + Sync + 'static,
+    F: FnOnce(Option<&T>) -> R,
+{
+    // Don't borrow the context while `f` runs, it may override it
+    let context = coroutine_local::current_context();
+
+    f(context.get())
+}
+
+/// Runs `f` with `value` as the context value of type `T`
+/// The coroutines spawned meanwhile inherit the value, the previous one is restored on return
+pub fn scope<T, F, R>(value: T, f: F) -> R
+where
+    T: Send + Sync + 'static,
+    F: FnOnce() -> R,
+{
+    let mut context = (*coroutine_local::current_context()).clone();
+
+    context.insert(value);
+
+    let _restore = Restore(Some(coroutine_local::replace_context(Arc::new(context))));
+
+    f()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spawn;
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct RequestId(u64);
+
+    #[test]
+    fn children_inherit_the_context() {
+        assert_eq!(get::<RequestId>(), None);
+
+        let handle = scope(RequestId(1), || {
+            let child = || {
+                // An override in the child is seen by its own children only
+                let grandchild = scope(RequestId(2), || {
+                    unsafe { spawn(get::<RequestId>) }.join().unwrap()
+                });
+
+                (get::<RequestId>(), grandchild)
+            };
+
+            unsafe { spawn(child) }
+        });
+
+        assert_eq!(get::<RequestId>(), None);
+
+        assert_eq!(
+            handle.join().unwrap(),
+            (Some(RequestId(1)), Some(RequestId(2)))
+        );
+    }
+}
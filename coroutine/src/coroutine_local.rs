@@ -3,7 +3,10 @@ use std::{
     sync::Arc,
 };
 
-use crate::{Coroutine, id_hasher::IdHasher, join::Join, runtime::get_local_data};
+use crate::{
+    Coroutine, CoroutineImpl, context::ContextMap, id_hasher::IdHasher, join::Join,
+    runtime::get_local_data,
+};
 
 pub(crate) trait Opaque {}
 
@@ -13,6 +16,8 @@ pub(crate) type LocalMap = RefCell<HashMap<TypeId, Box<dyn Opaque>, BuildHasherD
 
 thread_local! { static LOCALMAP: LocalMap = RefCell::new(HashMap::default()); }
 
+thread_local! { static CONTEXT: RefCell<Arc<ContextMap>> = RefCell::default(); }
+
 /// Coroutine local storage
 pub struct CoroutineLocal {
     // Current coroutine handle
@@ -23,15 +28,23 @@ pub struct CoroutineLocal {
 
     // Real local data hashmap
     local_data: LocalMap,
+
+    // Context inherited from the spawner, passed on to the spawned coroutines
+    context: RefCell<Arc<ContextMap>>,
 }
 
 impl CoroutineLocal {
     /// Create new coroutine local storage
-    pub fn new(coroutine: Coroutine, join: Arc<Join>) -> Box<CoroutineLocal> {
+    pub fn new(
+        coroutine: Coroutine,
+        join: Arc<Join>,
+        context: Arc<ContextMap>,
+    ) -> Box<CoroutineLocal> {
         Box::new(CoroutineLocal {
             coroutine,
             join,
             local_data: RefCell::new(HashMap::default()),
+            context: RefCell::new(context),
         })
     }
 
@@ -44,6 +57,23 @@ impl CoroutineLocal {
     pub fn get_join(&self) -> Arc<Join> {
         self.join.clone()
     }
+
+    /// Drop the coroutine local values
+    /// The map is emptied first, so that the destructors never see it borrowed
+    pub(crate) fn drop_local_data(&self) {
+        let local_data = std::mem::take(&mut *self.local_data.borrow_mut());
+
+        drop(local_data);
+    }
+}
+
+/// Get the local storage attached to a coroutine
+#[inline]
+pub(crate) fn get_coroutine_local(coroutine: &CoroutineImpl) -> *mut CoroutineLocal {
+    #[allow(clippy::cast_ptr_alignment)]
+    let local = coroutine.get_local_data() as *mut CoroutineLocal;
+
+    local
 }
 
 #[inline]
@@ -54,8 +84,10 @@ pub fn get_coroutine_local_data() -> Option<NonNull<CoroutineLocal>> {
     NonNull::new(ptr as *mut CoroutineLocal)
 }
 
+/// Run `f` with the local map of the current coroutine, or of the current thread out of
+/// coroutines
 #[inline]
-fn with<F, R>(f: F) -> R
+pub(crate) fn with<F, R>(f: F) -> R
 where
     F: FnOnce(&LocalMap) -> R,
 {
@@ -64,3 +96,26 @@ where
         None => LOCALMAP.with(|data| f(data)),
     }
 }
+
+#[inline]
+fn with_context<F, R>(f: F) -> R
+where
+    F: FnOnce(&RefCell<Arc<ContextMap>>) -> R,
+{
+    match get_coroutine_local_data() {
+        Some(c_local) => f(&unsafe { c_local.as_ref() }.context),
+        None => CONTEXT.with(|context| f(context)),
+    }
+}
+
+/// Get the context of the current coroutine, or of the current thread out of coroutines
+#[inline]
+pub(crate) fn current_context() -> Arc<ContextMap> {
+    with_context(|context| context.borrow().clone())
+}
+
+/// Set the context of the current coroutine or thread, returns the previous one
+#[inline]
+pub(crate) fn replace_context(new: Arc<ContextMap>) -> Arc<ContextMap> {
+    with_context(|context| context.replace(new))
+}
@@ -1,33 +1,71 @@
+use std::{
+    panic::{self, AssertUnwindSafe},
+    thread,
+};
+
 use log::{debug, error};
 
-use crate::event::EventSource;
+use crate::{
+    CoroutineImpl, coroutine_local::get_coroutine_local, event::EventSource, registry,
+    scheduler::get_scheduler, stack_profile,
+};
 
 pub struct Done;
 
 impl Done {
     pub(crate) fn drop_coroutine(coroutine: CoroutineImpl) {
-        let local = unsafe { Box::from_raw(get_local_coroutine(&coroutine)) };
+        Done::release(coroutine, false);
+    }
+
+    /// Drop a coroutine terminated by a stack overflow, its stack is discarded
+    pub(crate) fn drop_overflowed(coroutine: CoroutineImpl) {
+        Done::release(coroutine, true);
+    }
+
+    fn release(coroutine: CoroutineImpl, overflowed: bool) {
+        let local = unsafe { Box::from_raw(get_coroutine_local(&coroutine)) };
         let name = local.get_coroutine().name();
 
+        registry::unregister(local.get_coroutine().id());
+
+        // Run the destructors of the coroutine local values, a panic must not take the worker down
+        // They run out of the coroutine, see `coroutine_local!` for what they can't do
+        if panic::catch_unwind(AssertUnwindSafe(|| local.drop_local_data())).is_err() {
+            error!("Coroutine local destructor panicked, name = {:?}", name);
+        }
+
         // Recycle the coroutine
         let (size, used) = coroutine.stack_usage();
+        let tracked = local.get_coroutine().stack_size() & 1 == 1;
+
+        if tracked && stack_profile::is_enabled() {
+            stack_profile::record(name, if overflowed { size } else { used });
+        }
 
-        if used == size {
-            error!("Stack overflow detected, size = {}", size);
-            std::process::exit(1);
+        // The frames abandoned on an overflowed stack are never unwound, the stack is unmapped
+        // along with the coroutine rather than recycled
+        if overflowed || used == size {
+            error!(
+                "Stack overflow detected, name = {:?}, size = {}, thread = {:?}",
+                name,
+                size,
+                thread::current().name()
+            );
+
+            return;
         }
 
         // Show the actual used stack size in debug log
-        if local.get_coroutine().stack_size() & 1 == 1 {
+        if tracked {
             debug!(
                 "Coroutine name = {:?}, stack size = {}, used size = {}",
                 name, size, used
             );
         }
 
-        if size == config().get_stack_size() {
-            get_scheduler().pool.put(coroutine);
-        }
+        get_scheduler()
+            .pool
+            .put(coroutine, local.get_coroutine().stack_size());
     }
 }
 
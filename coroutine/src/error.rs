@@ -8,6 +8,4 @@ pub enum Error {
     TypeErr,
     /// Stack overflow panic
     StackErr,
-    /// Wrong context panic
-    ContextErr,
 }
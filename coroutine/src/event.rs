@@ -1,12 +1,16 @@
 use std::io;
 
-use crate::cancel::Cancel;
+use crate::{CoroutineImpl, cancel::Cancel, registry};
 
 pub type EventResult = io::Error;
 
 pub trait EventSource {
     /// Kernel handler of the Event
     fn subscribe(&mut self, coroutine_impl: CoroutineImpl);
+    /// What a coroutine suspended on the event waits for, shown by the registry
+    fn reason(&self) -> &'static str {
+        "event"
+    }
     /// After yield back process
     fn yield_back(&self, cancel: &'static Cancel) {
         // After return back we should re-check the panic and clear it
@@ -19,3 +23,15 @@ pub struct EventSubscriber {
 }
 
 unsafe impl Send for EventSubscriber {}
+
+impl EventSubscriber {
+    /// Hand the suspended coroutine over to the event source it yielded
+    #[inline]
+    pub fn subscribe(self, coroutine: CoroutineImpl) {
+        let resource = unsafe { &mut *self.resource };
+
+        registry::park(&coroutine, resource.reason());
+
+        resource.subscribe(coroutine);
+    }
+}
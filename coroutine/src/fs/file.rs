@@ -0,0 +1,193 @@
+use std::{
+    fmt, fs,
+    io::{self, Read, Seek, SeekFrom, Write},
+    os::unix::fs::FileExt,
+    path::Path,
+    sync::Arc,
+};
+
+use bytes::{Bytes, BytesMut};
+
+use crate::spawn_blocking;
+
+/// An open file whose operations suspend the running coroutine
+///
+/// Every operation runs on the blocking pool, sharing the std file with it. Reads return the data
+/// they got as `Bytes`, writes take it as `Bytes`. Like the std file, the reads and writes move
+/// the cursor, while `read_at` and `write_all_at` leave it untouched.
+pub struct File {
+    std: Arc<fs::File>,
+}
+
+impl File {
+    /// Opens a file in read-only mode
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<File> {
+        let path = path.as_ref().to_owned();
+
+        spawn_blocking(move || fs::File::open(path))?.map(File::from_std)
+    }
+
+    /// Opens a file in write-only mode, creating or truncating it
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<File> {
+        let path = path.as_ref().to_owned();
+
+        spawn_blocking(move || fs::File::create(path))?.map(File::from_std)
+    }
+
+    /// Opens a file with the given options, e.g. to append to it
+    pub fn open_with<P: AsRef<Path>>(path: P, options: &fs::OpenOptions) -> io::Result<File> {
+        let path = path.as_ref().to_owned();
+        let options = options.clone();
+
+        spawn_blocking(move || options.open(path))?.map(File::from_std)
+    }
+
+    /// Wrap a std file
+    pub fn from_std(std: fs::File) -> File {
+        File { std: Arc::new(std) }
+    }
+
+    /// Reads at most `len` bytes at the cursor, the result is empty at the end of the file
+    pub fn read(&mut self, len: usize) -> io::Result<Bytes> {
+        self.blocking(move |mut file| {
+            let mut buf = BytesMut::zeroed(len);
+            let n = file.read(&mut buf)?;
+
+            buf.truncate(n);
+
+            Ok(buf.freeze())
+        })
+    }
+
+    /// Reads from the cursor to the end of the file
+    pub fn read_to_end(&mut self) -> io::Result<Bytes> {
+        self.blocking(super::read_to_end)
+    }
+
+    /// Reads at most `len` bytes at `offset` without moving the cursor
+    /// The result is shorter only at the end of the file
+    pub fn read_at(&self, offset: u64, len: usize) -> io::Result<Bytes> {
+        self.blocking(move |file| {
+            let mut buf = BytesMut::zeroed(len);
+            let mut filled = 0;
+
+            while filled < len {
+                match file.read_at(&mut buf[filled..], offset + filled as u64) {
+                    Ok(0) => break,
+                    Ok(n) => filled += n,
+                    Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                    Err(err) => return Err(err),
+                }
+            }
+
+            buf.truncate(filled);
+
+            Ok(buf.freeze())
+        })
+    }
+
+    /// Writes all of `data` at the cursor
+    pub fn write_all<B: Into<Bytes>>(&mut self, data: B) -> io::Result<()> {
+        let data = data.into();
+
+        self.blocking(move |mut file| file.write_all(&data))
+    }
+
+    /// Writes all of `data` at `offset` without moving the cursor
+    pub fn write_all_at<B: Into<Bytes>>(&self, data: B, offset: u64) -> io::Result<()> {
+        let data = data.into();
+
+        self.blocking(move |file| file.write_all_at(&data, offset))
+    }
+
+    /// Moves the cursor, returns its new position from the start of the file
+    pub fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.blocking(move |mut file| file.seek(pos))
+    }
+
+    /// Flushes the data and the metadata to the disk
+    pub fn sync_all(&self) -> io::Result<()> {
+        self.blocking(|file| file.sync_all())
+    }
+
+    /// Flushes the data to the disk, the metadata only as needed to read it back
+    pub fn sync_data(&self) -> io::Result<()> {
+        self.blocking(|file| file.sync_data())
+    }
+
+    /// Truncates or extends the file to `size` bytes
+    pub fn set_len(&self, size: u64) -> io::Result<()> {
+        self.blocking(move |file| file.set_len(size))
+    }
+
+    /// Queries the metadata of the file
+    pub fn metadata(&self) -> io::Result<fs::Metadata> {
+        self.blocking(|file| file.metadata())
+    }
+
+    /// Unwrap the std file, waiting for no operation since the caller owns the file
+    /// Fails with the file back if a cancelled operation still runs on the pool
+    pub fn into_std(self) -> Result<fs::File, File> {
+        Arc::try_unwrap(self.std).map_err(|std| File { std })
+    }
+
+    fn blocking<F, T>(&self, f: F) -> io::Result<T>
+    where
+        F: FnOnce(&fs::File) -> io::Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let std = self.std.clone();
+
+        spawn_blocking(move || f(&std))?
+    }
+}
+
+impl fmt::Debug for File {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("File").field("std", &self.std).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, process};
+
+    use super::*;
+    use crate::spawn;
+
+    #[test]
+    fn reads_and_writes_move_the_cursor() {
+        let path = env::temp_dir().join(format!("coroutine-file-{}", process::id()));
+
+        let handle = {
+            let path = path.clone();
+
+            unsafe {
+                spawn(move || {
+                    let mut file = File::create(&path).unwrap();
+
+                    file.write_all("hello ").unwrap();
+                    file.write_all("world").unwrap();
+                    file.sync_all().unwrap();
+
+                    let mut file = File::open(&path).unwrap();
+
+                    let head = file.read(5).unwrap();
+                    let tail = file.read_to_end().unwrap();
+                    let middle = file.read_at(3, 5).unwrap();
+
+                    (head, tail, middle, file.read(5).unwrap())
+                })
+            }
+        };
+
+        let (head, tail, middle, end) = handle.join().unwrap();
+
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(head, "hello");
+        assert_eq!(tail, " world");
+        assert_eq!(middle, "lo wo");
+        assert!(end.is_empty());
+    }
+}
@@ -0,0 +1,163 @@
+//! Filesystem operations that suspend the running coroutine instead of blocking its worker
+//!
+//! Every call runs on the blocking pool of `spawn_blocking`, the calling coroutine is parked until
+//! it's done. Outside of coroutines the calling thread is blocked instead. Reads land right in a
+//! `BytesMut` and are handed out as `Bytes` without another copy.
+//!
+//! The data given to a write is moved to the pool, hence the owned `Bytes`. A cancelled coroutine
+//! unwinds right away while the operation still completes on the pool.
+
+mod file;
+
+use std::{
+    fs::{self, DirEntry, Metadata},
+    io::{self, Read},
+    path::Path,
+};
+
+use bytes::{Bytes, BytesMut};
+
+use crate::spawn_blocking;
+
+pub use file::File;
+
+/// Size of the reads probing for the end of a file once the expected size is filled
+const PROBE_SIZE: usize = 32;
+
+/// Reads the entire contents of a file
+pub fn read<P: AsRef<Path>>(path: P) -> io::Result<Bytes> {
+    let path = path.as_ref().to_owned();
+
+    spawn_blocking(move || read_to_end(&fs::File::open(path)?))?
+}
+
+/// Writes `contents` as the entire contents of a file, creating or truncating it
+pub fn write<P: AsRef<Path>, C: Into<Bytes>>(path: P, contents: C) -> io::Result<()> {
+    let path = path.as_ref().to_owned();
+    let contents = contents.into();
+
+    spawn_blocking(move || fs::write(path, contents))?
+}
+
+/// Queries the metadata of a file or directory, following symbolic links
+pub fn metadata<P: AsRef<Path>>(path: P) -> io::Result<Metadata> {
+    let path = path.as_ref().to_owned();
+
+    spawn_blocking(move || fs::metadata(path))?
+}
+
+/// Lists the entries of a directory
+/// The whole listing is read in one go, in no particular order
+pub fn read_dir<P: AsRef<Path>>(path: P) -> io::Result<Vec<DirEntry>> {
+    let path = path.as_ref().to_owned();
+
+    spawn_blocking(move || fs::read_dir(path)?.collect())?
+}
+
+/// Renames a file or directory, replacing the destination if it exists
+pub fn rename<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> io::Result<()> {
+    let from = from.as_ref().to_owned();
+    let to = to.as_ref().to_owned();
+
+    spawn_blocking(move || fs::rename(from, to))?
+}
+
+/// Removes a file
+pub fn remove_file<P: AsRef<Path>>(path: P) -> io::Result<()> {
+    let path = path.as_ref().to_owned();
+
+    spawn_blocking(move || fs::remove_file(path))?
+}
+
+/// Read from the cursor to the end of the file, sizing the buffer from the metadata
+fn read_to_end(mut file: &fs::File) -> io::Result<Bytes> {
+    let size = file.metadata().map(|m| m.len() as usize).unwrap_or(0);
+    let mut buf = BytesMut::zeroed(size);
+    let mut filled = 0;
+
+    loop {
+        // The file may have grown since, probe before growing the buffer
+        if filled == buf.len() {
+            let mut probe = [0; PROBE_SIZE];
+
+            match file.read(&mut probe) {
+                Ok(0) => break,
+                Ok(n) => {
+                    buf.extend_from_slice(&probe[..n]);
+                    buf.resize(buf.capacity(), 0);
+                    filled += n;
+                }
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+
+            continue;
+        }
+
+        match file.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+
+    buf.truncate(filled);
+
+    Ok(buf.freeze())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, process};
+
+    use super::*;
+    use crate::spawn;
+
+    #[test]
+    fn file_round_trip_in_coroutine() {
+        let dir = env::temp_dir().join(format!("coroutine-fs-{}", process::id()));
+
+        fs::create_dir_all(&dir).unwrap();
+
+        let handle = {
+            let dir = dir.clone();
+
+            unsafe {
+                spawn(move || {
+                    let from = dir.join("from");
+                    let to = dir.join("to");
+
+                    write(&from, vec![7; 100_000]).unwrap();
+
+                    assert_eq!(metadata(&from).unwrap().len(), 100_000);
+
+                    rename(&from, &to).unwrap();
+
+                    let names: Vec<_> = read_dir(&dir)
+                        .unwrap()
+                        .into_iter()
+                        .map(|entry| entry.file_name())
+                        .collect();
+
+                    assert_eq!(names, ["to"]);
+
+                    let data = read(&to).unwrap();
+
+                    remove_file(&to).unwrap();
+
+                    assert!(metadata(&to).is_err());
+
+                    data
+                })
+            }
+        };
+
+        let data = handle.join().unwrap();
+
+        fs::remove_dir(&dir).unwrap();
+
+        assert_eq!(data.len(), 100_000);
+        assert!(data.iter().all(|&b| b == 7));
+    }
+}
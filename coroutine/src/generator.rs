@@ -0,0 +1,552 @@
+use std::{
+    any::Any,
+    fmt,
+    marker::PhantomData,
+    mem,
+    panic::{self, AssertUnwindSafe},
+    sync::{Once, atomic},
+    thread,
+};
+
+use log::error;
+
+use crate::{
+    config::config,
+    error::Error,
+    runtime::{Context, ContextStack},
+    stack::{Stack, overflow},
+    yield_now::{raw_yield_now, yield_now},
+};
+
+/// The closure run by a generator, consumed by the first resume
+type Func<'a> = Box<dyn FnOnce() + Send + 'a>;
+
+/// Generator state, boxed so that the context never moves while it is linked in a `ContextStack`
+struct GeneratorImpl<'a, A, T> {
+    /// Generator context
+    context: Context,
+
+    /// Generator stack
+    stack: Stack,
+
+    /// Value passed in by `send`
+    para: Option<A>,
+
+    /// Value yielded or returned by the closure
+    ret: Option<T>,
+
+    /// The closure, `None` once it started running
+    f: Option<Func<'a>>,
+}
+
+/// Stackful generator
+///
+/// The closure runs on its own stack and can suspend itself at any depth with `yield_`, handing a
+/// `T` to the caller of `resume`/`send` and receiving an `A` on the next `send`. Dropping a
+/// generator that is not done unwinds its stack, running the destructors of the suspended frames.
+pub struct Generator<'a, A, T> {
+    inner: Box<GeneratorImpl<'a, A, T>>,
+}
+
+// The closure is required to be `Send` and the generator only runs on the thread resuming it
+unsafe impl<A: Send, T: Send> Send for Generator<'_, A, T> {}
+
+/// Typed handle to yield from a generator created with `Generator::new_scoped`
+pub struct Scope<'s, A, T> {
+    para: *mut Option<A>,
+    ret: *mut Option<T>,
+
+    /// Invariant lifetime, the scope can't escape the generator closure
+    scope: PhantomData<&'s mut &'s ()>,
+}
+
+// SAFETY: the scope only points at the para and ret slots of its own generator and can't
+// escape the generator closure, so it never crosses a thread on its own. `Send` is only needed
+// to box the closure capturing it; the generator itself is `Send` only if `A` and `T` are, which
+// keeps the values passed through the scope on one thread otherwise
+unsafe impl<A, T> Send for Scope<'_, A, T> {}
+
+impl<'a, A: Any, T: Any> Generator<'a, A, T> {
+    /// Create a generator with the default stack size
+    /// The closure yields with the free `yield_` function
+    pub fn new<F>(f: F) -> Generator<'a, A, T>
+    where
+        F: FnOnce() -> T + Send + 'a,
+    {
+        Generator::new_opt(config().get_stack_size(), f)
+    }
+
+    /// Create a generator with a stack of `size` words
+    pub fn new_opt<F>(size: usize, f: F) -> Generator<'a, A, T>
+    where
+        F: FnOnce() -> T + Send + 'a,
+    {
+        let mut generator = Generator::with_stack(size);
+
+        generator.init_code(f);
+
+        generator
+    }
+
+    /// Create a generator with the default stack size
+    /// The closure yields through the `Scope` it is given, which is checked at compile time
+    pub fn new_scoped<F>(f: F) -> Generator<'a, A, T>
+    where
+        F: for<'s> FnOnce(Scope<'s, A, T>) -> T + Send + 'a,
+    {
+        Generator::new_scoped_opt(config().get_stack_size(), f)
+    }
+
+    /// Create a scoped generator with a stack of `size` words
+    pub fn new_scoped_opt<F>(size: usize, f: F) -> Generator<'a, A, T>
+    where
+        F: for<'s> FnOnce(Scope<'s, A, T>) -> T + Send + 'a,
+    {
+        let mut generator = Generator::with_stack(size);
+        let scope = Scope {
+            para: &mut generator.inner.para,
+            ret: &mut generator.inner.ret,
+            scope: PhantomData,
+        };
+
+        generator.init_code(move || f(scope));
+
+        generator
+    }
+
+    /// Create a generator without code, it is done until `init_code` is called
+    pub(crate) fn with_stack(size: usize) -> Generator<'a, A, T> {
+        Generator {
+            inner: GeneratorImpl::new(size),
+        }
+    }
+
+    /// Load new code into the generator, cancelling the previous code if it is suspended
+    pub(crate) fn init_code<F>(&mut self, f: F)
+    where
+        F: FnOnce() -> T + Send + 'a,
+    {
+        self.inner.init_code(f);
+    }
+
+    /// Resume the generator, returning the next yielded value
+    /// Returns `None` when the generator is done
+    #[inline]
+    pub fn resume(&mut self) -> Option<T> {
+        self.inner.resume()
+    }
+
+    /// Resume the generator with `para`, returned by the pending `yield_` inside the generator
+    #[inline]
+    pub fn raw_send(&mut self, para: Option<A>) -> Option<T> {
+        self.inner.raw_send(para)
+    }
+
+    /// Send `para` to the generator and return the next yielded value
+    /// Panics if the generator is done
+    pub fn send(&mut self, para: A) -> T {
+        self.raw_send(Some(para))
+            .expect("Send to a generator that is done")
+    }
+
+    /// Unwind the generator stack and mark it as done
+    pub fn cancel(&mut self) {
+        self.inner.cancel();
+    }
+
+    /// Returns true if the generator finished or was cancelled
+    #[inline]
+    pub fn is_done(&self) -> bool {
+        self.inner.is_done()
+    }
+
+    /// Returns the stack size and the used stack size in words
+    /// The used size is only accurate when the stack size is odd
+    pub fn stack_usage(&self) -> (usize, usize) {
+        (self.inner.stack.size(), self.inner.stack.get_used_size())
+    }
+
+    /// Returns the depth in words of the stack where the suspended generator stopped
+    /// None if it stopped in a nested generator, which runs on its own stack
+    pub(crate) fn stack_depth(&self) -> Option<usize> {
+        let top = self.inner.context.parent;
+
+        if top.is_null() {
+            return None;
+        }
+
+        let sp = unsafe { (*top).regs.sp() };
+        let begin = self.inner.stack.begin() as usize;
+        let end = self.inner.stack.end() as usize;
+
+        (begin..=end)
+            .contains(&sp)
+            .then(|| (end - sp) / mem::size_of::<usize>())
+    }
+
+    /// Release the memory of the used stack region, only valid while the generator is done
+    #[inline]
+    pub(crate) fn trim_stack(&self) {
+        debug_assert!(self.is_done());
+
+        if let Err(err) = self.inner.stack.trim() {
+            error!("Failed to trim generator stack: {}", err);
+        }
+    }
+
+    /// Measure the stack usage from scratch, only valid while the generator is done
+    #[inline]
+    pub(crate) fn reset_stack_usage(&self) {
+        debug_assert!(self.is_done());
+
+        self.inner.stack.reset_used_size();
+    }
+
+    /// Set the value returned to the generator by its pending yield
+    #[inline]
+    pub(crate) fn set_para(&mut self, para: A) {
+        self.inner.para = Some(para);
+    }
+
+    /// Attach the coroutine local storage, making the generator a coroutine
+    #[inline]
+    pub(crate) fn set_local_data(&mut self, data: *mut u8) {
+        self.inner.context.local_data = data;
+    }
+
+    /// Get the coroutine local storage
+    #[inline]
+    pub(crate) fn get_local_data(&self) -> *mut u8 {
+        self.inner.context.local_data
+    }
+
+    /// Take the panic of a coroutine, coroutines don't propagate their panics on resume
+    #[inline]
+    pub(crate) fn get_panic_data(&mut self) -> Option<Box<dyn Any + Send>> {
+        self.inner.context.err.take()
+    }
+}
+
+impl<A: Any, T: Any> Iterator for Generator<'_, A, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.resume()
+    }
+}
+
+impl<A, T> fmt::Debug for Generator<'_, A, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Generator")
+            .field("started", &self.inner.is_started())
+            .field("done", &self.inner.is_done())
+            .finish()
+    }
+}
+
+impl<A, T> Scope<'_, A, T> {
+    /// Yield `v` and return the value passed in by the next `send`
+    #[inline]
+    pub fn yield_(&mut self, v: T) -> Option<A> {
+        self.yield_with(v);
+
+        atomic::compiler_fence(atomic::Ordering::Acquire);
+
+        self.get_yield()
+    }
+
+    /// Yield `v` without taking the sent value
+    pub fn yield_with(&mut self, v: T) {
+        let env = ContextStack::current();
+        let context = env.top();
+
+        unsafe { *self.ret = Some(v) };
+
+        context._ref -= 1;
+
+        raw_yield_now(&env, context);
+
+        // The generator is being cancelled, unwind its stack
+        if context._ref != 1 {
+            panic::panic_any(Error::Cancel);
+        }
+    }
+
+    /// Get the value passed in by the last `send`
+    #[inline]
+    pub fn get_yield(&mut self) -> Option<A> {
+        unsafe { (*self.para).take() }
+    }
+}
+
+impl<A, T> GeneratorImpl<'_, A, T> {
+    /// The closure was consumed, it is running, suspended or done
+    #[inline]
+    fn is_started(&self) -> bool {
+        self.f.is_none()
+    }
+
+    /// The reference count is zero while the generator is suspended or not yet started
+    #[inline]
+    fn is_done(&self) -> bool {
+        self.is_started() && self.context._ref != 0
+    }
+
+    fn resume_gen(&mut self) {
+        let env = ContextStack::current();
+
+        // The registers of the running context are saved in the current top context
+        let cur = &mut env.top().regs;
+
+        // For a plain generator the parent is the generator itself, for a suspended coroutine
+        // it is the top of the generators nested inside of it
+        debug_assert!(!self.context.parent.is_null());
+
+        let top = unsafe { &mut *self.context.parent };
+
+        env.push_context(&mut self.context);
+
+        crate::register_context::RegisterContext::swap(cur, &top.regs);
+
+        // The overflow handler can't allocate, the actual payload is made back on this stack
+        if mem::take(&mut self.context.overflowed) {
+            self.context.err = Some(Box::new(Error::StackErr));
+        }
+
+        // Coroutine panics are collected by the scheduler instead
+        if !self.context.local_data.is_null() {
+            return;
+        }
+
+        // Propagate the generator panic up to the root context
+        if let Some(err) = self.context.err.take() {
+            panic::resume_unwind(err);
+        }
+    }
+
+    /// Make the pending yield panic with `Error::Cancel`, unwinding the generator stack
+    fn raw_cancel(&mut self) {
+        self.context._ref = 2;
+
+        self.resume_gen();
+    }
+
+    fn cancel(&mut self) {
+        if self.is_done() {
+            return;
+        }
+
+        if self.is_started() {
+            self.raw_cancel();
+        } else {
+            // Never run, just drop the closure
+            self.f.take();
+            self.context._ref = 1;
+        }
+    }
+}
+
+impl<'a, A: Any, T: Any> GeneratorImpl<'a, A, T> {
+    fn new(size: usize) -> Box<GeneratorImpl<'a, A, T>> {
+        install_panic_hook();
+        overflow::init_once();
+
+        let mut generator = Box::new(GeneratorImpl {
+            context: Context::new(),
+            stack: Stack::new(size),
+            para: None,
+            ret: None,
+            f: None,
+        });
+
+        generator.init_context();
+
+        generator
+    }
+
+    fn init_context(&mut self) {
+        let para = &mut self.para as &mut dyn Any as *mut dyn Any;
+        let ret = &mut self.ret as &mut dyn Any as *mut dyn Any;
+
+        self.context.para.write(para);
+        self.context.ret.write(ret);
+    }
+
+    fn init_code<F>(&mut self, f: F)
+    where
+        F: FnOnce() -> T + Send + 'a,
+    {
+        // Make sure the last code is finished
+        self.cancel();
+
+        // The context is the top of its own nested contexts
+        self.context.parent = &mut self.context;
+
+        // A zero reference count means ready to start
+        self.context._ref = 0;
+
+        // Smuggled as an address, the closure only runs on the generator stack
+        let ret = &mut self.ret as *mut Option<T> as usize;
+
+        self.f = Some(Box::new(move || {
+            let r = f();
+
+            unsafe { *(ret as *mut Option<T>) = Some(r) };
+        }));
+
+        self.context.stack_guard = (self.stack.guard_begin() as usize, self.stack.end() as usize);
+
+        let f = &mut self.f as *mut Option<Func<'a>> as *mut usize;
+
+        self.context.regs.init_with(gen_init, 0, f, &self.stack);
+    }
+
+    fn resume(&mut self) -> Option<T> {
+        if self.is_done() {
+            return None;
+        }
+
+        // Every resume increases the reference count, a yield decreases it and a return doesn't
+        self.context._ref += 1;
+
+        self.resume_gen();
+
+        self.ret.take()
+    }
+
+    fn raw_send(&mut self, para: Option<A>) -> Option<T> {
+        if self.is_done() {
+            return None;
+        }
+
+        self.para = para;
+
+        self.resume()
+    }
+}
+
+impl<A, T> Drop for GeneratorImpl<'_, A, T> {
+    fn drop(&mut self) {
+        // Never resume anything while the thread is unwinding
+        if thread::panicking() {
+            return;
+        }
+
+        if self.is_started() && !self.is_done() {
+            self.raw_cancel();
+        }
+    }
+}
+
+/// Entry point of every generator, running on the generator stack
+extern "sysv64" fn gen_init(_: usize, f: *mut usize) -> ! {
+    {
+        let f = unsafe { &mut *(f as *mut Option<Func<'_>>) };
+        let func = f.take().expect("Generator started without code");
+
+        // A panic can't unwind past the generator stack, hand it to the resumer instead
+        if let Err(cause) = panic::catch_unwind(AssertUnwindSafe(func)) {
+            check_err(cause);
+        }
+    }
+
+    yield_now();
+
+    unreachable!("Generator resumed after it's done");
+}
+
+fn check_err(cause: Box<dyn Any + Send>) {
+    // Cancel and done are not errors at all
+    if let Some(Error::Cancel | Error::Done) = cause.downcast_ref::<Error>() {
+        return;
+    }
+
+    error!("Panicked inside generator");
+
+    ContextStack::current().top().err = Some(cause);
+}
+
+/// Keep the panics used to cancel and finish generators out of the panic output
+fn install_panic_hook() {
+    static INSTALL: Once = Once::new();
+
+    INSTALL.call_once(|| {
+        let hook = panic::take_hook();
+
+        panic::set_hook(Box::new(move |info| {
+            if let Some(Error::Cancel | Error::Done) = info.payload().downcast_ref::<Error>() {
+                return;
+            }
+
+            hook(info);
+        }));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::yield_now::yield_;
+
+    #[test]
+    fn scoped_generator_yields_and_receives() {
+        let mut generator = Generator::new_scoped(|mut s| {
+            let mut sum = 0;
+
+            while let Some(v) = s.yield_(sum) {
+                sum += v;
+            }
+
+            crate::done!();
+        });
+
+        assert_eq!(generator.raw_send(None), Some(0));
+
+        for i in 1..4 {
+            assert!(!generator.is_done());
+            generator.send(i);
+        }
+
+        assert_eq!(generator.send(4), 10);
+    }
+
+    #[test]
+    fn generator_is_an_iterator() {
+        let generator = Generator::<(), _>::new(|| {
+            for i in 0..3 {
+                yield_::<(), _>(i);
+            }
+
+            3
+        });
+
+        assert_eq!(generator.collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn drop_unwinds_suspended_generator() {
+        struct Flag<'a>(&'a mut bool);
+
+        impl Drop for Flag<'_> {
+            fn drop(&mut self) {
+                *self.0 = true;
+            }
+        }
+
+        let mut dropped = false;
+
+        {
+            let flag = Flag(&mut dropped);
+            let mut generator = Generator::<(), ()>::new_scoped(move |mut s| {
+                let _flag = flag;
+
+                s.yield_(());
+            });
+
+            generator.resume();
+            assert!(!generator.is_done());
+        }
+
+        assert!(dropped);
+    }
+}
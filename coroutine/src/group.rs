@@ -0,0 +1,270 @@
+use std::{borrow::Cow, fmt, io, panic, thread};
+
+use crate::{
+    builder::CoroutineBuilder,
+    error::Error,
+    group_error::{Failure, GroupError},
+    join_handle::JoinHandle,
+    stack_profile::UNNAMED,
+    sync::mpsc::{self, Receiver, Sender},
+};
+
+/// Outcome of a member, sent to the group with the index of the member
+type Outcome<T, E> = (usize, thread::Result<Result<T, E>>);
+
+/// A set of named coroutines which succeed or fail together
+///
+/// `join` waits for all the members. On the first member which returns an error, panics or is
+/// cancelled, the others are cancelled and the failure is reported with the name of the member. A
+/// member in a `disable_cancel` section completes the section before it unwinds.
+pub struct CoroutineGroup<T, E> {
+    members: Vec<JoinHandle<()>>,
+    tx: Sender<Outcome<T, E>>,
+    rx: Receiver<Outcome<T, E>>,
+}
+
+impl<T: Send + 'static, E: Send + 'static> CoroutineGroup<T, E> {
+    /// Creates an empty group
+    pub fn new() -> CoroutineGroup<T, E> {
+        let (tx, rx) = mpsc::channel();
+
+        CoroutineGroup {
+            members: Vec::new(),
+            tx,
+            rx,
+        }
+    }
+
+    /// Spawns a member with the default configuration, named `name`
+    ///
+    /// # Safety
+    ///
+    /// Same as `spawn`.
+    pub unsafe fn spawn<F>(&mut self, name: impl Into<Cow<'static, str>>, f: F) -> io::Result<()>
+    where
+        F: FnOnce() -> Result<T, E> + Send + 'static,
+    {
+        unsafe { self.spawn_with(CoroutineBuilder::new().name(name), f) }
+    }
+
+    /// Spawns a member configured by `builder`, which should name it
+    ///
+    /// # Safety
+    ///
+    /// Same as `spawn`.
+    pub unsafe fn spawn_with<F>(&mut self, builder: CoroutineBuilder, f: F) -> io::Result<()>
+    where
+        F: FnOnce() -> Result<T, E> + Send + 'static,
+    {
+        let index = self.members.len();
+        let tx = self.tx.clone();
+
+        // The panic is caught to be reported right away, a cancelled member reports its unwind too
+        let handle = unsafe {
+            builder.spawn(move || {
+                tx.send((index, panic::catch_unwind(panic::AssertUnwindSafe(f))))
+                    .ok();
+            })
+        }?;
+
+        self.members.push(handle);
+
+        Ok(())
+    }
+
+    /// Returns the number of members
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    /// Returns true if the group has no member
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    /// Waits for all the members, returns their results in spawning order
+    /// Fails with the first failure, once the other members are cancelled and done
+    pub fn join(self) -> Result<Vec<T>, GroupError<E>> {
+        let CoroutineGroup { members, tx, rx } = self;
+
+        // The outcomes end once every member dropped its sender
+        drop(tx);
+
+        let mut results: Vec<Option<T>> = members.iter().map(|_| None).collect();
+        let mut failure = None;
+
+        for (index, outcome) in rx.iter() {
+            let err = match outcome {
+                Ok(Ok(t)) => {
+                    results[index] = Some(t);
+
+                    continue;
+                }
+                Ok(Err(err)) => Failure::Error(err),
+                // A member cancelled through its handle unwinds with the cancel payload
+                Err(panic) if panic.downcast_ref::<Error>() == Some(&Error::Cancel) => {
+                    Failure::Cancelled
+                }
+                Err(panic) => Failure::Panicked(panic),
+            };
+
+            // The outcomes of the cancelled members are dropped
+            if failure.is_some() {
+                continue;
+            }
+
+            failure = Some(GroupError {
+                name: member_name(&members[index]),
+                failure: err,
+            });
+
+            for member in members.iter().filter(|member| !member.is_done()) {
+                unsafe { member.coroutine().cancel() };
+            }
+        }
+
+        for member in &members {
+            member.wait();
+        }
+
+        if let Some(err) = failure {
+            return Err(err);
+        }
+
+        // A member which never ran doesn't report any outcome
+        results
+            .into_iter()
+            .zip(&members)
+            .map(|(result, member)| {
+                result.ok_or_else(|| GroupError {
+                    name: member_name(member),
+                    failure: Failure::Cancelled,
+                })
+            })
+            .collect()
+    }
+}
+
+impl<T: Send + 'static, E: Send + 'static> Default for CoroutineGroup<T, E> {
+    fn default() -> CoroutineGroup<T, E> {
+        CoroutineGroup::new()
+    }
+}
+
+impl<T, E> fmt::Debug for CoroutineGroup<T, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CoroutineGroup")
+            .field(
+                "members",
+                &self
+                    .members
+                    .iter()
+                    .map(JoinHandle::coroutine)
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+fn member_name(member: &JoinHandle<()>) -> String {
+    member.coroutine().name().unwrap_or(UNNAMED).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            Arc,
+            atomic::{AtomicBool, Ordering},
+        },
+        time::Duration,
+    };
+
+    use super::*;
+    use crate::{disable_cancel, sleep};
+
+    #[test]
+    fn all_members_succeed() {
+        let mut group = CoroutineGroup::<usize, ()>::new();
+
+        for i in 0..10 {
+            unsafe { group.spawn(format!("member-{}", i), move || Ok(i * 2)) }.unwrap();
+        }
+
+        assert_eq!(
+            group.join().unwrap(),
+            (0..10).map(|i| i * 2).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn first_failure_cancels_the_rest() {
+        let section_done = Arc::new(AtomicBool::new(false));
+        let mut group = CoroutineGroup::<(), &str>::new();
+
+        unsafe {
+            group
+                .spawn("forever", || {
+                    loop {
+                        sleep(Duration::from_millis(5));
+                    }
+                })
+                .unwrap();
+
+            let section_done = section_done.clone();
+
+            group
+                .spawn("critical", move || {
+                    disable_cancel(|| {
+                        sleep(Duration::from_millis(50));
+
+                        section_done.store(true, Ordering::Relaxed);
+                    });
+
+                    Ok(())
+                })
+                .unwrap();
+
+            group
+                .spawn("failing", || {
+                    sleep(Duration::from_millis(10));
+
+                    Err("boom")
+                })
+                .unwrap();
+        }
+
+        let err = group.join().unwrap_err();
+
+        assert_eq!(err.name, "failing");
+        assert!(matches!(err.failure, Failure::Error("boom")));
+        assert!(section_done.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn cancelled_member_is_not_a_panic() {
+        let mut group = CoroutineGroup::<(), ()>::new();
+
+        for name in ["cancelled", "other"] {
+            unsafe {
+                group
+                    .spawn(name, || {
+                        loop {
+                            sleep(Duration::from_millis(5));
+                        }
+                    })
+                    .unwrap();
+            }
+        }
+
+        // Let the member start, so that it unwinds rather than never running
+        std::thread::sleep(Duration::from_millis(20));
+
+        unsafe { group.members[0].coroutine().cancel() };
+
+        let err = group.join().unwrap_err();
+
+        assert_eq!(err.name, "cancelled");
+        assert!(matches!(err.failure, Failure::Cancelled));
+    }
+}
@@ -0,0 +1,45 @@
+use std::{any::Any, error::Error, fmt};
+
+/// Error returned by `CoroutineGroup::join`, the first failure of a member
+#[derive(Debug)]
+pub struct GroupError<E> {
+    /// Name of the failed member
+    pub name: String,
+
+    /// How the member failed
+    pub failure: Failure<E>,
+}
+
+/// How a member of a `CoroutineGroup` failed
+#[derive(Debug)]
+pub enum Failure<E> {
+    /// The member returned an error
+    Error(E),
+
+    /// The member panicked, with the panic payload
+    Panicked(Box<dyn Any + Send>),
+
+    /// The member was cancelled through its handle, or before its closure started
+    /// The members the group cancels after a failure are not reported, the group returns that
+    /// first failure instead
+    Cancelled,
+}
+
+impl<E: fmt::Display> fmt::Display for GroupError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.failure {
+            Failure::Error(ref err) => write!(f, "Coroutine {} failed: {}", self.name, err),
+            Failure::Panicked(_) => write!(f, "Coroutine {} panicked", self.name),
+            Failure::Cancelled => write!(f, "Coroutine {} was cancelled", self.name),
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for GroupError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self.failure {
+            Failure::Error(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
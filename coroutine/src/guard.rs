@@ -1,16 +1,15 @@
 use std::ops::Range;
 
-use crate::{
-    runtime::{ContextStack, is_generator},
-    stack::page_size,
-};
+use crate::runtime::{ContextStack, is_generator};
 
 pub type Guard = Range<usize>;
 
+/// The stack of the running generator, its guard pages included
+/// Only reads the range cached by `init_code`, the overflow handler calls it
 pub fn current() -> Guard {
     assert!(is_generator());
 
     let guard = unsafe { (*(*ContextStack::current().root).child).stack_guard };
 
-    guard.0 - page_size()..guard.1
+    guard.0..guard.1
 }
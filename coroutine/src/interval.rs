@@ -0,0 +1,93 @@
+use std::time::{Duration, Instant};
+
+use crate::{
+    select::{Selectable, Token},
+    sleep::sleep_until,
+};
+
+/// Ticks at a fixed period, suspending the current coroutine between the ticks
+///
+/// The first tick completes right away. When a tick is late by more than a period, for example
+/// because the coroutine was busy, the missed ticks are skipped rather than completed in a burst,
+/// and the next ones are a period apart from the late tick.
+#[derive(Debug)]
+pub struct Interval {
+    period: Duration,
+
+    /// Deadline of the next tick
+    next: Instant,
+}
+
+impl Interval {
+    /// Creates an interval whose first tick completes right away
+    ///
+    /// # Panics
+    /// Panics if `period` is zero
+    pub fn new(period: Duration) -> Interval {
+        Interval::new_at(Instant::now(), period)
+    }
+
+    /// Creates an interval whose first tick completes at `start`
+    ///
+    /// # Panics
+    /// Panics if `period` is zero
+    pub fn new_at(start: Instant, period: Duration) -> Interval {
+        assert!(!period.is_zero(), "Interval period must be non-zero");
+
+        Interval {
+            period,
+            next: start,
+        }
+    }
+
+    /// Waits until the next tick, returns the instant the tick was scheduled at
+    pub fn tick(&mut self) -> Instant {
+        let deadline = self.next;
+
+        sleep_until(deadline);
+
+        self.advance(Instant::now())
+    }
+
+    /// Restarts the interval, the next tick completes a period from now
+    pub fn reset(&mut self) {
+        self.next = Instant::now() + self.period;
+    }
+
+    /// Returns the period of the interval
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    /// Complete the due tick at `now`, returns the instant it was scheduled at
+    fn advance(&mut self, now: Instant) -> Instant {
+        let deadline = self.next;
+
+        self.next = if now.duration_since(deadline) > self.period {
+            now + self.period
+        } else {
+            deadline + self.period
+        };
+
+        deadline
+    }
+}
+
+/// The next tick, as a `select!` branch
+impl Selectable for &mut Interval {
+    type Output = Instant;
+
+    fn try_select(&mut self) -> Option<Instant> {
+        let now = Instant::now();
+
+        (now >= self.next).then(|| self.advance(now))
+    }
+
+    fn watch(&mut self, _token: &Token) {}
+
+    fn unwatch(&mut self, _token: &Token) {}
+
+    fn deadline(&self) -> Option<Instant> {
+        Some(self.next)
+    }
+}
@@ -0,0 +1,110 @@
+use std::{
+    io, mem,
+    os::fd::{AsRawFd, OwnedFd},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use log::error;
+
+use crate::io::{
+    IoData, Waker,
+    sys::{self, EPOLLET, EPOLLIN, EPOLLOUT, EPOLLRDHUP, epoll_event},
+};
+
+/// Maximum number of events handled by a single poll
+const EVENTS_CAPACITY: usize = 256;
+
+/// Event data of the waker, the registered descriptors use the address of their `IoData`
+const WAKER_TOKEN: u64 = 0;
+
+/// Reactor backend built on an epoll instance
+pub(crate) struct Epoll {
+    epoll: OwnedFd,
+
+    waker: Waker,
+
+    /// Deregistered descriptors, freed by the next poll once no event can point to them anymore
+    released: Mutex<Vec<Arc<IoData>>>,
+}
+
+impl Epoll {
+    pub(crate) fn new() -> io::Result<Epoll> {
+        let epoll = sys::epoll_create()?;
+        let waker = Waker::new()?;
+
+        sys::epoll_add(
+            epoll.as_raw_fd(),
+            waker.as_raw_fd(),
+            EPOLLIN | EPOLLET,
+            WAKER_TOKEN,
+        )?;
+
+        Ok(Epoll {
+            epoll,
+            waker,
+            released: Mutex::new(Vec::new()),
+        })
+    }
+
+    pub(crate) fn register(&self, io: &Arc<IoData>) -> io::Result<()> {
+        sys::epoll_add(
+            self.epoll.as_raw_fd(),
+            io.fd,
+            EPOLLIN | EPOLLOUT | EPOLLRDHUP | EPOLLET,
+            Arc::as_ptr(io) as u64,
+        )
+    }
+
+    pub(crate) fn deregister(&self, io: &Arc<IoData>) {
+        if let Err(err) = sys::epoll_del(self.epoll.as_raw_fd(), io.fd) {
+            error!("Failed to deregister fd {}: {}", io.fd, err);
+        }
+
+        // An event for the descriptor may be in the hands of the poller right now
+        self.released.lock().unwrap().push(io.clone());
+    }
+
+    #[inline]
+    pub(crate) fn wake(&self) {
+        self.waker.wake();
+    }
+
+    pub(crate) fn poll(&self, timeout: Option<Duration>) -> usize {
+        // The events of the previous poll are all handled, nothing points to these anymore
+        let released = mem::take(&mut *self.released.lock().unwrap());
+
+        drop(released);
+
+        let mut events = [epoll_event { events: 0, u64: 0 }; EVENTS_CAPACITY];
+
+        let n = match sys::epoll_wait_events(self.epoll.as_raw_fd(), &mut events, timeout) {
+            Ok(n) => n,
+            Err(err) => {
+                error!("Failed to poll reactor: {}", err);
+
+                return 0;
+            }
+        };
+
+        let mut ready = 0;
+
+        for event in &events[..n] {
+            let (flags, token) = (event.events, event.u64);
+
+            if token == WAKER_TOKEN {
+                self.waker.drain();
+
+                continue;
+            }
+
+            let io = unsafe { &*(token as *const IoData) };
+
+            io.ready(flags);
+
+            ready += 1;
+        }
+
+        ready
+    }
+}
@@ -0,0 +1,94 @@
+use std::{
+    io,
+    os::fd::{AsRawFd, RawFd},
+    sync::{Arc, atomic::Ordering},
+};
+
+use crate::{
+    io::{
+        Interest, IoData,
+        sys::{self, POLLIN, POLLOUT},
+    },
+    is_coroutine,
+    net::Readiness,
+    scheduler::get_scheduler,
+};
+
+/// A non-blocking descriptor whose operations wait for readiness instead of failing
+///
+/// In a coroutine the wait parks the coroutine until the reactor reports readiness, the
+/// descriptor is registered with the reactor on the first wait. On a plain thread the wait blocks
+/// the thread in `poll`, so the operations behave like their blocking std counterparts.
+pub(crate) struct Evented<T: AsRawFd> {
+    inner: T,
+    io: Arc<IoData>,
+}
+
+impl<T: AsRawFd> Evented<T> {
+    /// Switch the descriptor to non-blocking mode and wrap it
+    pub(crate) fn new(inner: T) -> io::Result<Evented<T>> {
+        let fd = inner.as_raw_fd();
+
+        sys::set_nonblocking(fd)?;
+
+        Ok(Evented {
+            inner,
+            io: Arc::new(IoData::new(fd)),
+        })
+    }
+
+    #[inline]
+    pub(crate) fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Run `f` until it doesn't fail with `WouldBlock`, waiting for readiness in between
+    pub(crate) fn do_io<R, F>(&self, interest: Interest, mut f: F) -> io::Result<R>
+    where
+        F: FnMut(&T) -> io::Result<R>,
+    {
+        loop {
+            self.io.reset(interest);
+
+            match f(&self.inner) {
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => self.wait(interest)?,
+                ret => return ret,
+            }
+        }
+    }
+
+    /// Wait until the descriptor is ready in the direction
+    pub(crate) fn wait(&self, interest: Interest) -> io::Result<()> {
+        if !is_coroutine() {
+            let events = match interest {
+                Interest::Read => POLLIN,
+                Interest::Write => POLLOUT,
+            };
+
+            return sys::poll_fd(self.io.fd, events, None).map(drop);
+        }
+
+        self.io.register()?;
+        self.io.wait(interest)
+    }
+
+    /// Readiness in the direction, for `select!`
+    #[inline]
+    pub(crate) fn readiness(&self, interest: Interest) -> Readiness<'_> {
+        Readiness::new(&self.io, interest)
+    }
+}
+
+impl<T: AsRawFd> AsRawFd for Evented<T> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.io.fd
+    }
+}
+
+impl<T: AsRawFd> Drop for Evented<T> {
+    fn drop(&mut self) {
+        if self.io.registered.load(Ordering::Acquire) {
+            get_scheduler().get_reactor(self.io.fd).deregister(&self.io);
+        }
+    }
+}
@@ -0,0 +1,275 @@
+use std::{
+    collections::VecDeque,
+    io, mem,
+    os::fd::RawFd,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use crate::{
+    CoroutineImpl,
+    cancel::Cancel,
+    coroutine_cancel_data,
+    event::EventSource,
+    io::sys::{EPOLLERR, EPOLLHUP, EPOLLIN, EPOLLOUT, EPOLLRDHUP},
+    scheduler::get_scheduler,
+    select::Token,
+    sync::AtomicOption,
+    yield_now::{get_coroutine_para, yield_with_event},
+};
+
+/// Direction of an I/O operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Interest {
+    Read,
+    Write,
+}
+
+/// Holds a suspended coroutine until either a wake or a cancel takes it out
+type Slot = Arc<AtomicOption<CoroutineImpl>>;
+
+/// What waits for the readiness of a descriptor
+enum IoWaiter {
+    /// A coroutine suspended in an I/O operation
+    Coroutine(Slot),
+
+    /// A `select!` watching the descriptor among other event sources
+    Select(Token),
+}
+
+/// Readiness state of a file descriptor registered with a reactor
+///
+/// The registration is edge-triggered, so readiness is remembered in a flag until the next
+/// operation in that direction fails with `WouldBlock`. Any number of coroutines and `select!`s
+/// may wait in a direction, readiness wakes them all up and those that lose the race for the
+/// descriptor wait again.
+pub(crate) struct IoData {
+    pub(crate) fd: RawFd,
+
+    /// The descriptor was added to its reactor
+    pub(crate) registered: AtomicBool,
+
+    readable: AtomicBool,
+    writable: AtomicBool,
+
+    readers: Mutex<VecDeque<IoWaiter>>,
+    writers: Mutex<VecDeque<IoWaiter>>,
+}
+
+/// The wait of a coroutine for a descriptor, registered with the cancel data of the coroutine
+pub(crate) struct IoWaitHandle {
+    io: Arc<IoData>,
+    interest: Interest,
+    slot: Slot,
+}
+
+impl IoData {
+    pub(crate) fn new(fd: RawFd) -> IoData {
+        IoData {
+            fd,
+            registered: AtomicBool::new(false),
+            readable: AtomicBool::new(false),
+            writable: AtomicBool::new(false),
+            readers: Mutex::new(VecDeque::new()),
+            writers: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    #[inline]
+    fn flag(&self, interest: Interest) -> &AtomicBool {
+        match interest {
+            Interest::Read => &self.readable,
+            Interest::Write => &self.writable,
+        }
+    }
+
+    #[inline]
+    fn waiters(&self, interest: Interest) -> &Mutex<VecDeque<IoWaiter>> {
+        match interest {
+            Interest::Read => &self.readers,
+            Interest::Write => &self.writers,
+        }
+    }
+
+    /// Forget the coroutine slot of a wait that ended without a wake
+    fn remove(&self, interest: Interest, slot: &Slot) {
+        self.waiters(interest)
+            .lock()
+            .unwrap()
+            .retain(|waiter| match waiter {
+                IoWaiter::Coroutine(other) => !Arc::ptr_eq(other, slot),
+                IoWaiter::Select(_) => true,
+            });
+    }
+
+    /// Forget the readiness in a direction, called before every attempt of an operation
+    #[inline]
+    pub(crate) fn reset(&self, interest: Interest) {
+        self.flag(interest).store(false, Ordering::Release);
+    }
+
+    /// Add the descriptor to its reactor unless it's already registered
+    pub(crate) fn register(self: &Arc<IoData>) -> io::Result<()> {
+        if self.registered.load(Ordering::Acquire)
+            || self
+                .registered
+                .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+                .is_err()
+        {
+            return Ok(());
+        }
+
+        let ret = get_scheduler().get_reactor(self.fd).register(self);
+
+        if ret.is_err() {
+            self.registered.store(false, Ordering::Release);
+        }
+
+        ret
+    }
+
+    /// Suspend the running coroutine until the descriptor is ready in the direction
+    /// Fails with the error the coroutine is resumed with when its cancel is disabled
+    pub(crate) fn wait(self: &Arc<IoData>, interest: Interest) -> io::Result<()> {
+        yield_with_event(&IoWait { io: self, interest });
+
+        match get_coroutine_para() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// Wake `token` once the descriptor is ready in the direction
+    pub(crate) fn watch(&self, interest: Interest, token: &Token) {
+        self.waiters(interest)
+            .lock()
+            .unwrap()
+            .push_back(IoWaiter::Select(token.clone()));
+    }
+
+    /// Stop waking the token of a `select!`
+    pub(crate) fn unwatch(&self, interest: Interest, token: &Token) {
+        self.waiters(interest)
+            .lock()
+            .unwrap()
+            .retain(|waiter| match waiter {
+                IoWaiter::Coroutine(_) => true,
+                IoWaiter::Select(other) => !Arc::ptr_eq(other.node(), token.node()),
+            });
+    }
+
+    /// Record the readiness reported by the reactor and wake up the waiting coroutines
+    pub(crate) fn ready(&self, events: u32) {
+        if events & (EPOLLIN | EPOLLRDHUP | EPOLLHUP | EPOLLERR) != 0 {
+            self.wake(Interest::Read);
+        }
+
+        if events & (EPOLLOUT | EPOLLHUP | EPOLLERR) != 0 {
+            self.wake(Interest::Write);
+        }
+    }
+
+    #[inline]
+    fn wake(&self, interest: Interest) {
+        self.flag(interest).store(true, Ordering::SeqCst);
+
+        let waiters = mem::take(&mut *self.waiters(interest).lock().unwrap());
+
+        for waiter in waiters {
+            waiter.wake();
+        }
+    }
+}
+
+impl IoWaitHandle {
+    /// Resume the waiting coroutine with a `Cancelled` error
+    /// Fails if the coroutine was woken up meanwhile
+    pub(crate) fn cancel(self) -> io::Result<()> {
+        self.io.remove(self.interest, &self.slot);
+
+        match self.slot.take() {
+            Some(mut coroutine) => {
+                coroutine.set_para(io::Error::other("Cancelled"));
+                get_scheduler().schedule(coroutine);
+
+                Ok(())
+            }
+            None => Err(io::Error::other("No coroutine waits for the descriptor")),
+        }
+    }
+}
+
+impl IoWaiter {
+    #[inline]
+    fn wake(self) {
+        match self {
+            IoWaiter::Coroutine(slot) => {
+                // A cancel may have taken the coroutine already
+                if let Some(coroutine) = slot.take() {
+                    get_scheduler().schedule(coroutine);
+                }
+            }
+            IoWaiter::Select(token) => token.wake(),
+        }
+    }
+}
+
+/// Event source of a coroutine waiting for readiness
+///
+/// The wait is registered with the cancel data of the coroutine, a cancel takes the coroutine out
+/// of the descriptor and resumes it with an error.
+struct IoWait<'a> {
+    io: &'a Arc<IoData>,
+    interest: Interest,
+}
+
+impl EventSource for IoWait<'_> {
+    fn subscribe(&mut self, coroutine: CoroutineImpl) {
+        let cancel = coroutine_cancel_data(&coroutine);
+        let slot: Slot = Arc::new(AtomicOption::none());
+
+        slot.store(coroutine);
+
+        // Register the wait for cancellation first, a wake may resume the coroutine as soon as
+        // it's published and its `yield_back` has to find the registration to clear it
+        cancel.set_io(IoWaitHandle {
+            io: self.io.clone(),
+            interest: self.interest,
+            slot: slot.clone(),
+        });
+
+        self.io
+            .waiters(self.interest)
+            .lock()
+            .unwrap()
+            .push_back(IoWaiter::Coroutine(slot.clone()));
+
+        // Re-check the readiness, the event may have arrived before the coroutine was registered
+        if self.io.flag(self.interest).load(Ordering::SeqCst) {
+            self.io.remove(self.interest, &slot);
+
+            if let Some(coroutine) = slot.take() {
+                return get_scheduler().schedule(coroutine);
+            }
+        }
+
+        // Re-check the cancel flag, a cancel before the registration didn't find the wait
+        if cancel.is_cancelled() {
+            unsafe { cancel.cancel() };
+        }
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.interest {
+            Interest::Read => "io read",
+            Interest::Write => "io write",
+        }
+    }
+
+    fn yield_back(&self, cancel: &'static Cancel) {
+        cancel.clear();
+        cancel.check_cancel();
+    }
+}
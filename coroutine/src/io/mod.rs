@@ -0,0 +1,18 @@
+mod epoll;
+mod evented;
+mod io_data;
+mod reactor;
+pub(crate) mod sys;
+#[cfg(feature = "io-uring")]
+mod uring;
+#[cfg(feature = "io-uring")]
+mod uring_sys;
+mod waker;
+
+pub(crate) use epoll::Epoll;
+pub(crate) use evented::Evented;
+pub(crate) use io_data::{Interest, IoData, IoWaitHandle};
+pub(crate) use reactor::Reactor;
+#[cfg(feature = "io-uring")]
+pub(crate) use uring::Uring;
+pub(crate) use waker::Waker;
@@ -0,0 +1,137 @@
+use std::{io, sync::Arc, time::Duration};
+
+#[cfg(feature = "io-uring")]
+use log::{error, warn};
+
+#[cfg(feature = "io-uring")]
+use crate::io::Uring;
+use crate::io::{Epoll, IoData};
+
+/// I/O reactor owned by a worker
+///
+/// Only the owning worker polls the reactor, the ready coroutines go to its local queue. Any
+/// thread can register descriptors, and wake the worker while it sleeps in `poll`.
+///
+/// With the `io-uring` feature the reactor is built on io_uring when the kernel supports it, and
+/// falls back to epoll otherwise. Both report the readiness of the descriptors the same way, the
+/// reads and writes are syscalls of the sockets with either.
+pub(crate) enum Reactor {
+    Epoll(Epoll),
+
+    #[cfg(feature = "io-uring")]
+    Uring(Uring),
+}
+
+impl Reactor {
+    pub(crate) fn new() -> io::Result<Reactor> {
+        #[cfg(feature = "io-uring")]
+        match Uring::new() {
+            Ok(uring) => return Ok(Reactor::Uring(uring)),
+            Err(err) => warn!("io_uring is not available, falling back to epoll: {}", err),
+        }
+
+        Epoll::new().map(Reactor::Epoll)
+    }
+
+    /// Add a descriptor, its current readiness is reported by the next poll
+    pub(crate) fn register(&self, io: &Arc<IoData>) -> io::Result<()> {
+        match self {
+            Reactor::Epoll(epoll) => epoll.register(io),
+
+            #[cfg(feature = "io-uring")]
+            Reactor::Uring(uring) => uring.register(io),
+        }
+    }
+
+    /// Remove a descriptor before it's closed
+    pub(crate) fn deregister(&self, io: &Arc<IoData>) {
+        match self {
+            Reactor::Epoll(epoll) => epoll.deregister(io),
+
+            #[cfg(feature = "io-uring")]
+            Reactor::Uring(uring) => uring.deregister(io),
+        }
+    }
+
+    /// Hand the batched submissions to the kernel, the owning worker calls it after every task
+    pub(crate) fn flush(&self) {
+        match self {
+            Reactor::Epoll(_) => {}
+
+            #[cfg(feature = "io-uring")]
+            Reactor::Uring(uring) => {
+                if let Err(err) = uring.flush() {
+                    error!("Failed to flush reactor: {}", err);
+                }
+            }
+        }
+    }
+
+    /// Interrupt the poll of the owning worker
+    pub(crate) fn wake(&self) {
+        match self {
+            Reactor::Epoll(epoll) => epoll.wake(),
+
+            #[cfg(feature = "io-uring")]
+            Reactor::Uring(uring) => uring.wake(),
+        }
+    }
+
+    /// Wait up to `timeout` for events and schedule the coroutines waiting for them
+    /// Returns the number of ready descriptors
+    pub(crate) fn poll(&self, timeout: Option<Duration>) -> usize {
+        match self {
+            Reactor::Epoll(epoll) => epoll.poll(timeout),
+
+            #[cfg(feature = "io-uring")]
+            Reactor::Uring(uring) => uring.poll(timeout),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{os::unix::net::UnixStream, time::Instant};
+
+    use super::*;
+
+    #[test]
+    fn wake_interrupts_poll() {
+        let reactor = Reactor::new().unwrap();
+
+        reactor.wake();
+
+        let start = Instant::now();
+
+        reactor.poll(Some(Duration::from_secs(5)));
+
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn reports_readiness_until_deregistered() {
+        let reactor = Reactor::new().unwrap();
+        let (a, b) = UnixStream::pair().unwrap();
+
+        a.set_nonblocking(true).unwrap();
+
+        let io = Arc::new(IoData::new(std::os::fd::AsRawFd::as_raw_fd(&a)));
+
+        reactor.register(&io).unwrap();
+
+        // The socket is writable right away
+        assert_eq!(reactor.poll(Some(Duration::from_secs(5))), 1);
+
+        std::io::Write::write_all(&mut &b, b"ping").unwrap();
+
+        assert_eq!(reactor.poll(Some(Duration::from_secs(5))), 1);
+
+        reactor.deregister(&io);
+        reactor.poll(Some(Duration::ZERO));
+
+        drop(a);
+        drop(b);
+
+        assert_eq!(reactor.poll(Some(Duration::from_millis(10))), 0);
+    }
+}
@@ -0,0 +1,382 @@
+use core::ffi::{c_int, c_uint, c_ulong, c_void};
+use std::{
+    io, mem,
+    os::fd::{FromRawFd, OwnedFd, RawFd},
+    ptr,
+    time::Duration,
+};
+
+#[allow(non_camel_case_types)]
+pub type socklen_t = c_uint;
+#[allow(non_camel_case_types)]
+pub type nfds_t = c_ulong;
+
+pub const EPOLL_CLOEXEC: c_int = 0x80000;
+
+pub const EPOLL_CTL_ADD: c_int = 1;
+pub const EPOLL_CTL_DEL: c_int = 2;
+
+pub const EPOLLIN: u32 = 0x001;
+pub const EPOLLOUT: u32 = 0x004;
+pub const EPOLLERR: u32 = 0x008;
+pub const EPOLLHUP: u32 = 0x010;
+pub const EPOLLRDHUP: u32 = 0x2000;
+pub const EPOLLET: u32 = 1 << 31;
+
+pub const EFD_CLOEXEC: c_int = 0x80000;
+pub const EFD_NONBLOCK: c_int = 0x800;
+
+pub const POLLIN: i16 = 0x001;
+pub const POLLOUT: i16 = 0x004;
+
+pub const F_GETFL: c_int = 3;
+pub const F_SETFL: c_int = 4;
+pub const O_NONBLOCK: c_int = 0x800;
+
+pub const AF_INET: c_int = 2;
+pub const AF_INET6: c_int = 10;
+
+pub const SOCK_STREAM: c_int = 1;
+pub const SOCK_NONBLOCK: c_int = 0x800;
+pub const SOCK_CLOEXEC: c_int = 0x80000;
+
+pub const SOL_SOCKET: c_int = 1;
+pub const SO_PEERCRED: c_int = 17;
+pub const SCM_RIGHTS: c_int = 1;
+
+pub const MSG_CMSG_CLOEXEC: c_int = 0x40000000;
+
+pub const EINPROGRESS: i32 = 115;
+
+#[repr(C, packed)]
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy)]
+pub struct epoll_event {
+    pub events: u32,
+    pub u64: u64,
+}
+
+#[repr(C)]
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy)]
+pub struct sockaddr_in {
+    pub sin_family: u16,
+    pub sin_port: u16,
+    pub sin_addr: [u8; 4],
+    pub sin_zero: [u8; 8],
+}
+
+#[repr(C)]
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy)]
+pub struct sockaddr_in6 {
+    pub sin6_family: u16,
+    pub sin6_port: u16,
+    pub sin6_flowinfo: u32,
+    pub sin6_addr: [u8; 16],
+    pub sin6_scope_id: u32,
+}
+
+#[repr(C, align(8))]
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy)]
+pub struct sockaddr_storage {
+    pub ss_family: u16,
+    pub ss_data: [u8; 126],
+}
+
+#[repr(C)]
+#[allow(non_camel_case_types)]
+pub struct iovec {
+    pub iov_base: *mut c_void,
+    pub iov_len: usize,
+}
+
+#[repr(C)]
+#[allow(non_camel_case_types)]
+pub struct msghdr {
+    pub msg_name: *mut c_void,
+    pub msg_namelen: socklen_t,
+    pub msg_iov: *mut iovec,
+    pub msg_iovlen: usize,
+    pub msg_control: *mut c_void,
+    pub msg_controllen: usize,
+    pub msg_flags: c_int,
+}
+
+#[repr(C)]
+#[allow(non_camel_case_types)]
+pub struct cmsghdr {
+    pub cmsg_len: usize,
+    pub cmsg_level: c_int,
+    pub cmsg_type: c_int,
+}
+
+#[repr(C)]
+#[allow(non_camel_case_types)]
+#[derive(Default)]
+pub struct ucred {
+    pub pid: i32,
+    pub uid: u32,
+    pub gid: u32,
+}
+
+#[repr(C)]
+#[allow(non_camel_case_types)]
+pub struct mmsghdr {
+    pub msg_hdr: msghdr,
+    pub msg_len: c_uint,
+}
+
+#[repr(C)]
+#[allow(non_camel_case_types)]
+pub struct pollfd {
+    pub fd: c_int,
+    pub events: i16,
+    pub revents: i16,
+}
+
+unsafe extern "C" {
+    fn epoll_create1(flags: c_int) -> c_int;
+
+    fn epoll_ctl(epfd: c_int, op: c_int, fd: c_int, event: *mut epoll_event) -> c_int;
+
+    fn epoll_wait(epfd: c_int, events: *mut epoll_event, maxevents: c_int, timeout: c_int)
+    -> c_int;
+
+    fn eventfd(initval: c_uint, flags: c_int) -> c_int;
+
+    fn poll(fds: *mut pollfd, nfds: nfds_t, timeout: c_int) -> c_int;
+
+    fn fcntl(fd: c_int, cmd: c_int, ...) -> c_int;
+
+    fn socket(domain: c_int, ty: c_int, protocol: c_int) -> c_int;
+
+    fn connect(socket: c_int, address: *const c_void, len: socklen_t) -> c_int;
+
+    fn sendmsg(fd: c_int, msg: *const msghdr, flags: c_int) -> isize;
+
+    fn recvmsg(fd: c_int, msg: *mut msghdr, flags: c_int) -> isize;
+
+    fn getsockopt(
+        fd: c_int,
+        level: c_int,
+        name: c_int,
+        value: *mut c_void,
+        len: *mut socklen_t,
+    ) -> c_int;
+
+    fn sendmmsg(fd: c_int, msgvec: *mut mmsghdr, vlen: c_uint, flags: c_int) -> c_int;
+
+    fn recvmmsg(
+        fd: c_int,
+        msgvec: *mut mmsghdr,
+        vlen: c_uint,
+        flags: c_int,
+        timeout: *mut c_void,
+    ) -> c_int;
+}
+
+/// Turn a `-1` return value into the last OS error
+#[inline]
+fn cvt(ret: c_int) -> io::Result<c_int> {
+    if ret == -1 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(ret)
+    }
+}
+
+/// Convert a timeout to milliseconds, rounding up so that a short timeout doesn't busy loop
+fn timeout_ms(timeout: Option<Duration>) -> c_int {
+    match timeout {
+        None => -1,
+        Some(dur) => {
+            let ms = dur.as_nanos().div_ceil(1_000_000);
+
+            ms.min(c_int::MAX as u128) as c_int
+        }
+    }
+}
+
+pub fn epoll_create() -> io::Result<OwnedFd> {
+    let fd = cvt(unsafe { epoll_create1(EPOLL_CLOEXEC) })?;
+
+    Ok(unsafe { OwnedFd::from_raw_fd(fd) })
+}
+
+pub fn epoll_add(epfd: RawFd, fd: RawFd, events: u32, data: u64) -> io::Result<()> {
+    let mut event = epoll_event { events, u64: data };
+
+    cvt(unsafe { epoll_ctl(epfd, EPOLL_CTL_ADD, fd, &mut event) }).map(drop)
+}
+
+pub fn epoll_del(epfd: RawFd, fd: RawFd) -> io::Result<()> {
+    let mut event = epoll_event { events: 0, u64: 0 };
+
+    cvt(unsafe { epoll_ctl(epfd, EPOLL_CTL_DEL, fd, &mut event) }).map(drop)
+}
+
+/// Wait for events, an interrupted wait returns no events
+pub fn epoll_wait_events(
+    epfd: RawFd,
+    events: &mut [epoll_event],
+    timeout: Option<Duration>,
+) -> io::Result<usize> {
+    let ret = unsafe {
+        epoll_wait(
+            epfd,
+            events.as_mut_ptr(),
+            events.len() as c_int,
+            timeout_ms(timeout),
+        )
+    };
+
+    match cvt(ret) {
+        Ok(n) => Ok(n as usize),
+        Err(err) if err.kind() == io::ErrorKind::Interrupted => Ok(0),
+        Err(err) => Err(err),
+    }
+}
+
+pub fn event_fd() -> io::Result<OwnedFd> {
+    let fd = cvt(unsafe { eventfd(0, EFD_CLOEXEC | EFD_NONBLOCK) })?;
+
+    Ok(unsafe { OwnedFd::from_raw_fd(fd) })
+}
+
+/// Block the thread until `fd` is ready for `events`, retrying when interrupted
+pub fn poll_fd(fd: RawFd, events: i16, timeout: Option<Duration>) -> io::Result<bool> {
+    let mut pollfd = pollfd {
+        fd,
+        events,
+        revents: 0,
+    };
+
+    loop {
+        match cvt(unsafe { poll(&mut pollfd, 1, timeout_ms(timeout)) }) {
+            Ok(n) => return Ok(n > 0),
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+pub fn set_nonblocking(fd: RawFd) -> io::Result<()> {
+    let flags = cvt(unsafe { fcntl(fd, F_GETFL) })?;
+
+    if flags & O_NONBLOCK != 0 {
+        return Ok(());
+    }
+
+    cvt(unsafe { fcntl(fd, F_SETFL, flags | O_NONBLOCK) }).map(drop)
+}
+
+/// Create a non-blocking, close-on-exec socket
+pub fn new_socket(domain: c_int, ty: c_int) -> io::Result<OwnedFd> {
+    let fd = cvt(unsafe { socket(domain, ty | SOCK_NONBLOCK | SOCK_CLOEXEC, 0) })?;
+
+    Ok(unsafe { OwnedFd::from_raw_fd(fd) })
+}
+
+/// Start connecting a socket, returns false when the connection is still in progress
+pub fn connect_socket(fd: RawFd, addr: *const c_void, len: socklen_t) -> io::Result<bool> {
+    match cvt(unsafe { connect(fd, addr, len) }) {
+        Ok(_) => Ok(true),
+        Err(err) if err.raw_os_error() == Some(EINPROGRESS) => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+impl Default for sockaddr_storage {
+    fn default() -> Self {
+        sockaddr_storage {
+            ss_family: 0,
+            ss_data: [0; 126],
+        }
+    }
+}
+
+impl msghdr {
+    /// A message header for a single buffer, with an optional address
+    pub fn new(name: *mut c_void, namelen: socklen_t, iov: *mut iovec) -> msghdr {
+        msghdr {
+            msg_name: name,
+            msg_namelen: namelen,
+            msg_iov: iov,
+            msg_iovlen: 1,
+            msg_control: ptr::null_mut(),
+            msg_controllen: 0,
+            msg_flags: 0,
+        }
+    }
+}
+
+/// Send several messages with a single syscall, returns the number of messages sent
+pub fn send_mmsg(fd: RawFd, msgs: &mut [mmsghdr]) -> io::Result<usize> {
+    let vlen = msgs.len().min(c_uint::MAX as usize) as c_uint;
+
+    cvt(unsafe { sendmmsg(fd, msgs.as_mut_ptr(), vlen, 0) }).map(|n| n as usize)
+}
+
+/// Receive several messages with a single syscall, returns the number of messages received
+pub fn recv_mmsg(fd: RawFd, msgs: &mut [mmsghdr]) -> io::Result<usize> {
+    let vlen = msgs.len().min(c_uint::MAX as usize) as c_uint;
+
+    cvt(unsafe { recvmmsg(fd, msgs.as_mut_ptr(), vlen, 0, ptr::null_mut()) }).map(|n| n as usize)
+}
+
+/// Turn a `-1` size returned by a syscall into the last OS error
+#[inline]
+fn cvt_size(ret: isize) -> io::Result<usize> {
+    if ret == -1 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(ret as usize)
+    }
+}
+
+pub fn send_msg(fd: RawFd, msg: &msghdr) -> io::Result<usize> {
+    cvt_size(unsafe { sendmsg(fd, msg, 0) })
+}
+
+/// Receive a message, the passed descriptors are created close-on-exec
+pub fn recv_msg(fd: RawFd, msg: &mut msghdr) -> io::Result<usize> {
+    cvt_size(unsafe { recvmsg(fd, msg, MSG_CMSG_CLOEXEC) })
+}
+
+/// Get the credentials of the process on the other end of a Unix socket
+pub fn peer_cred(fd: RawFd) -> io::Result<ucred> {
+    let mut cred = ucred::default();
+    let mut len = mem::size_of::<ucred>() as socklen_t;
+
+    cvt(unsafe {
+        getsockopt(
+            fd,
+            SOL_SOCKET,
+            SO_PEERCRED,
+            &mut cred as *mut ucred as *mut c_void,
+            &mut len,
+        )
+    })?;
+
+    Ok(cred)
+}
+
+/// Round a control message length up to the alignment of the headers
+#[inline]
+pub const fn cmsg_align(len: usize) -> usize {
+    (len + mem::size_of::<usize>() - 1) & !(mem::size_of::<usize>() - 1)
+}
+
+/// Space taken in a control buffer by a message with `len` bytes of data
+#[inline]
+pub const fn cmsg_space(len: usize) -> usize {
+    cmsg_align(mem::size_of::<cmsghdr>()) + cmsg_align(len)
+}
+
+/// Value of `cmsg_len` for a message with `len` bytes of data
+#[inline]
+pub const fn cmsg_len(len: usize) -> usize {
+    cmsg_align(mem::size_of::<cmsghdr>()) + len
+}
@@ -0,0 +1,464 @@
+use core::ffi::c_void;
+use std::{
+    collections::HashMap,
+    io,
+    os::fd::{AsRawFd, OwnedFd},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, AtomicU32, Ordering},
+    },
+    time::Duration,
+};
+
+use log::error;
+
+use crate::io::{
+    IoData, Waker,
+    sys::{EPOLLERR, EPOLLIN, EPOLLOUT, EPOLLRDHUP},
+    uring_sys::{
+        self, EBUSY, IORING_CQE_F_MORE, IORING_FEAT_EXT_ARG, IORING_FEAT_NODROP,
+        IORING_FEAT_SINGLE_MMAP, IORING_OFF_SQ_RING, IORING_OFF_SQES, IORING_OP_POLL_ADD,
+        IORING_OP_POLL_REMOVE, IORING_POLL_ADD_MULTI, io_uring_cqe, io_uring_params, io_uring_sqe,
+    },
+};
+
+/// Number of submission queue entries, the completion queue is twice as large
+const ENTRIES: u32 = 256;
+
+/// User data of the waker poll, the registered descriptors use the address of their `IoData`
+const WAKER_TOKEN: u64 = 0;
+
+/// Tag of the user data of a poll removal, `IoData` addresses are aligned so the bit is free
+const REMOVE_TAG: u64 = 1;
+
+/// Events polled for the registered descriptors, the waker is only polled for input
+const IO_EVENTS: u32 = EPOLLIN | EPOLLOUT | EPOLLRDHUP;
+
+/// How long the setup waits for the probe completion
+const PROBE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Reactor backend built on an io_uring instance
+///
+/// A descriptor is registered with a multishot `POLL_ADD`, which posts a completion every time
+/// the descriptor becomes ready, the same edge-triggered readiness the epoll backend reports.
+/// The backend is readiness-only: the socket operations still issue their reads and writes as
+/// syscalls once the descriptor is ready, exactly as with epoll.
+///
+/// What the ring saves are the syscalls of the readiness bookkeeping. A busy worker reaps the
+/// completions straight from the shared ring, and the registrations and re-armed polls are only
+/// queued in the ring. The owning worker hands them to the kernel in a batch with `flush` after
+/// every task it runs, or with its next poll. A sleeping worker is woken up to submit them.
+///
+/// The kernel keeps a reference to a polled file, so the removal of a poll is submitted before
+/// `deregister` returns, which `Evented` calls before closing the descriptor. Its `IoData` is
+/// kept until the poll posted its last completion.
+pub(crate) struct Uring {
+    ring: OwnedFd,
+
+    /// Submission queue, shared by all the threads
+    sq: Mutex<SubmissionQueue>,
+
+    /// Completion queue, only read by the owning worker
+    cq: CompletionQueue,
+
+    /// Mapping of the rings
+    _rings: Mapping,
+
+    waker: Waker,
+
+    /// The owning worker is about to wait for completions
+    sleeping: AtomicBool,
+
+    /// Deregistered descriptors whose poll is not terminated yet
+    removing: Mutex<HashMap<u64, Arc<IoData>>>,
+}
+
+unsafe impl Send for Uring {}
+unsafe impl Sync for Uring {}
+
+struct SubmissionQueue {
+    head: *const AtomicU32,
+    tail: *const AtomicU32,
+    mask: u32,
+    entries: u32,
+    sqes: Mapping,
+}
+
+struct CompletionQueue {
+    head: *const AtomicU32,
+    tail: *const AtomicU32,
+    mask: u32,
+    cqes: *const io_uring_cqe,
+}
+
+/// Region of the ring mapped into memory
+struct Mapping {
+    ptr: *mut c_void,
+    len: usize,
+}
+
+impl Uring {
+    /// Set up a ring, fails when the kernel refuses it or lacks multishot polls
+    pub(crate) fn new() -> io::Result<Uring> {
+        let mut params = io_uring_params::default();
+
+        let ring = uring_sys::io_uring_setup(ENTRIES, &mut params)?;
+
+        let required = IORING_FEAT_SINGLE_MMAP | IORING_FEAT_NODROP | IORING_FEAT_EXT_ARG;
+
+        if params.features & required != required {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "io_uring is missing required features",
+            ));
+        }
+
+        let sq_len = params.sq_off.array as usize + params.sq_entries as usize * 4;
+        let cq_len =
+            params.cq_off.cqes as usize + params.cq_entries as usize * size_of::<io_uring_cqe>();
+
+        let rings = Mapping::new(&ring, sq_len.max(cq_len), IORING_OFF_SQ_RING)?;
+        let sqes = Mapping::new(
+            &ring,
+            params.sq_entries as usize * size_of::<io_uring_sqe>(),
+            IORING_OFF_SQES,
+        )?;
+
+        let sq = unsafe {
+            let field = |offset: u32| rings.ptr.byte_add(offset as usize);
+
+            // The entries are always written at the index of the same slot
+            let array = field(params.sq_off.array) as *mut u32;
+
+            for i in 0..params.sq_entries {
+                *array.add(i as usize) = i;
+            }
+
+            SubmissionQueue {
+                head: field(params.sq_off.head) as *const AtomicU32,
+                tail: field(params.sq_off.tail) as *const AtomicU32,
+                mask: *(field(params.sq_off.ring_mask) as *const u32),
+                entries: params.sq_entries,
+                sqes,
+            }
+        };
+
+        let cq = unsafe {
+            let field = |offset: u32| rings.ptr.byte_add(offset as usize);
+
+            CompletionQueue {
+                head: field(params.cq_off.head) as *const AtomicU32,
+                tail: field(params.cq_off.tail) as *const AtomicU32,
+                mask: *(field(params.cq_off.ring_mask) as *const u32),
+                cqes: field(params.cq_off.cqes) as *const io_uring_cqe,
+            }
+        };
+
+        let uring = Uring {
+            ring,
+            sq: Mutex::new(sq),
+            cq,
+            _rings: rings,
+            waker: Waker::new()?,
+            sleeping: AtomicBool::new(false),
+            removing: Mutex::new(HashMap::new()),
+        };
+
+        uring.probe()?;
+
+        Ok(uring)
+    }
+
+    /// Arm the poll of the waker, and check that the kernel keeps it armed
+    fn probe(&self) -> io::Result<()> {
+        self.waker.wake();
+        self.push(poll_add(self.waker.as_raw_fd(), EPOLLIN, WAKER_TOKEN))?;
+
+        uring_sys::io_uring_enter(&self.ring, 1, 1, Some(PROBE_TIMEOUT))?;
+
+        let cqe = self.cq.pop().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::TimedOut, "io_uring probe didn't complete")
+        })?;
+
+        self.waker.drain();
+
+        if cqe.res < 0 {
+            return Err(io::Error::from_raw_os_error(-cqe.res));
+        }
+
+        if cqe.flags & IORING_CQE_F_MORE == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "io_uring doesn't support multishot polls",
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn register(&self, io: &Arc<IoData>) -> io::Result<()> {
+        self.submit(poll_add(io.fd, IO_EVENTS, Arc::as_ptr(io) as u64))
+    }
+
+    pub(crate) fn deregister(&self, io: &Arc<IoData>) {
+        let token = Arc::as_ptr(io) as u64;
+
+        // Hold the lock while submitting, so the poller can't re-arm the poll after the removal
+        let mut removing = self.removing.lock().unwrap();
+
+        removing.insert(token, io.clone());
+
+        let sqe = io_uring_sqe {
+            opcode: IORING_OP_POLL_REMOVE,
+            addr: token,
+            user_data: token | REMOVE_TAG,
+            ..Default::default()
+        };
+
+        // The descriptor is closed once this returns, the removal can't wait for a flush
+        if let Err(err) = self.push(sqe).and_then(|_| self.flush()) {
+            error!("Failed to deregister fd {}: {}", io.fd, err);
+        }
+    }
+
+    /// Hand the queued entries to the kernel
+    pub(crate) fn flush(&self) -> io::Result<()> {
+        let sq = self.sq.lock().unwrap();
+        let pending = sq.pending();
+
+        if pending > 0 {
+            uring_sys::io_uring_enter(&self.ring, pending, 0, None)?;
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    pub(crate) fn wake(&self) {
+        self.waker.wake();
+    }
+
+    pub(crate) fn poll(&self, timeout: Option<Duration>) -> usize {
+        let wait = timeout != Some(Duration::ZERO) && self.cq.is_empty();
+
+        if wait {
+            // Pairs with the check in `submit`, the entries pushed from now on come with a wake
+            self.sleeping.store(true, Ordering::SeqCst);
+        }
+
+        // Submit the queued entries along with the wait
+        let to_submit = self.sq.lock().unwrap().pending();
+
+        if wait || to_submit > 0 {
+            let min_complete = if wait { 1 } else { 0 };
+
+            if let Err(err) =
+                uring_sys::io_uring_enter(&self.ring, to_submit, min_complete, timeout)
+            {
+                error!("Failed to poll reactor: {}", err);
+            }
+        }
+
+        if wait {
+            self.sleeping.store(false, Ordering::Relaxed);
+        }
+
+        let mut ready = 0;
+
+        while let Some(cqe) = self.cq.pop() {
+            ready += self.complete(cqe);
+        }
+
+        ready
+    }
+
+    /// Handle a completion, returns 1 if it reported the readiness of a descriptor
+    fn complete(&self, cqe: io_uring_cqe) -> usize {
+        let token = cqe.user_data;
+        let terminated = cqe.flags & IORING_CQE_F_MORE == 0;
+
+        if token == WAKER_TOKEN {
+            self.waker.drain();
+
+            if terminated {
+                self.rearm(self.waker.as_raw_fd(), EPOLLIN, WAKER_TOKEN);
+            }
+
+            return 0;
+        }
+
+        if token & REMOVE_TAG != 0 {
+            // The poll terminated on its own before the removal, its last completion was handled
+            if cqe.res < 0 {
+                self.removing.lock().unwrap().remove(&(token & !REMOVE_TAG));
+            }
+
+            return 0;
+        }
+
+        let (fd, ready) = {
+            let io = unsafe { &*(token as *const IoData) };
+
+            // Let the waiting coroutines retry on a failed poll, the operation reports the error
+            let events = if cqe.res >= 0 {
+                cqe.res as u32
+            } else {
+                EPOLLERR
+            };
+
+            io.ready(events);
+
+            (io.fd, (cqe.res >= 0) as usize)
+        };
+
+        if terminated {
+            let mut removing = self.removing.lock().unwrap();
+
+            // Either the poll was removed and the `IoData` can go, or the kernel dropped it
+            if removing.remove(&token).is_none() {
+                self.rearm(fd, IO_EVENTS, token);
+            }
+        }
+
+        ready
+    }
+
+    fn rearm(&self, fd: i32, events: u32, token: u64) {
+        if let Err(err) = self.submit(poll_add(fd, events, token)) {
+            error!("Failed to re-arm the poll of fd {}: {}", fd, err);
+        }
+    }
+
+    /// Queue an entry and make sure the owning worker submits it soon
+    fn submit(&self, sqe: io_uring_sqe) -> io::Result<()> {
+        self.push(sqe)?;
+
+        if self.sleeping.load(Ordering::SeqCst) {
+            self.waker.wake();
+        }
+
+        Ok(())
+    }
+
+    /// Queue an entry for the next flush, submitting the queue right away when it's full
+    fn push(&self, sqe: io_uring_sqe) -> io::Result<()> {
+        let mut sq = self.sq.lock().unwrap();
+
+        if sq.pending() == sq.entries {
+            uring_sys::io_uring_enter(&self.ring, sq.entries, 0, None)?;
+
+            if sq.pending() == sq.entries {
+                return Err(io::Error::from_raw_os_error(EBUSY));
+            }
+        }
+
+        sq.push(sqe);
+
+        Ok(())
+    }
+}
+
+impl SubmissionQueue {
+    /// Number of entries not consumed by the kernel yet
+    #[inline]
+    fn pending(&self) -> u32 {
+        let tail = unsafe { (*self.tail).load(Ordering::Relaxed) };
+        let head = unsafe { (*self.head).load(Ordering::Acquire) };
+
+        tail.wrapping_sub(head)
+    }
+
+    fn push(&mut self, sqe: io_uring_sqe) {
+        unsafe {
+            let tail = (*self.tail).load(Ordering::Relaxed);
+            let slot = (self.sqes.ptr as *mut io_uring_sqe).add((tail & self.mask) as usize);
+
+            slot.write(sqe);
+
+            (*self.tail).store(tail.wrapping_add(1), Ordering::Release);
+        }
+    }
+}
+
+impl CompletionQueue {
+    #[inline]
+    fn is_empty(&self) -> bool {
+        unsafe { (*self.head).load(Ordering::Relaxed) == (*self.tail).load(Ordering::Acquire) }
+    }
+
+    fn pop(&self) -> Option<io_uring_cqe> {
+        unsafe {
+            let head = (*self.head).load(Ordering::Relaxed);
+
+            if head == (*self.tail).load(Ordering::Acquire) {
+                return None;
+            }
+
+            let cqe = *self.cqes.add((head & self.mask) as usize);
+
+            (*self.head).store(head.wrapping_add(1), Ordering::Release);
+
+            Some(cqe)
+        }
+    }
+}
+
+impl Mapping {
+    fn new(ring: &OwnedFd, len: usize, offset: i64) -> io::Result<Mapping> {
+        Ok(Mapping {
+            ptr: uring_sys::map_ring(ring, len, offset)?,
+            len,
+        })
+    }
+}
+
+impl Drop for Mapping {
+    fn drop(&mut self) {
+        unsafe { uring_sys::unmap_ring(self.ptr, self.len) };
+    }
+}
+
+/// Multishot poll of a descriptor
+fn poll_add(fd: i32, events: u32, token: u64) -> io_uring_sqe {
+    io_uring_sqe {
+        opcode: IORING_OP_POLL_ADD,
+        fd,
+        len: IORING_POLL_ADD_MULTI,
+        op_flags: events,
+        user_data: token,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::os::unix::net::UnixStream;
+
+    use super::*;
+
+    #[test]
+    fn registrations_are_batched_until_a_flush() {
+        let Ok(uring) = Uring::new() else {
+            return;
+        };
+
+        let (a, b) = UnixStream::pair().unwrap();
+        let io_a = Arc::new(IoData::new(a.as_raw_fd()));
+        let io_b = Arc::new(IoData::new(b.as_raw_fd()));
+
+        uring.register(&io_a).unwrap();
+        uring.register(&io_b).unwrap();
+        assert_eq!(uring.sq.lock().unwrap().pending(), 2);
+
+        uring.flush().unwrap();
+        assert_eq!(uring.sq.lock().unwrap().pending(), 0);
+
+        // The removal is submitted right away, the descriptor is closed next
+        uring.deregister(&io_a);
+        assert_eq!(uring.sq.lock().unwrap().pending(), 0);
+
+        uring.deregister(&io_b);
+
+        uring.poll(Some(Duration::ZERO));
+    }
+}
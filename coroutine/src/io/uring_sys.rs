@@ -0,0 +1,251 @@
+use core::ffi::{c_int, c_long, c_uint, c_void};
+use std::{
+    io, mem,
+    os::fd::{AsRawFd, FromRawFd, OwnedFd},
+    ptr,
+    time::Duration,
+};
+
+// The io_uring syscalls were added after the unification of the syscall tables
+pub const SYS_IO_URING_SETUP: c_long = 425;
+pub const SYS_IO_URING_ENTER: c_long = 426;
+
+pub const IORING_OFF_SQ_RING: i64 = 0;
+pub const IORING_OFF_SQES: i64 = 0x10000000;
+
+pub const IORING_FEAT_SINGLE_MMAP: u32 = 1 << 0;
+pub const IORING_FEAT_NODROP: u32 = 1 << 1;
+pub const IORING_FEAT_EXT_ARG: u32 = 1 << 8;
+
+pub const IORING_ENTER_GETEVENTS: u32 = 1 << 0;
+pub const IORING_ENTER_EXT_ARG: u32 = 1 << 3;
+
+pub const IORING_OP_POLL_ADD: u8 = 6;
+pub const IORING_OP_POLL_REMOVE: u8 = 7;
+
+pub const IORING_POLL_ADD_MULTI: u32 = 1 << 0;
+
+pub const IORING_CQE_F_MORE: u32 = 1 << 1;
+
+pub const ETIME: i32 = 62;
+pub const EBUSY: i32 = 16;
+
+const PROT_READ: c_int = 0x1;
+const PROT_WRITE: c_int = 0x2;
+const MAP_SHARED: c_int = 0x01;
+const MAP_POPULATE: c_int = 0x08000;
+const MAP_FAILED: *mut c_void = !0 as *mut c_void;
+
+#[repr(C)]
+#[allow(non_camel_case_types)]
+#[derive(Default)]
+pub struct io_sqring_offsets {
+    pub head: u32,
+    pub tail: u32,
+    pub ring_mask: u32,
+    pub ring_entries: u32,
+    pub flags: u32,
+    pub dropped: u32,
+    pub array: u32,
+    pub resv1: u32,
+    pub user_addr: u64,
+}
+
+#[repr(C)]
+#[allow(non_camel_case_types)]
+#[derive(Default)]
+pub struct io_cqring_offsets {
+    pub head: u32,
+    pub tail: u32,
+    pub ring_mask: u32,
+    pub ring_entries: u32,
+    pub overflow: u32,
+    pub cqes: u32,
+    pub flags: u32,
+    pub resv1: u32,
+    pub user_addr: u64,
+}
+
+#[repr(C)]
+#[allow(non_camel_case_types)]
+#[derive(Default)]
+pub struct io_uring_params {
+    pub sq_entries: u32,
+    pub cq_entries: u32,
+    pub flags: u32,
+    pub sq_thread_cpu: u32,
+    pub sq_thread_idle: u32,
+    pub features: u32,
+    pub wq_fd: u32,
+    pub resv: [u32; 3],
+    pub sq_off: io_sqring_offsets,
+    pub cq_off: io_cqring_offsets,
+}
+
+#[repr(C)]
+#[allow(non_camel_case_types)]
+#[derive(Default, Clone, Copy)]
+pub struct io_uring_sqe {
+    pub opcode: u8,
+    pub flags: u8,
+    pub ioprio: u16,
+    pub fd: i32,
+    pub off: u64,
+    pub addr: u64,
+    pub len: u32,
+    /// `poll32_events` for the poll opcodes
+    pub op_flags: u32,
+    pub user_data: u64,
+    pub buf_index: u16,
+    pub personality: u16,
+    pub splice_fd_in: i32,
+    pub addr3: u64,
+    pub pad: u64,
+}
+
+#[repr(C)]
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy)]
+pub struct io_uring_cqe {
+    pub user_data: u64,
+    pub res: i32,
+    pub flags: u32,
+}
+
+#[repr(C)]
+#[allow(non_camel_case_types)]
+struct __kernel_timespec {
+    tv_sec: i64,
+    tv_nsec: i64,
+}
+
+#[repr(C)]
+#[allow(non_camel_case_types)]
+struct io_uring_getevents_arg {
+    sigmask: u64,
+    sigmask_sz: u32,
+    pad: u32,
+    ts: u64,
+}
+
+unsafe extern "C" {
+    fn syscall(num: c_long, ...) -> c_long;
+
+    fn mmap(
+        addr: *mut c_void,
+        len: usize,
+        prot: c_int,
+        flags: c_int,
+        fd: c_int,
+        offset: i64,
+    ) -> *mut c_void;
+
+    fn munmap(addr: *mut c_void, len: usize) -> c_int;
+}
+
+pub fn io_uring_setup(entries: u32, params: &mut io_uring_params) -> io::Result<OwnedFd> {
+    let ret = unsafe {
+        syscall(
+            SYS_IO_URING_SETUP,
+            entries as c_uint,
+            params as *mut io_uring_params,
+        )
+    };
+
+    if ret < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(unsafe { OwnedFd::from_raw_fd(ret as c_int) })
+}
+
+/// Submit up to `to_submit` entries, and wait for `min_complete` completions at most `timeout`
+/// An interrupted or expired wait is not an error
+pub fn io_uring_enter(
+    ring: &OwnedFd,
+    to_submit: u32,
+    min_complete: u32,
+    timeout: Option<Duration>,
+) -> io::Result<usize> {
+    let mut flags = 0;
+
+    if min_complete > 0 {
+        flags |= IORING_ENTER_GETEVENTS;
+    }
+
+    // The kernel only reads the arguments during the call
+    let ts = timeout.map(|dur| __kernel_timespec {
+        tv_sec: dur.as_secs() as i64,
+        tv_nsec: dur.subsec_nanos() as i64,
+    });
+
+    let arg = ts.as_ref().map(|ts| io_uring_getevents_arg {
+        sigmask: 0,
+        sigmask_sz: 0,
+        pad: 0,
+        ts: ts as *const __kernel_timespec as u64,
+    });
+
+    let (arg_ptr, arg_size) = match &arg {
+        Some(arg) if min_complete > 0 => {
+            flags |= IORING_ENTER_EXT_ARG;
+
+            (
+                arg as *const io_uring_getevents_arg as *const c_void,
+                mem::size_of::<io_uring_getevents_arg>(),
+            )
+        }
+        _ => (ptr::null(), 0),
+    };
+
+    let ret = unsafe {
+        syscall(
+            SYS_IO_URING_ENTER,
+            ring.as_raw_fd() as c_uint,
+            to_submit as c_uint,
+            min_complete as c_uint,
+            flags as c_uint,
+            arg_ptr,
+            arg_size,
+        )
+    };
+
+    if ret >= 0 {
+        return Ok(ret as usize);
+    }
+
+    let err = io::Error::last_os_error();
+
+    match err.raw_os_error() {
+        Some(ETIME) => Ok(0),
+        _ if err.kind() == io::ErrorKind::Interrupted => Ok(0),
+        _ => Err(err),
+    }
+}
+
+/// Map a region of the ring into memory
+pub fn map_ring(ring: &OwnedFd, len: usize, offset: i64) -> io::Result<*mut c_void> {
+    let ptr = unsafe {
+        mmap(
+            ptr::null_mut(),
+            len,
+            PROT_READ | PROT_WRITE,
+            MAP_SHARED | MAP_POPULATE,
+            ring.as_raw_fd(),
+            offset,
+        )
+    };
+
+    if ptr == MAP_FAILED {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(ptr)
+}
+
+/// # Safety
+///
+/// The region must have been returned by `map_ring` with the same length, and not be used anymore
+pub unsafe fn unmap_ring(ptr: *mut c_void, len: usize) {
+    unsafe { munmap(ptr, len) };
+}
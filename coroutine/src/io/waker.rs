@@ -0,0 +1,44 @@
+use std::{
+    fs::File,
+    io::{self, Read, Write},
+    os::fd::{AsRawFd, RawFd},
+};
+
+use log::error;
+
+use crate::io::sys;
+
+/// Eventfd used to interrupt the blocking poll of a reactor
+pub(crate) struct Waker {
+    file: File,
+}
+
+impl Waker {
+    pub(crate) fn new() -> io::Result<Waker> {
+        Ok(Waker {
+            file: File::from(sys::event_fd()?),
+        })
+    }
+
+    pub(crate) fn wake(&self) {
+        if let Err(err) = (&self.file).write_all(&1u64.to_ne_bytes()) {
+            // A full counter already wakes up the poller
+            if err.kind() != io::ErrorKind::WouldBlock {
+                error!("Failed to wake up reactor: {}", err);
+            }
+        }
+    }
+
+    /// Reset the counter so that the next `wake` is reported again
+    pub(crate) fn drain(&self) {
+        let mut buf = [0u8; 8];
+
+        while (&self.file).read(&mut buf).is_ok() {}
+    }
+}
+
+impl AsRawFd for Waker {
+    fn as_raw_fd(&self) -> RawFd {
+        self.file.as_raw_fd()
+    }
+}
@@ -4,13 +4,24 @@ use std::{
         Arc,
         atomic::{AtomicBool, Ordering},
     },
+    task::Waker,
+    time::Duration,
 };
 
-use crate::sync::AtomicOption;
+use crate::sync::{AtomicOption, blocker::Blocker};
+
+/// What waits for the coroutine to be done
+pub(crate) enum Waiter {
+    /// A coroutine or a thread blocked in a join
+    Blocker(Arc<Blocker>),
+
+    /// A task awaiting the join handle
+    Waker(Waker),
+}
 
 pub struct Join {
-    /// The coroutine thats waiting for this join handler
-    pub(crate) to_wake: AtomicOption<Arc<Blocker>>,
+    /// The coroutine or the task thats waiting for this join handler
+    pub(crate) to_wake: AtomicOption<Waiter>,
 
     /// The flaf indicate if the host coroutine is not finished
     /// When set to false, the coroutine is done
@@ -32,32 +43,70 @@ impl Join {
     }
 
     /// Sets the panic information for the coroutine
-    pub fn set_panic_data(&self, panic: Arc<AtomicOption<Box<dyn Any + Send>>>) {
+    pub fn set_panic_data(&self, panic: Box<dyn Any + Send>) {
         self.panic.store(panic);
     }
 
     pub fn trigger(&self) {
         self.state.store(false, Ordering::Release);
 
-        if let Some(blocker) = self.to_wake.take() {
-            blocker.unpark()
+        match self.to_wake.take() {
+            Some(Waiter::Blocker(blocker)) => blocker.unpark(),
+            Some(Waiter::Waker(waker)) => waker.wake(),
+            None => {}
         }
     }
 
-    pub(crate) fn wait(&self) {
-        if self.state.load(Ordering::Acquire) {
-            let current_blocker = Blocker::current();
+    /// Return true if the coroutine is done
+    #[inline]
+    pub(crate) fn is_done(&self) -> bool {
+        !self.state.load(Ordering::Acquire)
+    }
+
+    /// Block until the coroutine is done, or `timeout` elapses
+    /// Returns true if the coroutine is done
+    pub(crate) fn wait(&self, timeout: Option<Duration>) -> bool {
+        if self.is_done() {
+            return true;
+        }
+
+        let current_blocker = Blocker::current();
+
+        // Register the blocker first
+        self.to_wake.store(Waiter::Blocker(current_blocker.clone()));
 
-            // Register the blocker first
-            self.to_wake.store(current_blocker.clone());
+        // Re-check the state
+        if self.is_done() {
+            self.to_wake.take();
+
+            return true;
+        }
 
-            // Re-check the state
-            if self.state.load(Ordering::Acquire) {
-                // Successfully register the blocker
-                current_blocker.park(None).ok();
-            } else {
-                self.to_wake.take();
-            }
+        // Successfully register the blocker
+        if current_blocker.park(timeout).is_err() {
+            // Unregister, unless the coroutine got done meanwhile and took the blocker already
+            self.to_wake.take();
         }
+
+        self.is_done()
+    }
+
+    /// Wake `waker` once the coroutine is done
+    /// Returns true if the coroutine is already done, `waker` is not registered then
+    pub(crate) fn register_waker(&self, waker: Waker) -> bool {
+        if self.is_done() {
+            return true;
+        }
+
+        self.to_wake.store(Waiter::Waker(waker));
+
+        // Re-check the state, the coroutine may have been done before the waker was registered
+        if self.is_done() {
+            self.to_wake.take();
+
+            return true;
+        }
+
+        false
     }
 }
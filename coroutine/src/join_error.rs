@@ -0,0 +1,139 @@
+use std::{any::Any, error, fmt, panic};
+
+use crate::error::Error;
+
+/// Error returned when joining a coroutine which didn't complete
+pub enum JoinError {
+    /// The coroutine panicked, with the panic payload
+    Panicked(Box<dyn Any + Send>),
+
+    /// The coroutine was cancelled
+    Cancelled,
+
+    /// The coroutine overflowed its stack
+    StackOverflow,
+
+    /// The coroutine was resumed with a value of the wrong type
+    TypeMismatch,
+}
+
+impl JoinError {
+    /// Classify the payload the coroutine unwound with
+    pub(crate) fn from_panic(payload: Box<dyn Any + Send>) -> JoinError {
+        match payload.downcast_ref::<Error>() {
+            Some(Error::Cancel) => JoinError::Cancelled,
+            Some(Error::StackErr) => JoinError::StackOverflow,
+            Some(Error::TypeErr) => JoinError::TypeMismatch,
+            _ => JoinError::Panicked(payload),
+        }
+    }
+
+    /// Returns true if the coroutine was cancelled
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self, JoinError::Cancelled)
+    }
+
+    /// Returns true if the coroutine panicked
+    pub fn is_panic(&self) -> bool {
+        matches!(self, JoinError::Panicked(_))
+    }
+
+    /// Returns the panic payload
+    ///
+    /// # Panics
+    /// Panics if the coroutine didn't panic, see `try_into_panic`
+    pub fn into_panic(self) -> Box<dyn Any + Send> {
+        self.try_into_panic()
+            .unwrap_or_else(|err| panic!("`into_panic` called on a {:?} error", err))
+    }
+
+    /// Returns the panic payload, or the error itself if the coroutine didn't panic
+    pub fn try_into_panic(self) -> Result<Box<dyn Any + Send>, JoinError> {
+        match self {
+            JoinError::Panicked(payload) => Ok(payload),
+            err => Err(err),
+        }
+    }
+
+    /// Unwinds the current thread or coroutine the way the joined coroutine unwound
+    /// A cancel is propagated as a cancel of the current coroutine
+    pub fn resume_unwind(self) -> ! {
+        let payload: Box<dyn Any + Send> = match self {
+            JoinError::Panicked(payload) => payload,
+            JoinError::Cancelled => Box::new(Error::Cancel),
+            JoinError::StackOverflow => Box::new(Error::StackErr),
+            JoinError::TypeMismatch => Box::new(Error::TypeErr),
+        };
+
+        panic::resume_unwind(payload)
+    }
+}
+
+/// Message of a panic payload, when it's a string
+fn panic_message(payload: &(dyn Any + Send)) -> Option<&str> {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+}
+
+impl fmt::Debug for JoinError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            JoinError::Panicked(ref payload) => match panic_message(&**payload) {
+                Some(message) => f.debug_tuple("Panicked").field(&message).finish(),
+                None => f.debug_tuple("Panicked").field(&"..").finish(),
+            },
+            JoinError::Cancelled => f.write_str("Cancelled"),
+            JoinError::StackOverflow => f.write_str("StackOverflow"),
+            JoinError::TypeMismatch => f.write_str("TypeMismatch"),
+        }
+    }
+}
+
+impl fmt::Display for JoinError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            JoinError::Panicked(ref payload) => match panic_message(&**payload) {
+                Some(message) => write!(f, "Coroutine panicked: {}", message),
+                None => f.write_str("Coroutine panicked"),
+            },
+            JoinError::Cancelled => f.write_str("Coroutine was cancelled"),
+            JoinError::StackOverflow => f.write_str("Coroutine overflowed its stack"),
+            JoinError::TypeMismatch => f.write_str("Coroutine was resumed with a mismatched type"),
+        }
+    }
+}
+
+impl error::Error for JoinError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spawn;
+
+    #[test]
+    fn join_classifies_the_unwind() {
+        let panicked = unsafe { spawn::<_, ()>(|| panic!("boom")) }
+            .join()
+            .unwrap_err();
+
+        assert!(panicked.is_panic());
+        assert_eq!(panicked.to_string(), "Coroutine panicked: boom");
+        assert_eq!(panicked.into_panic().downcast_ref::<&str>(), Some(&"boom"));
+
+        let cancelled = unsafe { spawn::<_, ()>(|| panic::panic_any(Error::Cancel)) }
+            .join()
+            .unwrap_err();
+
+        assert!(cancelled.is_cancelled());
+        assert!(cancelled.try_into_panic().is_err());
+
+        let resumed = panic::catch_unwind(|| JoinError::Cancelled.resume_unwind()).unwrap_err();
+
+        assert!(matches!(
+            JoinError::from_panic(resumed),
+            JoinError::Cancelled
+        ));
+    }
+}
@@ -1,10 +1,13 @@
 use std::{
     any::Any,
-    sync::{Arc, atomic::Ordering},
-    thread::Result,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
 };
 
-use crate::{Coroutine, error::Error, join::Join, sync::AtomicOption};
+use crate::{Coroutine, JoinError, join::Join, sync::AtomicOption};
 
 /// JoinHandle for Coroutine
 pub struct JoinHandle<T> {
@@ -12,6 +15,9 @@ pub struct JoinHandle<T> {
     join: Arc<Join>,
     packet: Arc<AtomicOption<T>>,
     panic: Arc<AtomicOption<Box<dyn Any + Send>>>,
+
+    /// The result was taken by `try_join`, `join_timeout` or by awaiting the handle
+    taken: bool,
 }
 
 unsafe impl<T: Send> Send for JoinHandle<T> {}
@@ -29,6 +35,7 @@ pub fn make_join_handle<T>(
         join,
         packet,
         panic,
+        taken: false,
     }
 }
 
@@ -40,21 +47,137 @@ impl<T> JoinHandle<T> {
 
     /// Return true if the coroutine is finished
     pub fn is_done(&self) -> bool {
-        !self.join.state.load(Ordering::Acquire)
+        self.join.is_done()
     }
 
     /// Block until the coroutine is done
     pub fn wait(&self) {
-        self.join.wait();
+        // The park of a wait without timeout can still end early, when it's cancelled
+        while !self.join.wait(None) {}
+    }
+
+    /// Join the coroutine, returning the result produced or why it didn't complete
+    ///
+    /// # Panics
+    /// Panics if the result was already taken by `try_join` or `join_timeout`
+    pub fn join(mut self) -> Result<T, JoinError> {
+        self.wait();
+
+        self.take_result()
+    }
+
+    /// Join the coroutine, waiting for at most `dur`
+    /// Returns none if the coroutine is not done once `dur` elapsed
+    ///
+    /// # Panics
+    /// Panics if the result was already taken
+    pub fn join_timeout(&mut self, dur: Duration) -> Option<Result<T, JoinError>> {
+        if !self.join.wait(Some(dur)) {
+            return None;
+        }
+
+        Some(self.take_result())
+    }
+
+    /// Join the coroutine if it's done, without waiting
+    ///
+    /// # Panics
+    /// Panics if the result was already taken
+    pub fn try_join(&mut self) -> Option<Result<T, JoinError>> {
+        if !self.is_done() {
+            return None;
+        }
+
+        Some(self.take_result())
+    }
+
+    fn take_result(&mut self) -> Result<T, JoinError> {
+        assert!(!self.taken, "JoinHandle result already taken");
+
+        self.taken = true;
+
+        // A coroutine without result nor panic was cancelled
+        self.packet.take().ok_or_else(|| {
+            self.panic
+                .take()
+                .map_or(JoinError::Cancelled, JoinError::from_panic)
+        })
     }
+}
+
+/// Awaiting the handle joins the coroutine, the task is woken once the coroutine is done
+///
+/// # Panics
+/// Polling the handle again after it returned `Ready`, or after the result was taken by
+/// `try_join` or `join_timeout`, panics
+impl<T> Future for JoinHandle<T> {
+    type Output = Result<T, JoinError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        if !this.join.register_waker(cx.waker().clone()) {
+            return Poll::Pending;
+        }
+
+        Poll::Ready(this.take_result())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        task::Wake,
+        thread::{self, Thread},
+    };
+
+    use super::*;
+    use crate::{spawn, sync::mpsc};
+
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    #[test]
+    fn join_timeout_and_try_join() {
+        let (tx, rx) = mpsc::channel::<()>();
+        let mut handle = unsafe { spawn(move || rx.recv().is_ok()) };
+
+        assert!(handle.try_join().is_none());
+        assert!(handle.join_timeout(Duration::from_millis(10)).is_none());
+
+        drop(tx);
+
+        assert!(
+            !handle
+                .join_timeout(Duration::from_secs(5))
+                .unwrap()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn await_handle() {
+        let (tx, rx) = mpsc::channel::<u32>();
+        let mut handle = unsafe { spawn(move || rx.recv().unwrap() + 1) };
+
+        let waker = Arc::new(ThreadWaker(thread::current())).into();
+        let mut cx = Context::from_waker(&waker);
+
+        assert!(Pin::new(&mut handle).poll(&mut cx).is_pending());
+
+        tx.send(1).unwrap();
 
-    /// Join the coroutine, returning the result produced
-    pub fn join(self) -> Result<T> {
-        self.join.wait();
+        loop {
+            if let Poll::Ready(result) = Pin::new(&mut handle).poll(&mut cx) {
+                break assert_eq!(result.unwrap(), 2);
+            }
 
-        // Take the result
-        self.packet
-            .take()
-            .ok_or_else(|| self.panic.take().unwrap_or_else(|| Box::new(Error::Cancel)))
+            thread::park();
+        }
     }
 }
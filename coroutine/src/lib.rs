@@ -1,52 +1,126 @@
-use std::{borrow::Cow, fmt, ptr, sync::Arc};
+use std::{
+    borrow::Cow,
+    fmt,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+};
 
 use cancel::Cancel;
+use coroutine_local::{get_coroutine_local, get_coroutine_local_data};
 use done::Done;
+use event::{EventResult, EventSubscriber};
 use park::Park;
-
+use status::{State, Status};
+
+pub use block_on::block_on;
+pub use builder::CoroutineBuilder;
+pub use cancel::disable_cancel;
+pub use config::{Config, ConfigBuilder, config};
+pub use config_error::ConfigError;
+pub use generator::{Generator, Scope};
+pub use group::CoroutineGroup;
+pub use group_error::{Failure, GroupError};
+pub use interval::Interval;
+pub use join_error::JoinError;
+pub use join_handle::JoinHandle;
+pub use local_key::LocalKey;
+pub use park::ParkError;
+pub use scope::{CoroutineScope, scope};
+pub use scoped_join_handle::ScopedJoinHandle;
+pub use sleep::{sleep, sleep_until};
+pub use spawn::{spawn, spawn_future};
+pub use spawn_blocking::spawn_blocking;
+pub use stack_usage::StackUsage;
+pub use yield_now::{done, get_yield, yield_, yield_with};
+
+/// The generator type backing every coroutine
+pub(crate) type CoroutineImpl = Generator<'static, EventResult, EventSubscriber>;
+
+mod block_on;
+mod blocking_pool;
 mod builder;
 mod cancel;
 mod cold;
+mod config;
+mod config_error;
+pub mod context;
 mod coroutine_local;
 mod done;
 mod error;
 mod event;
+pub mod fs;
+mod generator;
+mod group;
+mod group_error;
 mod guard;
 mod id_hasher;
+mod interval;
+mod io;
 mod join;
+mod join_error;
 mod join_handle;
-mod likely;
+mod local_key;
+pub mod net;
 mod park;
+mod pool;
+mod queue;
 mod register_context;
+pub mod registry;
 mod runtime;
+mod scheduler;
+mod scope;
+mod scoped_join_handle;
+pub mod select;
+mod sleep;
 mod spawn;
+mod spawn_blocking;
 mod stack;
-mod sync;
+pub mod stack_profile;
+mod stack_usage;
+mod status;
+pub mod sync;
+mod timer;
 mod unlikely;
 mod yield_now;
 
 pub(crate) struct Inner {
+    id: u64,
     name: Option<Cow<'static, str>>,
     stack_size: usize,
     park: Park,
     cancel: Cancel,
+    status: Status,
 }
 
+/// Handle to a spawned coroutine
 #[derive(Clone)]
-pub(crate) struct Coroutine {
+pub struct Coroutine {
     inner: Arc<Inner>,
 }
 
 impl Coroutine {
-    fn new(name: impl Into<Cow<'static, str>>, stack_size: usize) -> Coroutine {
-        Coroutine {
-            inner: Arc::new(Inner {
-                name: Some(name.into()),
-                stack_size,
-                park: Park::new(),
-                cancel: Cancel::new(),
-            }),
-        }
+    fn new(name: Option<Cow<'static, str>>, stack_size: usize) -> Coroutine {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+        let inner = Arc::new(Inner {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            name,
+            stack_size,
+            park: Park::new(),
+            cancel: Cancel::new(),
+            status: Status::new(),
+        });
+
+        registry::register(&inner);
+
+        Coroutine { inner }
+    }
+
+    /// Gets the id of the coroutine, unique for the whole process
+    pub fn id(&self) -> u64 {
+        self.inner.id
     }
 
     // Gets the coroutine stack size
@@ -59,7 +133,12 @@ impl Coroutine {
         self.inner.park.unpark();
     }
 
-    // Cancel a coroutine
+    /// Cancel a coroutine
+    ///
+    /// # Safety
+    ///
+    /// The coroutine unwinds from the point it is suspended at, the resources it holds must
+    /// tolerate being dropped there.
     pub unsafe fn cancel(&self) {
         unsafe {
             self.inner.cancel.cancel();
@@ -84,30 +163,91 @@ impl fmt::Debug for Coroutine {
     }
 }
 
+/// Returns the handle of the running coroutine
+///
+/// # Panics
+///
+/// Panics out of coroutines, see `try_current`
+pub fn current() -> Coroutine {
+    try_current().expect("Not running in a coroutine")
+}
+
+/// Returns the handle of the running coroutine, none out of coroutines
+pub fn try_current() -> Option<Coroutine> {
+    get_coroutine_local_data().map(|local| unsafe { local.as_ref() }.get_coroutine().clone())
+}
+
 /// Run the coroutine
 pub(crate) fn run_coroutine(mut coroutine: CoroutineImpl) {
+    registry::set_state(&coroutine, State::Running);
+
     match coroutine.resume() {
         Some(event_subscriber) => event_subscriber.subscribe(coroutine),
         None => {
             // Panic happened here
             let local = unsafe { &mut *get_coroutine_local(&coroutine) };
             let join = local.get_join();
+            let mut overflowed = false;
 
             // Set the panic data
             if let Some(panic) = coroutine.get_panic_data() {
+                overflowed = panic.downcast_ref::<error::Error>() == Some(&error::Error::StackErr);
+
                 join.set_panic_data(panic);
             }
 
             // Trigger the join here
             join.trigger();
 
-            Done::drop_coroutine(coroutine);
+            if overflowed {
+                Done::drop_overflowed(coroutine);
+            } else {
+                Done::drop_coroutine(coroutine);
+            }
         }
     }
 }
 
+/// Get the cancel data of the running coroutine
+#[inline]
+pub(crate) fn current_cancel_data() -> &'static Cancel {
+    let local = get_coroutine_local_data().expect("Not running in a coroutine");
+    let cancel = unsafe { local.as_ref() }.get_coroutine().get_cancel();
+
+    // The coroutine local storage lives as long as the coroutine
+    unsafe { &*(cancel as *const Cancel) }
+}
+
+/// Get the cancel data of a suspended coroutine
+#[inline]
+pub(crate) fn coroutine_cancel_data(coroutine: &CoroutineImpl) -> &'static Cancel {
+    let local = unsafe { &*get_coroutine_local(coroutine) };
+    let cancel = local.get_coroutine().get_cancel();
+
+    unsafe { &*(cancel as *const Cancel) }
+}
+
 /// Returns true if current context is coroutine
 pub(crate) fn is_coroutine() -> bool {
     // We will never call this function in a pure generator context
     get_coroutine_local_data().is_some()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn current_is_the_running_coroutine() {
+        assert!(try_current().is_none());
+
+        let handle = unsafe { CoroutineBuilder::new().name("current").spawn(current) }.unwrap();
+        let id = handle.coroutine().id();
+
+        let coroutine = handle.join().unwrap();
+
+        assert_eq!(coroutine.id(), id);
+        assert_eq!(coroutine.name(), Some("current"));
+        assert_ne!(unsafe { spawn(|| current().id()) }.join().unwrap(), id);
+    }
+}
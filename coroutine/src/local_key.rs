@@ -0,0 +1,215 @@
+use std::{
+    any::TypeId,
+    cell::{Cell, RefCell},
+    fmt,
+};
+
+use crate::coroutine_local::{self, Opaque};
+
+/// Declares a coroutine local value, accessed through a [`LocalKey`](crate::LocalKey)
+///
+/// Each coroutine gets its own value, lazily initialized with the expression on first access. Out
+/// of coroutines the key falls back to a thread local value. The values of a coroutine are dropped
+/// once it's done.
+///
+/// The values of a coroutine are dropped by its worker thread after the coroutine finished, out
+/// of the coroutine. So their destructors must not:
+///
+/// - access coroutine local data: other keys, `context::get` and `try_current` reach the values of
+///   the worker thread instead, and `current` panics;
+/// - block: a coroutine `Mutex`, a send on a full channel and the like park the worker thread
+///   itself, stalling all the coroutines scheduled on it.
+#[macro_export]
+macro_rules! coroutine_local {
+    () => {};
+
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $t:ty = $init:expr; $($rest:tt)*) => {
+        $crate::coroutine_local!($(#[$attr])* $vis static $name: $t = $init);
+        $crate::coroutine_local!($($rest)*);
+    };
+
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $t:ty = $init:expr) => {
+        $(#[$attr])*
+        $vis static $name: $crate::LocalKey<$t> = {
+            fn __init() -> $t {
+                $init
+            }
+
+            fn __key() -> ::std::any::TypeId {
+                struct __Key;
+
+                ::std::any::TypeId::of::<__Key>()
+            }
+
+            $crate::LocalKey::new(__init, __key)
+        };
+    };
+}
+
+/// A key to a coroutine local value, declared with `coroutine_local!`
+pub struct LocalKey<T: 'static> {
+    init: fn() -> T,
+
+    /// Identifies the key in the local map, unique to each declaration
+    key: fn() -> TypeId,
+}
+
+impl<T: 'static> LocalKey<T> {
+    #[doc(hidden)]
+    pub const fn new(init: fn() -> T, key: fn() -> TypeId) -> LocalKey<T> {
+        LocalKey { init, key }
+    }
+
+    /// Acquires a reference to the value of the current coroutine, or of the current thread out of
+    /// coroutines, initializing it on first access
+    pub fn with<F, R>(&'static self, f: F) -> R
+    where
+        F: FnOnce(&T) -> R,
+    {
+        let key = (self.key)();
+
+        let value = coroutine_local::with(|map| {
+            if let Some(value) = map.borrow().get(&key) {
+                return &**value as *const dyn Opaque as *const T;
+            }
+
+            // Initialize without borrowing the map, the initializer may use other keys
+            let value: Box<dyn Opaque> = Box::new((self.init)());
+            let mut map = map.borrow_mut();
+            let value = map.entry(key).or_insert(value);
+
+            &**value as *const dyn Opaque as *const T
+        });
+
+        // The values are boxed and only dropped once the coroutine or the thread is done
+        f(unsafe { &*value })
+    }
+}
+
+impl<T: 'static> LocalKey<Cell<T>> {
+    /// Sets the value
+    pub fn set(&'static self, value: T) {
+        self.with(|cell| cell.set(value));
+    }
+
+    /// Returns a copy of the value
+    pub fn get(&'static self) -> T
+    where
+        T: Copy,
+    {
+        self.with(Cell::get)
+    }
+
+    /// Takes the value, leaving `Default::default()` in its place
+    pub fn take(&'static self) -> T
+    where
+        T: Default,
+    {
+        self.with(Cell::take)
+    }
+
+    /// Replaces the value, returning the old one
+    pub fn replace(&'static self, value: T) -> T {
+        self.with(|cell| cell.replace(value))
+    }
+}
+
+impl<T: 'static> LocalKey<RefCell<T>> {
+    /// Sets the value
+    ///
+    /// # Panics
+    /// Panics if the value is currently borrowed
+    pub fn set(&'static self, value: T) {
+        self.replace(value);
+    }
+
+    /// Takes the value, leaving `Default::default()` in its place
+    ///
+    /// # Panics
+    /// Panics if the value is currently borrowed
+    pub fn take(&'static self) -> T
+    where
+        T: Default,
+    {
+        self.with(RefCell::take)
+    }
+
+    /// Replaces the value, returning the old one
+    ///
+    /// # Panics
+    /// Panics if the value is currently borrowed
+    pub fn replace(&'static self, value: T) -> T {
+        self.with(|cell| cell.replace(value))
+    }
+}
+
+impl<T: 'static> fmt::Debug for LocalKey<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LocalKey").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::{Duration, Instant},
+    };
+
+    use super::*;
+    use crate::{sleep, spawn};
+
+    static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+    struct Tracked;
+
+    impl Drop for Tracked {
+        fn drop(&mut self) {
+            DROPPED.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    coroutine_local! {
+        static COUNTER: Cell<u32> = Cell::new(0);
+        static TRACKED: RefCell<Option<Tracked>> = RefCell::new(None);
+    }
+
+    #[test]
+    fn each_coroutine_gets_its_own_value() {
+        COUNTER.set(10);
+
+        let handles: Vec<_> = (0..10)
+            .map(|i| unsafe {
+                spawn(move || {
+                    COUNTER.set(COUNTER.get() + i);
+
+                    sleep(Duration::from_millis(1));
+
+                    COUNTER.replace(0)
+                })
+            })
+            .collect();
+
+        for (i, handle) in handles.into_iter().enumerate() {
+            assert_eq!(handle.join().unwrap(), i as u32);
+        }
+
+        assert_eq!(COUNTER.take(), 10);
+    }
+
+    #[test]
+    fn values_are_dropped_with_the_coroutine() {
+        unsafe { spawn(|| TRACKED.set(Some(Tracked))) }
+            .join()
+            .unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+
+        // The join is triggered right before the coroutine is dropped
+        while DROPPED.load(Ordering::SeqCst) == 0 {
+            assert!(Instant::now() < deadline);
+
+            sleep(Duration::from_millis(1));
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use core::ffi::{c_int, c_void};
+use std::{
+    io, mem,
+    os::fd::{AsRawFd, BorrowedFd, FromRawFd, OwnedFd, RawFd},
+    ptr,
+};
+
+use crate::{
+    io::sys::{self, SCM_RIGHTS, SOL_SOCKET, cmsghdr, iovec, msghdr},
+    net::UCred,
+};
+
+/// Maximum number of descriptors the kernel accepts in a single message
+pub(crate) const SCM_MAX_FD: usize = 253;
+
+/// Send `buf` along with the descriptors in a `SCM_RIGHTS` control message
+pub(crate) fn send_with_fds(fd: RawFd, buf: &[u8], fds: &[BorrowedFd<'_>]) -> io::Result<usize> {
+    if fds.len() > SCM_MAX_FD {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Too many file descriptors in a single message",
+        ));
+    }
+
+    let mut iov = iovec {
+        iov_base: buf.as_ptr() as *mut c_void,
+        iov_len: buf.len(),
+    };
+
+    let mut msg = msghdr::new(ptr::null_mut(), 0, &mut iov);
+
+    let data_len = fds.len() * mem::size_of::<c_int>();
+    let mut control = control_buffer(data_len);
+
+    if !fds.is_empty() {
+        let control = control.as_mut_ptr() as *mut u8;
+
+        unsafe {
+            (control as *mut cmsghdr).write(cmsghdr {
+                cmsg_len: sys::cmsg_len(data_len),
+                cmsg_level: SOL_SOCKET,
+                cmsg_type: SCM_RIGHTS,
+            });
+
+            let data = control.add(sys::cmsg_align(mem::size_of::<cmsghdr>())) as *mut c_int;
+
+            for (i, fd) in fds.iter().enumerate() {
+                data.add(i).write_unaligned(fd.as_raw_fd());
+            }
+        }
+
+        msg.msg_control = control as *mut c_void;
+        msg.msg_controllen = sys::cmsg_space(data_len);
+    }
+
+    sys::send_msg(fd, &msg)
+}
+
+/// Receive into `buf`, appending the descriptors passed along to `fds`
+/// The descriptors beyond `SCM_MAX_FD` are closed by the kernel
+pub(crate) fn recv_with_fds(
+    fd: RawFd,
+    buf: &mut [u8],
+    fds: &mut Vec<OwnedFd>,
+) -> io::Result<usize> {
+    let mut iov = iovec {
+        iov_base: buf.as_mut_ptr() as *mut c_void,
+        iov_len: buf.len(),
+    };
+
+    let mut msg = msghdr::new(ptr::null_mut(), 0, &mut iov);
+
+    let data_len = SCM_MAX_FD * mem::size_of::<c_int>();
+    let mut control = control_buffer(data_len);
+
+    msg.msg_control = control.as_mut_ptr() as *mut c_void;
+    msg.msg_controllen = sys::cmsg_space(data_len);
+
+    let n = sys::recv_msg(fd, &mut msg)?;
+
+    let control = control.as_ptr() as *const u8;
+    let header_len = sys::cmsg_align(mem::size_of::<cmsghdr>());
+    let mut offset = 0;
+
+    while offset + mem::size_of::<cmsghdr>() <= msg.msg_controllen {
+        let header = unsafe { (control.add(offset) as *const cmsghdr).read_unaligned() };
+
+        if header.cmsg_len < header_len || offset + header.cmsg_len > msg.msg_controllen {
+            break;
+        }
+
+        if header.cmsg_level == SOL_SOCKET && header.cmsg_type == SCM_RIGHTS {
+            let data = unsafe { control.add(offset + header_len) as *const c_int };
+            let count = (header.cmsg_len - header_len) / mem::size_of::<c_int>();
+
+            for i in 0..count {
+                fds.push(unsafe { OwnedFd::from_raw_fd(data.add(i).read_unaligned()) });
+            }
+        }
+
+        offset += sys::cmsg_align(header.cmsg_len);
+    }
+
+    Ok(n)
+}
+
+/// Credentials of the peer of a Unix socket
+pub(crate) fn peer_cred(fd: RawFd) -> io::Result<UCred> {
+    let cred = sys::peer_cred(fd)?;
+
+    Ok(UCred {
+        pid: cred.pid,
+        uid: cred.uid,
+        gid: cred.gid,
+    })
+}
+
+/// Zeroed control buffer aligned for the message headers
+fn control_buffer(data_len: usize) -> Vec<u64> {
+    vec![0; sys::cmsg_space(data_len).div_ceil(mem::size_of::<u64>())]
+}
@@ -0,0 +1,25 @@
+//! Networking primitives that suspend the running coroutine instead of blocking its worker
+//!
+//! Outside of coroutines the types block the calling thread like their std counterparts.
+
+mod ancillary;
+mod readiness;
+mod recv_meta;
+mod socket_addr;
+mod tcp_listener;
+mod tcp_stream;
+mod ucred;
+mod udp_socket;
+mod unix_datagram;
+mod unix_listener;
+mod unix_stream;
+
+pub use readiness::Readiness;
+pub use recv_meta::RecvMeta;
+pub use tcp_listener::{Incoming, TcpListener};
+pub use tcp_stream::TcpStream;
+pub use ucred::UCred;
+pub use udp_socket::UdpSocket;
+pub use unix_datagram::UnixDatagram;
+pub use unix_listener::{UnixIncoming, UnixListener};
+pub use unix_stream::UnixStream;
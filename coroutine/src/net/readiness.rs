@@ -0,0 +1,58 @@
+use std::{sync::Arc, time::Duration};
+
+use crate::{
+    io::{
+        Interest, IoData,
+        sys::{self, POLLIN, POLLOUT},
+    },
+    select::{Selectable, Token},
+};
+
+/// Readiness of a socket in one direction, an operation for `select!` and `join!`
+///
+/// It completes once the socket can be read or written without waiting.
+pub struct Readiness<'a> {
+    io: &'a Arc<IoData>,
+    interest: Interest,
+}
+
+impl<'a> Readiness<'a> {
+    pub(crate) fn new(io: &'a Arc<IoData>, interest: Interest) -> Readiness<'a> {
+        Readiness { io, interest }
+    }
+
+    /// Check the socket without waiting, an error counts as ready so the next operation reports it
+    fn poll(&self) -> bool {
+        let events = match self.interest {
+            Interest::Read => POLLIN,
+            Interest::Write => POLLOUT,
+        };
+
+        sys::poll_fd(self.io.fd, events, Some(Duration::ZERO)).unwrap_or(true)
+    }
+}
+
+impl Selectable for Readiness<'_> {
+    type Output = ();
+
+    fn try_select(&mut self) -> Option<()> {
+        self.poll().then_some(())
+    }
+
+    fn watch(&mut self, token: &Token) {
+        if self.io.register().is_err() {
+            return token.wake();
+        }
+
+        self.io.watch(self.interest, token);
+
+        // The socket may have become ready before the token was stored
+        if self.poll() {
+            token.wake();
+        }
+    }
+
+    fn unwatch(&mut self, token: &Token) {
+        self.io.unwatch(self.interest, token);
+    }
+}
@@ -0,0 +1,20 @@
+use std::net::{Ipv4Addr, SocketAddr};
+
+/// Metadata of a datagram received by `UdpSocket::recv_mmsg`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecvMeta {
+    /// Number of bytes written to the buffer
+    pub len: usize,
+
+    /// Address the datagram was sent from
+    pub addr: SocketAddr,
+}
+
+impl Default for RecvMeta {
+    fn default() -> Self {
+        RecvMeta {
+            len: 0,
+            addr: SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)),
+        }
+    }
+}
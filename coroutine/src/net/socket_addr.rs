@@ -0,0 +1,87 @@
+use core::ffi::{c_int, c_void};
+use std::{
+    io, mem,
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6},
+};
+
+use crate::io::sys::{AF_INET, AF_INET6, sockaddr_in, sockaddr_in6, sockaddr_storage, socklen_t};
+
+/// A socket address in the layout expected by the socket syscalls
+pub(crate) enum RawSocketAddr {
+    V4(sockaddr_in),
+    V6(sockaddr_in6),
+}
+
+impl RawSocketAddr {
+    /// Address family of the socket to create for this address
+    pub(crate) fn family(&self) -> c_int {
+        match self {
+            RawSocketAddr::V4(_) => AF_INET,
+            RawSocketAddr::V6(_) => AF_INET6,
+        }
+    }
+
+    /// Pointer and length to pass to the syscalls
+    pub(crate) fn as_ptr(&self) -> (*const c_void, socklen_t) {
+        match self {
+            RawSocketAddr::V4(addr) => (
+                addr as *const _ as *const c_void,
+                mem::size_of::<sockaddr_in>() as socklen_t,
+            ),
+            RawSocketAddr::V6(addr) => (
+                addr as *const _ as *const c_void,
+                mem::size_of::<sockaddr_in6>() as socklen_t,
+            ),
+        }
+    }
+}
+
+impl From<&SocketAddr> for RawSocketAddr {
+    fn from(addr: &SocketAddr) -> Self {
+        match addr {
+            SocketAddr::V4(addr) => RawSocketAddr::V4(sockaddr_in {
+                sin_family: AF_INET as u16,
+                sin_port: addr.port().to_be(),
+                sin_addr: addr.ip().octets(),
+                sin_zero: [0; 8],
+            }),
+            SocketAddr::V6(addr) => RawSocketAddr::V6(sockaddr_in6 {
+                sin6_family: AF_INET6 as u16,
+                sin6_port: addr.port().to_be(),
+                sin6_flowinfo: addr.flowinfo(),
+                sin6_addr: addr.ip().octets(),
+                sin6_scope_id: addr.scope_id(),
+            }),
+        }
+    }
+}
+
+/// Read back an address filled in by a syscall
+pub(crate) fn to_socket_addr(storage: &sockaddr_storage) -> io::Result<SocketAddr> {
+    let ptr = storage as *const sockaddr_storage;
+
+    match storage.ss_family as c_int {
+        AF_INET => {
+            let addr = unsafe { &*(ptr as *const sockaddr_in) };
+
+            Ok(SocketAddr::V4(SocketAddrV4::new(
+                Ipv4Addr::from(addr.sin_addr),
+                u16::from_be(addr.sin_port),
+            )))
+        }
+        AF_INET6 => {
+            let addr = unsafe { &*(ptr as *const sockaddr_in6) };
+
+            Ok(SocketAddr::V6(SocketAddrV6::new(
+                Ipv6Addr::from(addr.sin6_addr),
+                u16::from_be(addr.sin6_port),
+                addr.sin6_flowinfo,
+                addr.sin6_scope_id,
+            )))
+        }
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Invalid socket address family",
+        )),
+    }
+}
@@ -0,0 +1,129 @@
+use std::{
+    fmt, io,
+    net::{self, SocketAddr, ToSocketAddrs},
+    os::fd::{AsRawFd, RawFd},
+};
+
+use crate::{
+    io::{Evented, Interest},
+    net::{Readiness, TcpStream},
+};
+
+/// A TCP socket server, listening for connections
+///
+/// `accept` suspends the running coroutine until a connection arrives.
+pub struct TcpListener {
+    inner: Evented<net::TcpListener>,
+}
+
+impl TcpListener {
+    /// Creates a new `TcpListener` bound to the address
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<TcpListener> {
+        net::TcpListener::bind(addr).and_then(TcpListener::from_std)
+    }
+
+    /// Wrap a std listener, which is switched to non-blocking mode
+    pub fn from_std(listener: net::TcpListener) -> io::Result<TcpListener> {
+        Ok(TcpListener {
+            inner: Evented::new(listener)?,
+        })
+    }
+
+    /// Accept a new incoming connection
+    pub fn accept(&self) -> io::Result<(TcpStream, SocketAddr)> {
+        let (stream, addr) = self.inner.do_io(Interest::Read, |l| l.accept())?;
+
+        Ok((TcpStream::from_std(stream)?, addr))
+    }
+
+    /// Returns an iterator over the connections being received on this listener
+    pub fn incoming(&self) -> Incoming<'_> {
+        Incoming { listener: self }
+    }
+
+    /// Returns the local socket address of this listener
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.inner.get_ref().local_addr()
+    }
+
+    /// Sets the value for the `IP_TTL` option on this socket
+    pub fn set_ttl(&self, ttl: u32) -> io::Result<()> {
+        self.inner.get_ref().set_ttl(ttl)
+    }
+
+    /// Gets the value of the `IP_TTL` option for this socket
+    pub fn ttl(&self) -> io::Result<u32> {
+        self.inner.get_ref().ttl()
+    }
+
+    /// Get the value of the `SO_ERROR` option on this socket
+    pub fn take_error(&self) -> io::Result<Option<io::Error>> {
+        self.inner.get_ref().take_error()
+    }
+
+    /// Readiness to accept a connection, for `select!` and `join!`
+    pub fn readable(&self) -> Readiness<'_> {
+        self.inner.readiness(Interest::Read)
+    }
+}
+
+impl AsRawFd for TcpListener {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
+
+impl fmt::Debug for TcpListener {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.get_ref().fmt(f)
+    }
+}
+
+/// An iterator that infinitely accepts connections on a `TcpListener`
+#[derive(Debug)]
+pub struct Incoming<'a> {
+    listener: &'a TcpListener,
+}
+
+impl Iterator for Incoming<'_> {
+    type Item = io::Result<TcpStream>;
+
+    fn next(&mut self) -> Option<io::Result<TcpStream>> {
+        Some(self.listener.accept().map(|(stream, _)| stream))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, thread, time::Duration};
+
+    use super::*;
+    use crate::spawn;
+
+    #[test]
+    fn concurrent_acceptors() {
+        let listener = Arc::new(TcpListener::bind("127.0.0.1:0").unwrap());
+        let addr = listener.local_addr().unwrap();
+
+        let mut acceptors: Vec<_> = (0..2)
+            .map(|_| {
+                let listener = listener.clone();
+
+                unsafe { spawn(move || listener.accept().map(|_| ()).unwrap()) }
+            })
+            .collect();
+
+        // Let both acceptors suspend on the listener before the connections arrive
+        thread::sleep(Duration::from_millis(100));
+
+        let _clients: Vec<_> = (0..2)
+            .map(|_| net::TcpStream::connect(addr).unwrap())
+            .collect();
+
+        for acceptor in &mut acceptors {
+            let result = acceptor.join_timeout(Duration::from_secs(2));
+
+            assert!(matches!(result, Some(Ok(()))));
+        }
+    }
+}
@@ -0,0 +1,259 @@
+use std::{
+    fmt,
+    io::{self, IoSlice, IoSliceMut, Read, Write},
+    net::{self, Shutdown, SocketAddr, ToSocketAddrs},
+    os::fd::{AsRawFd, RawFd},
+};
+
+use crate::{
+    io::{
+        Evented, Interest,
+        sys::{self, SOCK_STREAM},
+    },
+    is_coroutine,
+    net::{Readiness, socket_addr::RawSocketAddr},
+};
+
+/// A TCP stream between a local and a remote socket
+///
+/// Reads and writes suspend the running coroutine until the socket is ready. A stream can be
+/// read and written concurrently through `&TcpStream` from two coroutines.
+pub struct TcpStream {
+    inner: Evented<net::TcpStream>,
+}
+
+impl TcpStream {
+    /// Opens a TCP connection to a remote host
+    /// Every resolved address is tried in turn until one connects
+    pub fn connect<A: ToSocketAddrs>(addr: A) -> io::Result<TcpStream> {
+        let mut last_err = None;
+
+        for addr in addr.to_socket_addrs()? {
+            match TcpStream::connect_addr(&addr) {
+                Ok(stream) => return Ok(stream),
+                Err(err) => last_err = Some(err),
+            }
+        }
+
+        Err(last_err.unwrap_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Could not resolve to any addresses",
+            )
+        }))
+    }
+
+    fn connect_addr(addr: &SocketAddr) -> io::Result<TcpStream> {
+        if !is_coroutine() {
+            return net::TcpStream::connect(addr).and_then(TcpStream::from_std);
+        }
+
+        let raw = RawSocketAddr::from(addr);
+        let socket = sys::new_socket(raw.family(), SOCK_STREAM)?;
+        let (ptr, len) = raw.as_ptr();
+        let connected = sys::connect_socket(socket.as_raw_fd(), ptr, len)?;
+        let stream = TcpStream::from_std(net::TcpStream::from(socket))?;
+
+        if !connected {
+            // The connection is established or failed once the socket becomes writable
+            stream.inner.wait(Interest::Write)?;
+
+            if let Some(err) = stream.take_error()? {
+                return Err(err);
+            }
+        }
+
+        Ok(stream)
+    }
+
+    /// Wrap a std stream, which is switched to non-blocking mode
+    pub fn from_std(stream: net::TcpStream) -> io::Result<TcpStream> {
+        Ok(TcpStream {
+            inner: Evented::new(stream)?,
+        })
+    }
+
+    /// Returns the socket address of the remote peer of this TCP connection
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.inner.get_ref().peer_addr()
+    }
+
+    /// Returns the socket address of the local half of this TCP connection
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.inner.get_ref().local_addr()
+    }
+
+    /// Shuts down the read, write, or both halves of this connection
+    pub fn shutdown(&self, how: Shutdown) -> io::Result<()> {
+        self.inner.get_ref().shutdown(how)
+    }
+
+    /// Creates a new independently owned handle to the underlying socket
+    pub fn try_clone(&self) -> io::Result<TcpStream> {
+        self.inner
+            .get_ref()
+            .try_clone()
+            .and_then(TcpStream::from_std)
+    }
+
+    /// Receives data on the socket without removing it from the queue
+    pub fn peek(&self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.do_io(Interest::Read, |s| s.peek(buf))
+    }
+
+    /// Sets the value of the `TCP_NODELAY` option on this socket
+    pub fn set_nodelay(&self, nodelay: bool) -> io::Result<()> {
+        self.inner.get_ref().set_nodelay(nodelay)
+    }
+
+    /// Gets the value of the `TCP_NODELAY` option on this socket
+    pub fn nodelay(&self) -> io::Result<bool> {
+        self.inner.get_ref().nodelay()
+    }
+
+    /// Sets the value for the `IP_TTL` option on this socket
+    pub fn set_ttl(&self, ttl: u32) -> io::Result<()> {
+        self.inner.get_ref().set_ttl(ttl)
+    }
+
+    /// Gets the value of the `IP_TTL` option for this socket
+    pub fn ttl(&self) -> io::Result<u32> {
+        self.inner.get_ref().ttl()
+    }
+
+    /// Get the value of the `SO_ERROR` option on this socket
+    pub fn take_error(&self) -> io::Result<Option<io::Error>> {
+        self.inner.get_ref().take_error()
+    }
+
+    /// Readiness to read, for `select!` and `join!`
+    pub fn readable(&self) -> Readiness<'_> {
+        self.inner.readiness(Interest::Read)
+    }
+
+    /// Readiness to write, for `select!` and `join!`
+    pub fn writable(&self) -> Readiness<'_> {
+        self.inner.readiness(Interest::Write)
+    }
+}
+
+impl Read for TcpStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        (&*self).read(buf)
+    }
+
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        (&*self).read_vectored(bufs)
+    }
+}
+
+impl Write for TcpStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        (&*self).write(buf)
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        (&*self).write_vectored(bufs)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Read for &TcpStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.do_io(Interest::Read, |mut s| s.read(buf))
+    }
+
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        self.inner
+            .do_io(Interest::Read, |mut s| s.read_vectored(bufs))
+    }
+}
+
+impl Write for &TcpStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.do_io(Interest::Write, |mut s| s.write(buf))
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        self.inner
+            .do_io(Interest::Write, |mut s| s.write_vectored(bufs))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl AsRawFd for TcpStream {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
+
+impl fmt::Debug for TcpStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.get_ref().fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{net::TcpListener, spawn};
+
+    fn echo_server() -> (SocketAddr, crate::JoinHandle<()>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = unsafe {
+            spawn(move || {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut buf = [0; 5];
+
+                stream.read_exact(&mut buf).unwrap();
+                stream.write_all(&buf).unwrap();
+            })
+        };
+
+        (addr, server)
+    }
+
+    #[test]
+    fn echo_between_coroutines() {
+        let (addr, server) = echo_server();
+
+        let client = unsafe {
+            spawn(move || {
+                let mut stream = TcpStream::connect(addr).unwrap();
+                let mut buf = [0; 5];
+
+                stream.write_all(b"hello").unwrap();
+                stream.read_exact(&mut buf).unwrap();
+
+                buf
+            })
+        };
+
+        assert_eq!(&client.join().unwrap(), b"hello");
+
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn blocking_fallback_on_threads() {
+        let (addr, server) = echo_server();
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        let mut buf = [0; 5];
+
+        stream.write_all(b"world").unwrap();
+        stream.read_exact(&mut buf).unwrap();
+
+        assert_eq!(&buf, b"world");
+
+        server.join().unwrap();
+    }
+}
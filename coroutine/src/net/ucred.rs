@@ -0,0 +1,15 @@
+/// Credentials of the process on the other end of a Unix socket
+///
+/// The credentials are the ones the peer had when it connected, or when the socket pair was
+/// created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct UCred {
+    /// Process id of the peer
+    pub pid: i32,
+
+    /// Effective user id of the peer
+    pub uid: u32,
+
+    /// Effective group id of the peer
+    pub gid: u32,
+}
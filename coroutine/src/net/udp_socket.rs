@@ -0,0 +1,378 @@
+use core::ffi::c_void;
+use std::{
+    fmt, io, mem,
+    net::{self, Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs},
+    os::fd::{AsRawFd, RawFd},
+    ptr,
+};
+
+use crate::{
+    io::{
+        Evented, Interest,
+        sys::{self, iovec, mmsghdr, msghdr, sockaddr_storage, socklen_t},
+    },
+    net::{
+        Readiness, RecvMeta,
+        socket_addr::{RawSocketAddr, to_socket_addr},
+    },
+};
+
+/// A UDP socket
+///
+/// Sends and receives suspend the running coroutine until the socket is ready. The `_mmsg`
+/// variants move a batch of datagrams with a single syscall.
+pub struct UdpSocket {
+    inner: Evented<net::UdpSocket>,
+}
+
+impl UdpSocket {
+    /// Creates a UDP socket bound to the address
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<UdpSocket> {
+        net::UdpSocket::bind(addr).and_then(UdpSocket::from_std)
+    }
+
+    /// Wrap a std socket, which is switched to non-blocking mode
+    pub fn from_std(socket: net::UdpSocket) -> io::Result<UdpSocket> {
+        Ok(UdpSocket {
+            inner: Evented::new(socket)?,
+        })
+    }
+
+    /// Connects the socket to a remote address, `send` and `recv` then use this address
+    pub fn connect<A: ToSocketAddrs>(&self, addr: A) -> io::Result<()> {
+        self.inner.get_ref().connect(addr)
+    }
+
+    /// Sends a datagram to the address, returns the number of bytes written
+    pub fn send_to<A: ToSocketAddrs>(&self, buf: &[u8], addr: A) -> io::Result<usize> {
+        let addr = addr.to_socket_addrs()?.next().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "No addresses to send data to")
+        })?;
+
+        self.inner.do_io(Interest::Write, |s| s.send_to(buf, addr))
+    }
+
+    /// Receives a datagram, returns the number of bytes read and the address it came from
+    pub fn recv_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        self.inner.do_io(Interest::Read, |s| s.recv_from(buf))
+    }
+
+    /// Receives a datagram without removing it from the queue
+    pub fn peek_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        self.inner.do_io(Interest::Read, |s| s.peek_from(buf))
+    }
+
+    /// Sends a datagram to the connected address
+    pub fn send(&self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.do_io(Interest::Write, |s| s.send(buf))
+    }
+
+    /// Receives a datagram from the connected address
+    pub fn recv(&self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.do_io(Interest::Read, |s| s.recv(buf))
+    }
+
+    /// Receives a datagram from the connected address without removing it from the queue
+    pub fn peek(&self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.do_io(Interest::Read, |s| s.peek(buf))
+    }
+
+    /// Sends every buffer as a datagram to its address with a single syscall
+    /// Returns the number of datagrams sent, which is at least one for a non-empty batch
+    pub fn send_mmsg_to(&self, msgs: &[(&[u8], SocketAddr)]) -> io::Result<usize> {
+        let mut addrs: Vec<RawSocketAddr> = msgs.iter().map(|(_, addr)| addr.into()).collect();
+        let mut iovs: Vec<iovec> = msgs.iter().map(|(buf, _)| send_iovec(buf)).collect();
+
+        let mut hdrs: Vec<mmsghdr> = addrs
+            .iter_mut()
+            .zip(iovs.iter_mut())
+            .map(|(addr, iov)| {
+                let (name, namelen) = addr.as_ptr();
+
+                mmsghdr {
+                    msg_hdr: msghdr::new(name as *mut c_void, namelen, iov),
+                    msg_len: 0,
+                }
+            })
+            .collect();
+
+        self.send_mmsg_hdrs(&mut hdrs)
+    }
+
+    /// Sends every buffer as a datagram to the connected address with a single syscall
+    /// Returns the number of datagrams sent, which is at least one for a non-empty batch
+    pub fn send_mmsg(&self, bufs: &[&[u8]]) -> io::Result<usize> {
+        let mut iovs: Vec<iovec> = bufs.iter().map(|buf| send_iovec(buf)).collect();
+
+        let mut hdrs: Vec<mmsghdr> = iovs
+            .iter_mut()
+            .map(|iov| mmsghdr {
+                msg_hdr: msghdr::new(ptr::null_mut(), 0, iov),
+                msg_len: 0,
+            })
+            .collect();
+
+        self.send_mmsg_hdrs(&mut hdrs)
+    }
+
+    fn send_mmsg_hdrs(&self, hdrs: &mut [mmsghdr]) -> io::Result<usize> {
+        if hdrs.is_empty() {
+            return Ok(0);
+        }
+
+        self.inner
+            .do_io(Interest::Write, |s| sys::send_mmsg(s.as_raw_fd(), hdrs))
+    }
+
+    /// Receives up to one datagram per buffer with a single syscall
+    ///
+    /// Waits for at least one datagram, then takes the ones already queued. The length and the
+    /// source of the datagram received in `bufs[i]` are written to `meta[i]`. Returns the number
+    /// of datagrams received.
+    pub fn recv_mmsg(&self, bufs: &mut [&mut [u8]], meta: &mut [RecvMeta]) -> io::Result<usize> {
+        let batch = bufs.len().min(meta.len());
+
+        if batch == 0 {
+            return Ok(0);
+        }
+
+        let mut addrs = vec![sockaddr_storage::default(); batch];
+        let mut iovs: Vec<iovec> = bufs[..batch]
+            .iter_mut()
+            .map(|buf| iovec {
+                iov_base: buf.as_mut_ptr() as *mut c_void,
+                iov_len: buf.len(),
+            })
+            .collect();
+
+        let mut hdrs: Vec<mmsghdr> = addrs
+            .iter_mut()
+            .zip(iovs.iter_mut())
+            .map(|(addr, iov)| mmsghdr {
+                msg_hdr: msghdr::new(
+                    addr as *mut sockaddr_storage as *mut c_void,
+                    mem::size_of::<sockaddr_storage>() as socklen_t,
+                    iov,
+                ),
+                msg_len: 0,
+            })
+            .collect();
+
+        let n = self
+            .inner
+            .do_io(Interest::Read, |s| sys::recv_mmsg(s.as_raw_fd(), &mut hdrs))?;
+
+        for i in 0..n {
+            meta[i] = RecvMeta {
+                len: hdrs[i].msg_len as usize,
+                addr: to_socket_addr(&addrs[i])?,
+            };
+        }
+
+        Ok(n)
+    }
+
+    /// Returns the address of the remote peer this socket is connected to
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.inner.get_ref().peer_addr()
+    }
+
+    /// Returns the local address this socket is bound to
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.inner.get_ref().local_addr()
+    }
+
+    /// Creates a new independently owned handle to the underlying socket
+    pub fn try_clone(&self) -> io::Result<UdpSocket> {
+        self.inner
+            .get_ref()
+            .try_clone()
+            .and_then(UdpSocket::from_std)
+    }
+
+    /// Sets the value of the `SO_BROADCAST` option for this socket
+    pub fn set_broadcast(&self, broadcast: bool) -> io::Result<()> {
+        self.inner.get_ref().set_broadcast(broadcast)
+    }
+
+    /// Gets the value of the `SO_BROADCAST` option for this socket
+    pub fn broadcast(&self) -> io::Result<bool> {
+        self.inner.get_ref().broadcast()
+    }
+
+    /// Joins an IPv4 multicast group on the interface with the address `interface`
+    pub fn join_multicast_v4(&self, multiaddr: &Ipv4Addr, interface: &Ipv4Addr) -> io::Result<()> {
+        self.inner.get_ref().join_multicast_v4(multiaddr, interface)
+    }
+
+    /// Leaves an IPv4 multicast group joined with `join_multicast_v4`
+    pub fn leave_multicast_v4(&self, multiaddr: &Ipv4Addr, interface: &Ipv4Addr) -> io::Result<()> {
+        self.inner
+            .get_ref()
+            .leave_multicast_v4(multiaddr, interface)
+    }
+
+    /// Joins an IPv6 multicast group on the interface with the index `interface`, 0 for any
+    pub fn join_multicast_v6(&self, multiaddr: &Ipv6Addr, interface: u32) -> io::Result<()> {
+        self.inner.get_ref().join_multicast_v6(multiaddr, interface)
+    }
+
+    /// Leaves an IPv6 multicast group joined with `join_multicast_v6`
+    pub fn leave_multicast_v6(&self, multiaddr: &Ipv6Addr, interface: u32) -> io::Result<()> {
+        self.inner
+            .get_ref()
+            .leave_multicast_v6(multiaddr, interface)
+    }
+
+    /// Sets the value of the `IP_MULTICAST_LOOP` option for this socket
+    pub fn set_multicast_loop_v4(&self, multicast_loop: bool) -> io::Result<()> {
+        self.inner.get_ref().set_multicast_loop_v4(multicast_loop)
+    }
+
+    /// Gets the value of the `IP_MULTICAST_LOOP` option for this socket
+    pub fn multicast_loop_v4(&self) -> io::Result<bool> {
+        self.inner.get_ref().multicast_loop_v4()
+    }
+
+    /// Sets the value of the `IP_MULTICAST_TTL` option for this socket
+    pub fn set_multicast_ttl_v4(&self, ttl: u32) -> io::Result<()> {
+        self.inner.get_ref().set_multicast_ttl_v4(ttl)
+    }
+
+    /// Gets the value of the `IP_MULTICAST_TTL` option for this socket
+    pub fn multicast_ttl_v4(&self) -> io::Result<u32> {
+        self.inner.get_ref().multicast_ttl_v4()
+    }
+
+    /// Sets the value of the `IPV6_MULTICAST_LOOP` option for this socket
+    pub fn set_multicast_loop_v6(&self, multicast_loop: bool) -> io::Result<()> {
+        self.inner.get_ref().set_multicast_loop_v6(multicast_loop)
+    }
+
+    /// Gets the value of the `IPV6_MULTICAST_LOOP` option for this socket
+    pub fn multicast_loop_v6(&self) -> io::Result<bool> {
+        self.inner.get_ref().multicast_loop_v6()
+    }
+
+    /// Sets the value for the `IP_TTL` option on this socket
+    pub fn set_ttl(&self, ttl: u32) -> io::Result<()> {
+        self.inner.get_ref().set_ttl(ttl)
+    }
+
+    /// Gets the value of the `IP_TTL` option for this socket
+    pub fn ttl(&self) -> io::Result<u32> {
+        self.inner.get_ref().ttl()
+    }
+
+    /// Get the value of the `SO_ERROR` option on this socket
+    pub fn take_error(&self) -> io::Result<Option<io::Error>> {
+        self.inner.get_ref().take_error()
+    }
+
+    /// Readiness to read, for `select!` and `join!`
+    pub fn readable(&self) -> Readiness<'_> {
+        self.inner.readiness(Interest::Read)
+    }
+
+    /// Readiness to write, for `select!` and `join!`
+    pub fn writable(&self) -> Readiness<'_> {
+        self.inner.readiness(Interest::Write)
+    }
+}
+
+impl AsRawFd for UdpSocket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
+
+impl fmt::Debug for UdpSocket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.get_ref().fmt(f)
+    }
+}
+
+/// The kernel only reads from the buffers of a send
+#[inline]
+fn send_iovec(buf: &[u8]) -> iovec {
+    iovec {
+        iov_base: buf.as_ptr() as *mut c_void,
+        iov_len: buf.len(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spawn;
+
+    #[test]
+    fn echo_between_coroutines() {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+
+        let server = unsafe {
+            spawn(move || {
+                let mut buf = [0; 16];
+                let (n, peer) = server.recv_from(&mut buf).unwrap();
+
+                server.send_to(&buf[..n], peer).unwrap();
+            })
+        };
+
+        let client = unsafe {
+            spawn(move || {
+                let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+                let mut buf = [0; 16];
+
+                socket.connect(addr).unwrap();
+                socket.send(b"hello").unwrap();
+
+                let n = socket.recv(&mut buf).unwrap();
+
+                buf[..n].to_vec()
+            })
+        };
+
+        assert_eq!(client.join().unwrap(), b"hello");
+
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn batched_send_and_recv() {
+        let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let (to, from) = (receiver.local_addr().unwrap(), sender.local_addr().unwrap());
+
+        let receiver = unsafe {
+            spawn(move || {
+                let mut bufs = [[0u8; 8]; 3];
+                let mut meta = [RecvMeta::default(); 3];
+                let mut received = Vec::new();
+
+                while received.len() < 3 {
+                    let mut slices: Vec<&mut [u8]> = bufs.iter_mut().map(|b| &mut b[..]).collect();
+                    let n = receiver.recv_mmsg(&mut slices, &mut meta).unwrap();
+
+                    for (buf, meta) in bufs.iter().zip(&meta).take(n) {
+                        assert_eq!(meta.addr, from);
+
+                        received.push(buf[..meta.len].to_vec());
+                    }
+                }
+
+                received
+            })
+        };
+
+        let msgs: [(&[u8], SocketAddr); 3] = [(b"one", to), (b"two", to), (b"three", to)];
+
+        assert_eq!(sender.send_mmsg_to(&msgs).unwrap(), 3);
+
+        assert_eq!(
+            receiver.join().unwrap(),
+            [b"one".to_vec(), b"two".to_vec(), b"three".to_vec()]
+        );
+    }
+}
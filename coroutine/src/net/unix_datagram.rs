@@ -0,0 +1,146 @@
+use std::{
+    fmt, io,
+    net::Shutdown,
+    os::{
+        fd::{AsRawFd, BorrowedFd, OwnedFd, RawFd},
+        unix::net::{self, SocketAddr},
+    },
+    path::Path,
+};
+
+use crate::{
+    io::{Evented, Interest},
+    net::{Readiness, UCred, ancillary},
+};
+
+/// A Unix domain datagram socket
+///
+/// Sends and receives suspend the running coroutine until the socket is ready, and block the
+/// calling thread outside of coroutines.
+pub struct UnixDatagram {
+    inner: Evented<net::UnixDatagram>,
+}
+
+impl UnixDatagram {
+    /// Creates a socket bound to the path
+    pub fn bind<P: AsRef<Path>>(path: P) -> io::Result<UnixDatagram> {
+        net::UnixDatagram::bind(path).and_then(UnixDatagram::from_std)
+    }
+
+    /// Creates a socket which is not bound to any address
+    pub fn unbound() -> io::Result<UnixDatagram> {
+        net::UnixDatagram::unbound().and_then(UnixDatagram::from_std)
+    }
+
+    /// Creates an unnamed pair of connected sockets
+    pub fn pair() -> io::Result<(UnixDatagram, UnixDatagram)> {
+        let (a, b) = net::UnixDatagram::pair()?;
+
+        Ok((UnixDatagram::from_std(a)?, UnixDatagram::from_std(b)?))
+    }
+
+    /// Wrap a std socket, which is switched to non-blocking mode
+    pub fn from_std(socket: net::UnixDatagram) -> io::Result<UnixDatagram> {
+        Ok(UnixDatagram {
+            inner: Evented::new(socket)?,
+        })
+    }
+
+    /// Connects the socket to the path, `send` and `recv` then use this address
+    pub fn connect<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.inner.get_ref().connect(path)
+    }
+
+    /// Sends a datagram to the socket bound to the path
+    pub fn send_to<P: AsRef<Path>>(&self, buf: &[u8], path: P) -> io::Result<usize> {
+        let path = path.as_ref();
+
+        self.inner.do_io(Interest::Write, |s| s.send_to(buf, path))
+    }
+
+    /// Receives a datagram, returns the number of bytes read and the address it came from
+    pub fn recv_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        self.inner.do_io(Interest::Read, |s| s.recv_from(buf))
+    }
+
+    /// Sends a datagram to the connected address
+    pub fn send(&self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.do_io(Interest::Write, |s| s.send(buf))
+    }
+
+    /// Receives a datagram from the connected address
+    pub fn recv(&self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.do_io(Interest::Read, |s| s.recv(buf))
+    }
+
+    /// Sends a datagram carrying the descriptors to the connected address
+    /// At most 253 descriptors can be sent at once
+    pub fn send_with_fds(&self, buf: &[u8], fds: &[BorrowedFd<'_>]) -> io::Result<usize> {
+        self.inner.do_io(Interest::Write, |s| {
+            ancillary::send_with_fds(s.as_raw_fd(), buf, fds)
+        })
+    }
+
+    /// Receives a datagram, appending the descriptors it carries to `fds`
+    /// Returns the number of bytes read
+    pub fn recv_with_fds(&self, buf: &mut [u8], fds: &mut Vec<OwnedFd>) -> io::Result<usize> {
+        self.inner.do_io(Interest::Read, |s| {
+            ancillary::recv_with_fds(s.as_raw_fd(), buf, fds)
+        })
+    }
+
+    /// Returns the credentials of the process on the other end of a socket pair
+    pub fn peer_cred(&self) -> io::Result<UCred> {
+        ancillary::peer_cred(self.as_raw_fd())
+    }
+
+    /// Returns the address of this socket
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.inner.get_ref().local_addr()
+    }
+
+    /// Returns the address of the socket this socket is connected to
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.inner.get_ref().peer_addr()
+    }
+
+    /// Shuts down the read, write, or both halves of this socket
+    pub fn shutdown(&self, how: Shutdown) -> io::Result<()> {
+        self.inner.get_ref().shutdown(how)
+    }
+
+    /// Creates a new independently owned handle to the underlying socket
+    pub fn try_clone(&self) -> io::Result<UnixDatagram> {
+        self.inner
+            .get_ref()
+            .try_clone()
+            .and_then(UnixDatagram::from_std)
+    }
+
+    /// Get the value of the `SO_ERROR` option on this socket
+    pub fn take_error(&self) -> io::Result<Option<io::Error>> {
+        self.inner.get_ref().take_error()
+    }
+
+    /// Readiness to read, for `select!` and `join!`
+    pub fn readable(&self) -> Readiness<'_> {
+        self.inner.readiness(Interest::Read)
+    }
+
+    /// Readiness to write, for `select!` and `join!`
+    pub fn writable(&self) -> Readiness<'_> {
+        self.inner.readiness(Interest::Write)
+    }
+}
+
+impl AsRawFd for UnixDatagram {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
+
+impl fmt::Debug for UnixDatagram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.get_ref().fmt(f)
+    }
+}
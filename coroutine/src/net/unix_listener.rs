@@ -0,0 +1,95 @@
+use std::{
+    fmt, io,
+    os::{
+        fd::{AsRawFd, RawFd},
+        unix::net::{self, SocketAddr},
+    },
+    path::Path,
+};
+
+use crate::{
+    io::{Evented, Interest},
+    net::{Readiness, UnixStream},
+};
+
+/// A Unix domain socket server, listening for connections
+///
+/// `accept` suspends the running coroutine until a connection arrives.
+pub struct UnixListener {
+    inner: Evented<net::UnixListener>,
+}
+
+impl UnixListener {
+    /// Creates a new `UnixListener` bound to the path
+    pub fn bind<P: AsRef<Path>>(path: P) -> io::Result<UnixListener> {
+        net::UnixListener::bind(path).and_then(UnixListener::from_std)
+    }
+
+    /// Wrap a std listener, which is switched to non-blocking mode
+    pub fn from_std(listener: net::UnixListener) -> io::Result<UnixListener> {
+        Ok(UnixListener {
+            inner: Evented::new(listener)?,
+        })
+    }
+
+    /// Accept a new incoming connection
+    pub fn accept(&self) -> io::Result<(UnixStream, SocketAddr)> {
+        let (stream, addr) = self.inner.do_io(Interest::Read, |l| l.accept())?;
+
+        Ok((UnixStream::from_std(stream)?, addr))
+    }
+
+    /// Returns an iterator over the connections being received on this listener
+    pub fn incoming(&self) -> UnixIncoming<'_> {
+        UnixIncoming { listener: self }
+    }
+
+    /// Returns the local socket address of this listener
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.inner.get_ref().local_addr()
+    }
+
+    /// Creates a new independently owned handle to the underlying socket
+    pub fn try_clone(&self) -> io::Result<UnixListener> {
+        self.inner
+            .get_ref()
+            .try_clone()
+            .and_then(UnixListener::from_std)
+    }
+
+    /// Get the value of the `SO_ERROR` option on this socket
+    pub fn take_error(&self) -> io::Result<Option<io::Error>> {
+        self.inner.get_ref().take_error()
+    }
+
+    /// Readiness to accept a connection, for `select!` and `join!`
+    pub fn readable(&self) -> Readiness<'_> {
+        self.inner.readiness(Interest::Read)
+    }
+}
+
+impl AsRawFd for UnixListener {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
+
+impl fmt::Debug for UnixListener {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.get_ref().fmt(f)
+    }
+}
+
+/// An iterator that infinitely accepts connections on a `UnixListener`
+#[derive(Debug)]
+pub struct UnixIncoming<'a> {
+    listener: &'a UnixListener,
+}
+
+impl Iterator for UnixIncoming<'_> {
+    type Item = io::Result<UnixStream>;
+
+    fn next(&mut self) -> Option<io::Result<UnixStream>> {
+        Some(self.listener.accept().map(|(stream, _)| stream))
+    }
+}
@@ -0,0 +1,269 @@
+use std::{
+    fmt,
+    io::{self, IoSlice, IoSliceMut, Read, Write},
+    net::Shutdown,
+    os::{
+        fd::{AsRawFd, BorrowedFd, OwnedFd, RawFd},
+        unix::net::{self, SocketAddr},
+    },
+    path::Path,
+};
+
+use crate::{
+    io::{Evented, Interest},
+    net::{Readiness, UCred, ancillary},
+};
+
+/// A Unix domain stream socket
+///
+/// Reads and writes suspend the running coroutine until the socket is ready, and block the
+/// calling thread outside of coroutines. Descriptors can be passed to the peer along with the
+/// data with `send_with_fds` and `recv_with_fds`.
+pub struct UnixStream {
+    inner: Evented<net::UnixStream>,
+}
+
+impl UnixStream {
+    /// Connects to the socket bound to the path
+    ///
+    /// Connecting to a listening socket completes right away, only a full accept backlog makes
+    /// the call wait.
+    pub fn connect<P: AsRef<Path>>(path: P) -> io::Result<UnixStream> {
+        net::UnixStream::connect(path).and_then(UnixStream::from_std)
+    }
+
+    /// Creates an unnamed pair of connected sockets
+    pub fn pair() -> io::Result<(UnixStream, UnixStream)> {
+        let (a, b) = net::UnixStream::pair()?;
+
+        Ok((UnixStream::from_std(a)?, UnixStream::from_std(b)?))
+    }
+
+    /// Wrap a std stream, which is switched to non-blocking mode
+    pub fn from_std(stream: net::UnixStream) -> io::Result<UnixStream> {
+        Ok(UnixStream {
+            inner: Evented::new(stream)?,
+        })
+    }
+
+    /// Sends `buf` along with the descriptors, returns the number of bytes written
+    /// At most 253 descriptors can be sent at once
+    pub fn send_with_fds(&self, buf: &[u8], fds: &[BorrowedFd<'_>]) -> io::Result<usize> {
+        self.inner.do_io(Interest::Write, |s| {
+            ancillary::send_with_fds(s.as_raw_fd(), buf, fds)
+        })
+    }
+
+    /// Receives into `buf`, appending the descriptors sent along with the data to `fds`
+    /// Returns the number of bytes read
+    pub fn recv_with_fds(&self, buf: &mut [u8], fds: &mut Vec<OwnedFd>) -> io::Result<usize> {
+        self.inner.do_io(Interest::Read, |s| {
+            ancillary::recv_with_fds(s.as_raw_fd(), buf, fds)
+        })
+    }
+
+    /// Returns the credentials of the process on the other end of the connection
+    pub fn peer_cred(&self) -> io::Result<UCred> {
+        ancillary::peer_cred(self.as_raw_fd())
+    }
+
+    /// Returns the socket address of the local half of this connection
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.inner.get_ref().local_addr()
+    }
+
+    /// Returns the socket address of the remote half of this connection
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.inner.get_ref().peer_addr()
+    }
+
+    /// Shuts down the read, write, or both halves of this connection
+    pub fn shutdown(&self, how: Shutdown) -> io::Result<()> {
+        self.inner.get_ref().shutdown(how)
+    }
+
+    /// Creates a new independently owned handle to the underlying socket
+    pub fn try_clone(&self) -> io::Result<UnixStream> {
+        self.inner
+            .get_ref()
+            .try_clone()
+            .and_then(UnixStream::from_std)
+    }
+
+    /// Get the value of the `SO_ERROR` option on this socket
+    pub fn take_error(&self) -> io::Result<Option<io::Error>> {
+        self.inner.get_ref().take_error()
+    }
+
+    /// Readiness to read, for `select!` and `join!`
+    pub fn readable(&self) -> Readiness<'_> {
+        self.inner.readiness(Interest::Read)
+    }
+
+    /// Readiness to write, for `select!` and `join!`
+    pub fn writable(&self) -> Readiness<'_> {
+        self.inner.readiness(Interest::Write)
+    }
+}
+
+impl Read for UnixStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        (&*self).read(buf)
+    }
+
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        (&*self).read_vectored(bufs)
+    }
+}
+
+impl Write for UnixStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        (&*self).write(buf)
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        (&*self).write_vectored(bufs)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Read for &UnixStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.do_io(Interest::Read, |mut s| s.read(buf))
+    }
+
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        self.inner
+            .do_io(Interest::Read, |mut s| s.read_vectored(bufs))
+    }
+}
+
+impl Write for &UnixStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.do_io(Interest::Write, |mut s| s.write(buf))
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        self.inner
+            .do_io(Interest::Write, |mut s| s.write_vectored(bufs))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl AsRawFd for UnixStream {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
+
+impl fmt::Debug for UnixStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.get_ref().fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs::File, io::Seek, os::fd::AsFd, process};
+
+    use super::*;
+    use crate::{net::UnixListener, spawn};
+
+    #[test]
+    fn echo_between_coroutines() {
+        let path = env::temp_dir().join(format!("coroutine-unix-{}.sock", process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let listener = UnixListener::bind(&path).unwrap();
+
+        let server = unsafe {
+            spawn(move || {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut buf = [0; 5];
+
+                stream.read_exact(&mut buf).unwrap();
+                stream.write_all(&buf).unwrap();
+
+                stream.peer_cred().unwrap()
+            })
+        };
+
+        let client = unsafe {
+            spawn({
+                let path = path.clone();
+
+                move || {
+                    let mut stream = UnixStream::connect(path).unwrap();
+                    let mut buf = [0; 5];
+
+                    stream.write_all(b"hello").unwrap();
+                    stream.read_exact(&mut buf).unwrap();
+
+                    buf
+                }
+            })
+        };
+
+        assert_eq!(&client.join().unwrap(), b"hello");
+        assert_eq!(server.join().unwrap().pid, process::id() as i32);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn passes_descriptors() {
+        let (a, b) = UnixStream::pair().unwrap();
+        let mut file = tempfile();
+
+        file.write_all(b"shared").unwrap();
+
+        let receiver = unsafe {
+            spawn(move || {
+                let mut buf = [0; 4];
+                let mut fds = Vec::new();
+
+                let n = b.recv_with_fds(&mut buf, &mut fds).unwrap();
+
+                (buf[..n].to_vec(), fds)
+            })
+        };
+
+        a.send_with_fds(b"file", &[file.as_fd()]).unwrap();
+
+        let (data, mut fds) = receiver.join().unwrap();
+
+        assert_eq!(data, b"file");
+        assert_eq!(fds.len(), 1);
+
+        // The received descriptor shares the offset of the sent one
+        let mut received = File::from(fds.pop().unwrap());
+        let mut content = String::new();
+
+        received.rewind().unwrap();
+        received.read_to_string(&mut content).unwrap();
+
+        assert_eq!(content, "shared");
+        assert_eq!(file.stream_position().unwrap(), 6);
+    }
+
+    fn tempfile() -> File {
+        let path = env::temp_dir().join(format!("coroutine-fd-{}", process::id()));
+        let file = File::options()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)
+            .unwrap();
+
+        std::fs::remove_file(path).unwrap();
+
+        file
+    }
+}
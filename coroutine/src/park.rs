@@ -4,9 +4,20 @@ use std::{
         Arc,
         atomic::{AtomicBool, AtomicPtr, Ordering},
     },
+    time::{Duration, Instant},
 };
 
-use crate::{run_coroutine, sync::AtomicOption};
+use crate::{
+    CoroutineImpl,
+    cancel::Cancel,
+    coroutine_cancel_data,
+    event::EventSource,
+    run_coroutine,
+    scheduler::{Waiter, get_scheduler},
+    sync::{AtomicDuration, AtomicOption},
+    timer::TimeoutHandle,
+    yield_now::{get_coroutine_para, yield_with_event},
+};
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum ParkError {
@@ -17,7 +28,7 @@ pub enum ParkError {
 #[derive(Debug)]
 pub struct Park {
     // The coroutine which is waiting for this park instance
-    wait_coroutine: Arc<AtomicOption<CoroutineImpl>>,
+    wait_coroutine: Waiter,
 
     // When true - Park doesn't need to block
     state: AtomicBool,
@@ -25,14 +36,11 @@ pub struct Park {
     // Control how to deal with the cancellation
     check_cancel: AtomicBool,
 
-    // Timeout setting in ms, 0 is none (park forever)
+    // Timeout of the current wait, none parks forever
     timeout: AtomicDuration,
 
-    // Timer handle - can be null
-    timeout_handle: AtomicPtr<TimeoutHandle<Arc<AtomicOption<CoroutineImpl>>>>,
-
-    // A flag if kernel is entered
-    wait_kernel: AtomicBool,
+    // Timer handle of the current wait - can be null
+    timeout_handle: AtomicPtr<TimeoutHandle<Waiter>>,
 }
 
 impl Default for Park {
@@ -49,7 +57,6 @@ impl Park {
             check_cancel: AtomicBool::new(true),
             timeout: AtomicDuration::new(None),
             timeout_handle: AtomicPtr::new(ptr::null_mut()),
-            wait_kernel: AtomicBool::new(false),
         }
     }
 
@@ -59,6 +66,48 @@ impl Park {
             .store(!ignore, std::sync::atomic::Ordering::Relaxed);
     }
 
+    // Park the current coroutine until the token is made available by `unpark` or the timeout
+    // elapses. Consumes the token right away if it is already available
+    pub fn park_timeout(&self, dur: Option<Duration>) -> Result<(), ParkError> {
+        let deadline = dur.map(|dur| Instant::now() + dur);
+
+        loop {
+            if self.state.swap(false, Ordering::AcqRel) {
+                return Ok(());
+            }
+
+            // Spurious wakeups park again for the remaining time only
+            let remaining =
+                deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+
+            if remaining == Some(Duration::ZERO) {
+                return Err(ParkError::Timeout);
+            }
+
+            self.timeout.store(remaining);
+
+            yield_with_event(self);
+
+            let timed_out = self.cancel_timeout();
+
+            // The cancel sets the coroutine parameter before it resumes the coroutine
+            if get_coroutine_para().is_some() {
+                return Err(ParkError::Cancelled);
+            }
+
+            // A token that raced with the timer wins
+            if self.state.swap(false, Ordering::AcqRel) {
+                return Ok(());
+            }
+
+            if timed_out {
+                return Err(ParkError::Timeout);
+            }
+
+            // A stale subscription can wake us up without the token, park again then
+        }
+    }
+
     // Unpark the underlying coroutine if any, push to the ready task queue
     #[inline]
     pub fn unpark(&self) {
@@ -77,4 +126,124 @@ impl Park {
             }
         }
     }
+
+    // Cancel the timer of the last wait, returns true if it fired
+    fn cancel_timeout(&self) -> bool {
+        let handle = self.timeout_handle.swap(ptr::null_mut(), Ordering::AcqRel);
+
+        if handle.is_null() {
+            return false;
+        }
+
+        let handle = unsafe { Box::from_raw(handle) };
+
+        handle.cancel();
+        handle.is_fired()
+    }
+}
+
+impl Drop for Park {
+    fn drop(&mut self) {
+        self.cancel_timeout();
+    }
+}
+
+impl EventSource for Park {
+    fn subscribe(&mut self, coroutine: CoroutineImpl) {
+        let cancel = coroutine_cancel_data(&coroutine);
+
+        // Arm the timer before the coroutine is published, an unpark may resume it right away
+        // and the resumed coroutine has to find the handle to cancel it
+        if let Some(dur) = self.timeout.load() {
+            let handle = get_scheduler().add_timeout(dur, self.wait_coroutine.clone());
+
+            let previous = self
+                .timeout_handle
+                .swap(Box::into_raw(Box::new(handle)), Ordering::AcqRel);
+
+            debug_assert!(previous.is_null());
+        }
+
+        // Register the coroutine for cancellation first, an unpark may resume it as soon as it's
+        // stored and its `yield_back` has to find the registration to clear it
+        cancel.set_coroutine(self.wait_coroutine.clone());
+
+        self.wait_coroutine.store(coroutine);
+
+        // Re-check the timer, it found no coroutine if it fired before the coroutine was stored.
+        // Only the parked coroutine frees the handle, so it's still there
+        let handle = self.timeout_handle.load(Ordering::Acquire);
+
+        if !handle.is_null() && unsafe { (*handle).is_fired() } {
+            return self.wake_up(false);
+        }
+
+        // Re-check the state, the token may be set before the coroutine was registered
+        if self.state.load(Ordering::Acquire) {
+            return self.wake_up(false);
+        }
+
+        // Re-check the cancel flag, a cancel before the registration didn't find the coroutine
+        if cancel.is_cancelled() {
+            unsafe { cancel.cancel() };
+        }
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.timeout.load() {
+            Some(_) => "park timeout",
+            None => "park",
+        }
+    }
+
+    fn yield_back(&self, cancel: &'static Cancel) {
+        cancel.clear();
+
+        if self.check_cancel.load(Ordering::Relaxed) {
+            if cancel.is_cancelled() {
+                self.cancel_timeout();
+            }
+
+            cancel.check_cancel();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{spawn, sync::blocker::Blocker};
+
+    #[test]
+    fn park_times_out_unless_unparked() {
+        let timed_out = unsafe {
+            spawn(|| {
+                let start = Instant::now();
+                let result = Blocker::current().park(Some(Duration::from_millis(30)));
+
+                (result, start.elapsed())
+            })
+        };
+
+        let (result, elapsed) = timed_out.join().unwrap();
+
+        assert_eq!(result, Err(ParkError::Timeout));
+        assert!(elapsed >= Duration::from_millis(30));
+
+        let (tx, rx) = std::sync::mpsc::channel();
+
+        let unparked = unsafe {
+            spawn(move || {
+                let blocker = Blocker::current();
+
+                tx.send(blocker.clone()).unwrap();
+
+                blocker.park(Some(Duration::from_secs(10)))
+            })
+        };
+
+        rx.recv().unwrap().unpark();
+
+        assert_eq!(unparked.join().unwrap(), Ok(()));
+    }
 }
@@ -0,0 +1,307 @@
+use std::{
+    sync::{
+        Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use crate::{CoroutineImpl, config::config, stack_profile, sync::CachePadded};
+
+/// Number of stack size classes
+const SIZE_CLASSES: usize = 6;
+
+/// Index of the class holding the default stack size, the classes below it are a quarter and a
+/// half of the default size and the classes above it are twice, four and eight times as large
+const DEFAULT_CLASS: usize = 2;
+
+/// Idle coroutines that were not needed for this long get their stacks trimmed
+const TRIM_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Pool of finished coroutines kept for reuse, grouped by stack size class
+///
+/// Allocating a stack is a `mmap` plus a `mprotect`, so finished coroutines are kept around and
+/// loaded with new code instead. A requested stack size is rounded up to the nearest class, sizes
+/// larger than the largest class are never pooled. At most `Config::get_pool_capacity` coroutines
+/// are kept in total.
+///
+/// An odd size asks for a stack that tracks its usage. While stack profiling every size is odd,
+/// and every class keeps tracked stacks.
+///
+/// Every class is a LIFO stack, so the coroutines at the bottom are the ones that stayed idle the
+/// longest. Those that were not needed during a whole trim interval, typically left over from a
+/// spike, have the used region of their stacks released with `MADV_DONTNEED`. The memory is
+/// faulted back in when they are reused.
+pub(crate) struct Pool {
+    classes: [SizeClass; SIZE_CLASSES],
+
+    /// Maximum number of idle coroutines
+    capacity: usize,
+
+    /// Number of idle coroutines over all classes
+    len: AtomicUsize,
+}
+
+struct SizeClass {
+    /// Stack size in words of the coroutines of this class
+    stack_size: usize,
+
+    idle: CachePadded<Mutex<IdleList>>,
+}
+
+struct IdleList {
+    coroutines: Vec<CoroutineImpl>,
+
+    /// The coroutines below this index have trimmed stacks
+    trimmed: usize,
+
+    /// Lowest length since the last trim, the coroutines below it were not used meanwhile
+    low_water: usize,
+
+    last_trim: Instant,
+}
+
+impl Pool {
+    pub(crate) fn new() -> Pool {
+        Pool::with_sizes(
+            config().get_stack_size(),
+            config().get_pool_capacity(),
+            stack_profile::is_enabled(),
+        )
+    }
+
+    /// Create a pool with classes around `default` words holding at most `capacity` coroutines
+    /// The stacks of every class track their usage if `tracked`, only those of an odd default
+    /// class otherwise
+    fn with_sizes(default: usize, capacity: usize, tracked: bool) -> Pool {
+        Pool {
+            classes: std::array::from_fn(|class| {
+                let stack_size = if class == DEFAULT_CLASS {
+                    default
+                } else {
+                    (default & !1) << class >> DEFAULT_CLASS
+                };
+                let stack_size = stack_size | tracked as usize;
+
+                SizeClass {
+                    stack_size,
+                    idle: CachePadded::new(Mutex::new(IdleList {
+                        coroutines: Vec::new(),
+                        trimmed: 0,
+                        low_water: 0,
+                        last_trim: Instant::now(),
+                    })),
+                }
+            }),
+            capacity,
+            len: AtomicUsize::new(0),
+        }
+    }
+
+    /// Get a coroutine with a stack of at least `stack_size` words, without code
+    /// A new one is created if the class of the size is empty or the size is not pooled
+    pub(crate) fn get(&self, stack_size: usize) -> CoroutineImpl {
+        let Some(class) = self.class_of(stack_size) else {
+            return CoroutineImpl::with_stack(stack_size);
+        };
+
+        let class = &self.classes[class];
+
+        let coroutine = {
+            let mut idle = class.idle.lock().unwrap();
+            let coroutine = idle.coroutines.pop();
+
+            idle.low_water = idle.low_water.min(idle.coroutines.len());
+            idle.trimmed = idle.trimmed.min(idle.coroutines.len());
+
+            coroutine
+        };
+
+        match coroutine {
+            Some(coroutine) => {
+                self.len.fetch_sub(1, Ordering::Relaxed);
+
+                // A tracked stack measures the usage of each coroutine on its own
+                if class.stack_size & 1 == 1 {
+                    coroutine.reset_stack_usage();
+                }
+
+                coroutine
+            }
+            None => CoroutineImpl::with_stack(class.stack_size),
+        }
+    }
+
+    /// Return a finished coroutine that was spawned with a stack of `stack_size` words
+    /// The coroutine is dropped if its size is not pooled or the pool is full
+    pub(crate) fn put(&self, coroutine: CoroutineImpl, stack_size: usize) {
+        let Some(class) = self.class_of(stack_size) else {
+            return;
+        };
+
+        if self.len.fetch_add(1, Ordering::Relaxed) >= self.capacity {
+            self.len.fetch_sub(1, Ordering::Relaxed);
+
+            return;
+        }
+
+        let mut idle = self.classes[class].idle.lock().unwrap();
+
+        idle.coroutines.push(coroutine);
+        idle.trim();
+    }
+
+    /// Trim the stacks that stayed idle for a whole interval, skipping the classes in use
+    /// Called by the workers before they go to sleep, when no more coroutines are put back
+    pub(crate) fn trim(&self) {
+        for class in &self.classes {
+            if let Ok(mut idle) = class.idle.try_lock() {
+                idle.trim();
+            }
+        }
+    }
+
+    /// Round `stack_size` up to the size of its class, the sizes larger than all the classes are
+    /// kept as they are
+    pub(crate) fn class_size(&self, stack_size: usize) -> usize {
+        self.classes
+            .iter()
+            .map(|class| class.stack_size & !1)
+            .find(|&size| size >= stack_size)
+            .unwrap_or(stack_size)
+    }
+
+    /// Find the smallest class holding stacks of at least `stack_size` words
+    /// An odd size asks for the stack usage to be tracked, which only the classes of tracked
+    /// stacks can do
+    fn class_of(&self, stack_size: usize) -> Option<usize> {
+        let class = self
+            .classes
+            .iter()
+            .position(|class| class.stack_size & !1 >= stack_size & !1)?;
+
+        let tracked = self.classes[class].stack_size & 1 == 1;
+
+        (stack_size & 1 == 0 || tracked).then_some(class)
+    }
+}
+
+impl IdleList {
+    fn trim(&mut self) {
+        let now = Instant::now();
+
+        if now.duration_since(self.last_trim) < TRIM_INTERVAL {
+            return;
+        }
+
+        let low_water = self.low_water.min(self.coroutines.len());
+
+        for coroutine in &self.coroutines[self.trimmed.min(low_water)..low_water] {
+            coroutine.trim_stack();
+        }
+
+        self.trimmed = self.trimmed.max(low_water);
+        self.low_water = self.coroutines.len();
+        self.last_trim = now;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn idle_len(pool: &Pool, class: usize) -> usize {
+        pool.classes[class].idle.lock().unwrap().coroutines.len()
+    }
+
+    #[test]
+    fn sizes_round_up_to_their_class() {
+        let pool = Pool::with_sizes(0x1000, 8, false);
+
+        assert_eq!(pool.class_of(2), Some(0));
+        assert_eq!(pool.class_of(0x400), Some(0));
+        assert_eq!(pool.class_of(0x900), Some(2));
+        assert_eq!(pool.class_of(0x8000), Some(5));
+        assert_eq!(pool.class_of(0x8002), None);
+
+        assert_eq!(pool.class_size(0x500), 0x800);
+        assert_eq!(pool.class_size(0x1000), 0x1000);
+        assert_eq!(pool.class_size(0x9000), 0x9000);
+    }
+
+    #[test]
+    fn odd_sizes_are_only_pooled_in_tracked_classes() {
+        let pool = Pool::with_sizes(0x1000, 8, false);
+
+        assert_eq!(pool.class_of(0x801), None);
+        assert_eq!(pool.class_of(0x1001), None);
+
+        pool.put(pool.get(0x801), 0x801);
+        assert_eq!(pool.len.load(Ordering::Relaxed), 0);
+
+        let pool = Pool::with_sizes(0x1001, 8, false);
+
+        assert_eq!(pool.class_of(0x1001), Some(DEFAULT_CLASS));
+        assert_eq!(pool.class_of(0x801), None);
+        assert_eq!(pool.class_of(0x800), Some(1));
+
+        pool.put(pool.get(0x1001), 0x1001);
+        assert_eq!(idle_len(&pool, DEFAULT_CLASS), 1);
+
+        // While profiling every class tracks, the sizes keep rounding up to their class
+        let pool = Pool::with_sizes(0x1000, 8, true);
+
+        assert_eq!(pool.class_of(0x801), Some(1));
+        assert_eq!(pool.class_of(0x1801), Some(3));
+        assert_eq!(pool.class_size(0x1800), 0x2000);
+
+        pool.put(pool.get(0x2001), 0x2001);
+        assert_eq!(idle_len(&pool, 3), 1);
+    }
+
+    #[test]
+    fn capacity_bounds_the_idle_coroutines() {
+        let pool = Pool::with_sizes(0x1000, 2, false);
+
+        for _ in 0..3 {
+            pool.put(CoroutineImpl::with_stack(0x1000), 0x1000);
+        }
+
+        pool.put(CoroutineImpl::with_stack(0x400), 0x400);
+
+        assert_eq!(pool.len.load(Ordering::Relaxed), 2);
+        assert_eq!(idle_len(&pool, DEFAULT_CLASS), 2);
+        assert_eq!(idle_len(&pool, 0), 0);
+
+        drop(pool.get(0x1000));
+
+        assert_eq!(pool.len.load(Ordering::Relaxed), 1);
+        assert_eq!(idle_len(&pool, DEFAULT_CLASS), 1);
+    }
+
+    #[test]
+    fn only_coroutines_idle_for_a_whole_interval_are_trimmed() {
+        let pool = Pool::with_sizes(0x1000, 8, false);
+        let class = &pool.classes[DEFAULT_CLASS];
+
+        for _ in 0..3 {
+            pool.put(CoroutineImpl::with_stack(0x1000), 0x1000);
+        }
+
+        // The pool was empty when the interval started, so nothing stayed idle through it
+        class.idle.lock().unwrap().last_trim -= TRIM_INTERVAL;
+        pool.trim();
+        assert_eq!(class.idle.lock().unwrap().trimmed, 0);
+
+        // Two coroutines stay idle through the next interval, the third one is used
+        drop(pool.get(0x1000));
+
+        class.idle.lock().unwrap().last_trim -= TRIM_INTERVAL;
+        pool.trim();
+
+        let idle = class.idle.lock().unwrap();
+
+        assert_eq!(idle.trimmed, 2);
+        assert_eq!(idle.low_water, 2);
+    }
+}
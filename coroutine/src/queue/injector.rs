@@ -0,0 +1,100 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+use super::local::Local;
+
+/// Multi-producer multi-consumer queue shared by all the workers
+///
+/// Tasks spawned from outside of the workers and tasks overflowing a worker local queue end up
+/// here. The length is mirrored in an atomic so that idle checks never touch the lock.
+pub(crate) struct Injector<T> {
+    queue: Mutex<VecDeque<T>>,
+    len: AtomicUsize,
+}
+
+impl<T> Injector<T> {
+    /// Create an empty injector
+    pub(crate) fn new() -> Injector<T> {
+        Injector {
+            queue: Mutex::new(VecDeque::new()),
+            len: AtomicUsize::new(0),
+        }
+    }
+
+    /// Number of queued tasks
+    #[inline]
+    pub(crate) fn len(&self) -> usize {
+        self.len.load(Ordering::SeqCst)
+    }
+
+    /// Returns true if there are no queued tasks
+    #[inline]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Push a single task
+    pub(crate) fn push(&self, task: T) {
+        let mut queue = self.queue.lock().unwrap();
+
+        queue.push_back(task);
+
+        self.len.store(queue.len(), Ordering::SeqCst);
+    }
+
+    /// Push a batch of tasks while holding the lock only once
+    pub(crate) fn push_batch(&self, tasks: impl Iterator<Item = T>) {
+        let mut queue = self.queue.lock().unwrap();
+
+        queue.extend(tasks);
+
+        self.len.store(queue.len(), Ordering::SeqCst);
+    }
+
+    /// Pop a single task
+    pub(crate) fn pop(&self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let mut queue = self.queue.lock().unwrap();
+        let task = queue.pop_front();
+
+        self.len.store(queue.len(), Ordering::SeqCst);
+
+        task
+    }
+
+    /// Move up to `max` tasks into the `dst` local queue, returning one of them to run directly
+    pub(crate) fn pop_batch_into(&self, dst: &mut Local<T>, max: usize) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let mut queue = self.queue.lock().unwrap();
+        let task = queue.pop_front();
+
+        if task.is_some() {
+            let n = max.min(queue.len()).min(dst.remaining());
+
+            for task in queue.drain(..n) {
+                dst.push_back_fit(task);
+            }
+        }
+
+        self.len.store(queue.len(), Ordering::SeqCst);
+
+        task
+    }
+}
+
+impl<T> Default for Injector<T> {
+    fn default() -> Self {
+        Injector::new()
+    }
+}
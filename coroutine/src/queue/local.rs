@@ -0,0 +1,334 @@
+use std::{
+    cell::UnsafeCell,
+    mem::MaybeUninit,
+    ptr,
+    sync::{
+        Arc,
+        atomic::{AtomicU32, AtomicU64, Ordering},
+    },
+};
+
+use super::injector::Injector;
+
+/// Capacity of a worker local run queue, must be a power of two
+pub(crate) const LOCAL_QUEUE_CAPACITY: usize = 256;
+
+const MASK: usize = LOCAL_QUEUE_CAPACITY - 1;
+
+/// Shared state of a worker local run queue
+///
+/// The head is packed into a single `u64`: the high half is the "steal" head and the low half is
+/// the "real" head. When no stealer is active both halves are equal. A stealer first moves the
+/// real head forward to claim a batch, copies the claimed slots and only then releases the steal
+/// head, so the owner never reuses slots that are still being copied.
+struct Inner<T> {
+    /// Packed `(steal, real)` heads
+    head: AtomicU64,
+
+    /// Only ever written by the owner
+    tail: AtomicU32,
+
+    /// Ring buffer of tasks
+    buffer: Box<[UnsafeCell<MaybeUninit<T>>]>,
+}
+
+unsafe impl<T: Send> Send for Inner<T> {}
+unsafe impl<T: Send> Sync for Inner<T> {}
+
+/// Owner side of the worker local run queue
+pub(crate) struct Local<T> {
+    inner: Arc<Inner<T>>,
+}
+
+/// Stealer side of the worker local run queue, shared with every other worker
+pub(crate) struct Steal<T>(Arc<Inner<T>>);
+
+/// Create a new local run queue, returning the stealer and the owner handles
+pub(crate) fn local<T>() -> (Steal<T>, Local<T>) {
+    let buffer = (0..LOCAL_QUEUE_CAPACITY)
+        .map(|_| UnsafeCell::new(MaybeUninit::uninit()))
+        .collect();
+
+    let inner = Arc::new(Inner {
+        head: AtomicU64::new(0),
+        tail: AtomicU32::new(0),
+        buffer,
+    });
+
+    (Steal(inner.clone()), Local { inner })
+}
+
+#[inline]
+fn unpack(n: u64) -> (u32, u32) {
+    ((n >> 32) as u32, n as u32)
+}
+
+#[inline]
+fn pack(steal: u32, real: u32) -> u64 {
+    ((steal as u64) << 32) | real as u64
+}
+
+impl<T> Inner<T> {
+    #[inline]
+    unsafe fn write(&self, pos: u32, task: T) {
+        let slot = &self.buffer[pos as usize & MASK];
+
+        unsafe { ptr::write((*slot.get()).as_mut_ptr(), task) }
+    }
+
+    #[inline]
+    unsafe fn read(&self, pos: u32) -> T {
+        let slot = &self.buffer[pos as usize & MASK];
+
+        unsafe { ptr::read(slot.get()).assume_init() }
+    }
+
+    fn len(&self) -> usize {
+        let (_, head) = unpack(self.head.load(Ordering::Acquire));
+        let tail = self.tail.load(Ordering::Acquire);
+
+        tail.wrapping_sub(head) as usize
+    }
+}
+
+impl<T> Local<T> {
+    /// Number of tasks in the queue
+    #[cfg(test)]
+    pub(crate) fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Number of tasks that can be pushed without overflowing into the injector
+    pub(crate) fn remaining(&self) -> usize {
+        let (steal, _) = unpack(self.inner.head.load(Ordering::Acquire));
+        let tail = self.inner.tail.load(Ordering::Relaxed);
+
+        LOCAL_QUEUE_CAPACITY - tail.wrapping_sub(steal) as usize
+    }
+
+    /// Push a task that is known to fit, the caller must have checked `remaining()`
+    pub(crate) fn push_back_fit(&mut self, task: T) {
+        let tail = self.inner.tail.load(Ordering::Relaxed);
+
+        debug_assert!(self.remaining() > 0);
+
+        unsafe { self.inner.write(tail, task) };
+
+        self.inner
+            .tail
+            .store(tail.wrapping_add(1), Ordering::Release);
+    }
+
+    /// Push a task to the back of the queue, moving half of the queue to the injector when full
+    pub(crate) fn push_back(&mut self, mut task: T, inject: &Injector<T>) {
+        let tail = loop {
+            let head = self.inner.head.load(Ordering::Acquire);
+            let (steal, real) = unpack(head);
+            let tail = self.inner.tail.load(Ordering::Relaxed);
+
+            if (tail.wrapping_sub(steal) as usize) < LOCAL_QUEUE_CAPACITY {
+                // There is capacity for the task
+                break tail;
+            } else if steal != real {
+                // Another worker is concurrently stealing, so there will soon be room again
+                inject.push(task);
+
+                return;
+            }
+
+            // Push the current task and half of the queue to the injector
+            match self.push_overflow(task, real, tail, inject) {
+                Ok(()) => return,
+                // Lost the race against a stealer, try again
+                Err(t) => task = t,
+            }
+        };
+
+        unsafe { self.inner.write(tail, task) };
+
+        self.inner
+            .tail
+            .store(tail.wrapping_add(1), Ordering::Release);
+    }
+
+    #[inline(never)]
+    fn push_overflow(
+        &mut self,
+        task: T,
+        head: u32,
+        tail: u32,
+        inject: &Injector<T>,
+    ) -> Result<(), T> {
+        const HALF: u32 = (LOCAL_QUEUE_CAPACITY / 2) as u32;
+
+        debug_assert_eq!(tail.wrapping_sub(head) as usize, LOCAL_QUEUE_CAPACITY);
+
+        let prev = pack(head, head);
+        let next = pack(head.wrapping_add(HALF), head.wrapping_add(HALF));
+
+        // Claim half of the tasks, this fails if a stealer got in first
+        if self
+            .inner
+            .head
+            .compare_exchange(prev, next, Ordering::Release, Ordering::Relaxed)
+            .is_err()
+        {
+            return Err(task);
+        }
+
+        let inner = &self.inner;
+        let batch = (0..HALF).map(|i| unsafe { inner.read(head.wrapping_add(i)) });
+
+        inject.push_batch(batch.chain(std::iter::once(task)));
+
+        Ok(())
+    }
+
+    /// Pop a task from the front of the queue
+    pub(crate) fn pop(&mut self) -> Option<T> {
+        let mut head = self.inner.head.load(Ordering::Acquire);
+
+        let idx = loop {
+            let (steal, real) = unpack(head);
+            let tail = self.inner.tail.load(Ordering::Relaxed);
+
+            if real == tail {
+                return None;
+            }
+
+            let next_real = real.wrapping_add(1);
+
+            // Only move the steal head when no stealer is active
+            let next = if steal == real {
+                pack(next_real, next_real)
+            } else {
+                debug_assert_ne!(steal, next_real);
+
+                pack(steal, next_real)
+            };
+
+            match self
+                .inner
+                .head
+                .compare_exchange(head, next, Ordering::AcqRel, Ordering::Acquire)
+            {
+                Ok(_) => break real,
+                Err(actual) => head = actual,
+            }
+        };
+
+        Some(unsafe { self.inner.read(idx) })
+    }
+}
+
+impl<T> Drop for Local<T> {
+    fn drop(&mut self) {
+        while self.pop().is_some() {}
+    }
+}
+
+impl<T> Steal<T> {
+    /// Returns true if there are no tasks to steal
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.len() == 0
+    }
+
+    /// Steal half of the tasks into `dst`, returning one of them to run directly
+    pub(crate) fn steal_into(&self, dst: &mut Local<T>) -> Option<T> {
+        let dst_tail = dst.inner.tail.load(Ordering::Relaxed);
+        let (steal, _) = unpack(dst.inner.head.load(Ordering::Acquire));
+
+        // Don't steal into a queue that is more than half full
+        if dst_tail.wrapping_sub(steal) as usize > LOCAL_QUEUE_CAPACITY / 2 {
+            return None;
+        }
+
+        let mut n = self.steal_into2(dst, dst_tail);
+
+        if n == 0 {
+            return None;
+        }
+
+        // Keep the last stolen task to run it directly
+        n -= 1;
+
+        let ret = unsafe { dst.inner.read(dst_tail.wrapping_add(n)) };
+
+        if n > 0 {
+            dst.inner
+                .tail
+                .store(dst_tail.wrapping_add(n), Ordering::Release);
+        }
+
+        Some(ret)
+    }
+
+    fn steal_into2(&self, dst: &mut Local<T>, dst_tail: u32) -> u32 {
+        let mut prev = self.0.head.load(Ordering::Acquire);
+
+        let (first, n) = loop {
+            let (steal, real) = unpack(prev);
+            let tail = self.0.tail.load(Ordering::Acquire);
+
+            // Another worker is already stealing from this queue
+            if steal != real {
+                return 0;
+            }
+
+            let n = tail.wrapping_sub(real);
+            let n = n - n / 2;
+
+            if n == 0 {
+                return 0;
+            }
+
+            let next = pack(steal, real.wrapping_add(n));
+
+            match self
+                .0
+                .head
+                .compare_exchange(prev, next, Ordering::AcqRel, Ordering::Acquire)
+            {
+                Ok(_) => break (steal, n),
+                Err(actual) => prev = actual,
+            }
+        };
+
+        for i in 0..n {
+            unsafe {
+                let task = self.0.read(first.wrapping_add(i));
+
+                dst.inner.write(dst_tail.wrapping_add(i), task);
+            }
+        }
+
+        // Release the claimed slots by catching the steal head up with the real head
+        let mut prev = pack(first, first.wrapping_add(n));
+
+        loop {
+            let (_, real) = unpack(prev);
+            let next = pack(real, real);
+
+            match self
+                .0
+                .head
+                .compare_exchange(prev, next, Ordering::AcqRel, Ordering::Acquire)
+            {
+                Ok(_) => return n,
+                Err(actual) => {
+                    let (steal, real) = unpack(actual);
+
+                    debug_assert_ne!(steal, real);
+
+                    prev = actual;
+                }
+            }
+        }
+    }
+}
+
+impl<T> Clone for Steal<T> {
+    fn clone(&self) -> Steal<T> {
+        Steal(self.0.clone())
+    }
+}
@@ -0,0 +1,61 @@
+//! Run queues used by the scheduler
+//! Every worker owns a bounded local queue that other workers can steal from, and all the workers
+//! share a global injector queue
+
+mod injector;
+mod local;
+
+pub(crate) use injector::Injector;
+pub(crate) use local::{Local, Steal, local};
+
+#[cfg(test)]
+mod tests {
+    use super::{local::LOCAL_QUEUE_CAPACITY, *};
+
+    #[test]
+    fn local_queue_is_fifo() {
+        let inject = Injector::new();
+        let (_, mut local) = local();
+
+        for i in 0..10 {
+            local.push_back(i, &inject);
+        }
+
+        for i in 0..10 {
+            assert_eq!(local.pop(), Some(i));
+        }
+
+        assert_eq!(local.pop(), None);
+        assert!(inject.is_empty());
+    }
+
+    #[test]
+    fn local_queue_overflows_into_injector() {
+        let inject = Injector::new();
+        let (_, mut local) = local();
+
+        for i in 0..LOCAL_QUEUE_CAPACITY + 1 {
+            local.push_back(i, &inject);
+        }
+
+        assert_eq!(inject.len(), LOCAL_QUEUE_CAPACITY / 2 + 1);
+        assert_eq!(local.len(), LOCAL_QUEUE_CAPACITY / 2);
+    }
+
+    #[test]
+    fn steal_takes_half() {
+        let inject = Injector::new();
+        let (steal, mut victim) = local();
+        let (_, mut thief) = local();
+
+        for i in 0..8 {
+            victim.push_back(i, &inject);
+        }
+
+        assert_eq!(steal.steal_into(&mut thief), Some(3));
+        assert_eq!(thief.len(), 3);
+        assert_eq!(victim.len(), 4);
+        assert_eq!(victim.pop(), Some(4));
+        assert_eq!(thief.pop(), Some(0));
+    }
+}
@@ -1,4 +1,4 @@
-use crate::stack::Register;
+use crate::stack::{InitFn, Register, Stack, initialize_call_frame, swap_registers};
 
 #[derive(Debug)]
 pub struct RegisterContext {
@@ -12,4 +12,21 @@ impl RegisterContext {
             regs: Register::new(),
         }
     }
+
+    /// The stack pointer saved by the last switch away from this context
+    #[inline]
+    pub fn sp(&self) -> usize {
+        self.regs.sp()
+    }
+
+    /// Prepare the registers so that the first switch calls `init(arg, start)` on `stack`
+    pub fn init_with(&mut self, init: InitFn, arg: usize, start: *mut usize, stack: &Stack) {
+        initialize_call_frame(&mut self.regs, init, arg, start, stack);
+    }
+
+    /// Save the current registers into `out_context` and resume `in_context`
+    #[inline]
+    pub fn swap(out_context: &mut RegisterContext, in_context: &RegisterContext) {
+        unsafe { swap_registers(&mut out_context.regs, &in_context.regs) }
+    }
 }
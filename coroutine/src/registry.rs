@@ -0,0 +1,164 @@
+//! Registry of the live coroutines, for debugging
+//!
+//! Once enabled with `ConfigBuilder::registry`, every spawned coroutine is recorded until it's
+//! done, along with its scheduling state, the event it's parked on and its stack depth. `dump`
+//! renders them as a table, to find out what a stuck server is waiting for.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{Arc, Mutex, Weak},
+};
+
+use crate::{
+    CoroutineImpl, Inner, config::config, coroutine_local::get_coroutine_local,
+    stack_profile::UNNAMED, status::State,
+};
+
+static REGISTRY: Mutex<BTreeMap<u64, Weak<Inner>>> = Mutex::new(BTreeMap::new());
+
+/// Returns true if the live coroutines are recorded
+#[inline]
+pub fn is_enabled() -> bool {
+    config().is_registry_enabled()
+}
+
+/// Record a new coroutine
+pub(crate) fn register(inner: &Arc<Inner>) {
+    if is_enabled() {
+        REGISTRY
+            .lock()
+            .unwrap()
+            .insert(inner.id, Arc::downgrade(inner));
+    }
+}
+
+/// Forget a coroutine that is done
+pub(crate) fn unregister(id: u64) {
+    if is_enabled() {
+        REGISTRY.lock().unwrap().remove(&id);
+    }
+}
+
+/// Record the state change of a spawned coroutine
+#[inline]
+pub(crate) fn set_state(coroutine: &CoroutineImpl, state: State) {
+    if let Some(inner) = inner(coroutine) {
+        inner.status.set_state(state);
+    }
+}
+
+/// Record the suspension of a spawned coroutine on `reason`
+#[inline]
+pub(crate) fn park(coroutine: &CoroutineImpl, reason: &'static str) {
+    if let Some(inner) = inner(coroutine) {
+        inner.status.park(reason, coroutine.stack_depth());
+    }
+}
+
+#[inline]
+fn inner(coroutine: &CoroutineImpl) -> Option<&Inner> {
+    if !is_enabled() {
+        return None;
+    }
+
+    let local = get_coroutine_local(coroutine);
+
+    if local.is_null() {
+        return None;
+    }
+
+    Some(&unsafe { &*local }.get_coroutine().inner)
+}
+
+/// Render the live coroutines as a table, ordered by id
+/// The stack columns are in words, the depth at the last suspension and the deepest one seen
+pub fn dump() -> String {
+    let coroutines: Vec<Arc<Inner>> = REGISTRY
+        .lock()
+        .unwrap()
+        .values()
+        .filter_map(Weak::upgrade)
+        .collect();
+
+    render(&coroutines)
+}
+
+fn render(coroutines: &[Arc<Inner>]) -> String {
+    let mut table = format!(
+        "{:>8}  {:<24}  {:<8}  {:<12}  {:>8}  {:>8}  {:>8}\n",
+        "id", "name", "state", "parked on", "stack", "max", "size"
+    );
+
+    for inner in coroutines {
+        let (state, reason) = match inner.status.state() {
+            State::Ready => ("ready", ""),
+            State::Running => ("running", ""),
+            State::Parked => ("parked", inner.status.reason()),
+        };
+
+        let (depth, max_depth) = inner.status.stack_depth();
+
+        writeln!(
+            table,
+            "{:>8}  {:<24}  {:<8}  {:<12}  {:>8}  {:>8}  {:>8}",
+            inner.id,
+            inner.name.as_deref().unwrap_or(UNNAMED),
+            state,
+            reason,
+            depth,
+            max_depth,
+            inner.stack_size
+        )
+        .unwrap();
+    }
+
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Coroutine;
+
+    #[test]
+    fn renders_one_row_per_coroutine() {
+        let parked = Coroutine::new(Some("acceptor".into()), 0x1000);
+        let ready = Coroutine::new(None, 0x2000);
+
+        parked.inner.status.park("io read", Some(300));
+        parked.inner.status.set_state(State::Running);
+        parked.inner.status.park("park", Some(120));
+
+        let table = render(&[parked.inner.clone(), ready.inner.clone()]);
+        let rows: Vec<Vec<&str>> = table
+            .lines()
+            .map(|line| line.split_whitespace().collect())
+            .collect();
+
+        assert_eq!(
+            rows[0],
+            [
+                "id", "name", "state", "parked", "on", "stack", "max", "size"
+            ]
+        );
+
+        let id = parked.id().to_string();
+        assert_eq!(
+            rows[1],
+            [
+                id.as_str(),
+                "acceptor",
+                "parked",
+                "park",
+                "120",
+                "300",
+                "4096"
+            ]
+        );
+
+        let id = ready.id().to_string();
+        assert_eq!(rows[2], [id.as_str(), UNNAMED, "ready", "0", "0", "8192"]);
+        assert_eq!(rows.len(), 3);
+    }
+}
@@ -40,7 +40,10 @@ pub struct Context {
     /// Propagate panic
     pub err: Option<Box<dyn Any + Send>>,
 
-    /// Cached stack guard for fast path
+    /// Set by the overflow handler, which can't touch `err`
+    pub overflowed: bool,
+
+    /// Cached stack range, guard pages included
     pub stack_guard: (usize, usize),
 }
 
@@ -53,6 +56,7 @@ impl Context {
             ret: MaybeUninit::zeroed(),
             _ref: 1, // Non-zero means Not running
             err: None,
+            overflowed: false,
             child: null_mut(),
             parent: null_mut(),
             local_data: null_mut(),
@@ -63,7 +67,7 @@ impl Context {
     /// Check if it is generator's context
     #[inline]
     pub fn is_generator(&self) -> bool {
-        self.parent != self as *const _ as *mut _
+        !ptr::eq(self.parent, self)
     }
 
     /// Get current generator send parameter
@@ -99,19 +103,6 @@ impl Context {
         para.take()
     }
 
-    /// Set coroutine send para
-    pub fn coroutine_set_para<T>(&mut self, data: T) {
-        let para = unsafe {
-            let para_ptr = *self.para.as_mut_ptr();
-
-            debug_assert!(!para_ptr.is_null());
-
-            &mut *(para_ptr as *mut Option<T>)
-        };
-
-        *para = Some(data);
-    }
-
     /// Set current generator return value
     pub fn set_ret<T>(&mut self, v: T)
     where
@@ -180,6 +171,43 @@ impl ContextStack {
         ContextStack { root }
     }
 
+    /// Push the context `ctx` as the new top context
+    /// The context's parent holds the top of its own nested contexts, which becomes the new top
+    #[inline]
+    pub fn push_context(&self, ctx: *mut Context) {
+        let root = unsafe { &mut *self.root };
+        let ctx = unsafe { &mut *ctx };
+        let top = unsafe { &mut *root.parent };
+        let new_top = ctx.parent;
+
+        // Link the top and the new context
+        top.child = ctx;
+        ctx.parent = top;
+
+        // Save the new top
+        root.parent = new_top;
+    }
+
+    /// Pop the context `ctx` and return its parent, which becomes the new top
+    /// The old top is saved in the context's parent, so it can be restored by `push_context`
+    #[inline]
+    pub fn pop_context(&self, ctx: *mut Context) -> &'static mut Context {
+        let root = unsafe { &mut *self.root };
+        let ctx = unsafe { &mut *ctx };
+        let parent = unsafe { &mut *ctx.parent };
+
+        // Save the old top in the context's parent
+        ctx.parent = root.parent;
+
+        // Unlink the context and its parent
+        parent.child = ptr::null_mut();
+
+        // Let the parent be the top
+        root.parent = parent;
+
+        parent
+    }
+
     /// Get the top context
     #[inline]
     pub fn top(&self) -> &'static mut Context {
@@ -196,7 +224,7 @@ impl ContextStack {
         // Search from top
         let mut ctx = unsafe { &mut *root.parent };
 
-        while ctx as *const _ != root as *const _ {
+        while !ptr::eq(ctx, root) {
             if !ctx.local_data.is_null() {
                 return Some(ctx);
             }
@@ -235,7 +263,7 @@ pub(crate) fn get_local_data() -> *mut u8 {
     // Search from top
     let mut ctx = unsafe { &mut *root.parent };
 
-    while ctx as *const _ != root as *const _ {
+    while !ptr::eq(ctx, root) {
         if !ctx.local_data.is_null() {
             return ctx.local_data;
         }
@@ -0,0 +1,469 @@
+use std::{
+    cell::{Cell, RefCell},
+    os::fd::RawFd,
+    ptr,
+    sync::{
+        Arc, Mutex, OnceLock,
+        atomic::{self, AtomicUsize, Ordering},
+        mpsc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    CoroutineImpl,
+    config::config,
+    io::Reactor,
+    pool::Pool,
+    queue::{self, Injector, Local, Steal},
+    registry, run_coroutine,
+    stack::overflow,
+    status::State,
+    sync::{AtomicOption, CachePadded},
+    timer::{TimeoutHandle, TimerWheel},
+};
+
+/// Every this many ticks a worker checks the shared queues before its local queue, so that tasks
+/// waiting there are not starved by a busy local queue
+const GLOBAL_POLL_INTERVAL: u32 = 61;
+
+/// Maximum number of tasks moved from the global queue into a local queue at once
+const GLOBAL_BATCH_SIZE: usize = 32;
+
+thread_local! {
+    /// The worker driven by the current thread, null if the thread is not a worker
+    static WORKER: Cell<*const Worker> = const { Cell::new(ptr::null()) };
+}
+
+/// Slot of a parked coroutine, taken by whoever wakes it up first
+pub(crate) type Waiter = Arc<AtomicOption<CoroutineImpl>>;
+
+/// Get the global scheduler, the worker threads are started on first use
+#[inline]
+pub(crate) fn get_scheduler() -> &'static Scheduler {
+    static SCHEDULER: OnceLock<&'static Scheduler> = OnceLock::new();
+
+    SCHEDULER.get_or_init(Scheduler::start)
+}
+
+/// State owned by a single worker thread
+struct Worker {
+    id: usize,
+
+    /// Local run queue, other workers can only steal from it
+    local: RefCell<Local<CoroutineImpl>>,
+
+    /// Number of scheduled tasks, used to periodically poll the shared queues
+    tick: Cell<u32>,
+
+    /// Xorshift state used to pick the first victim when stealing
+    seed: Cell<u32>,
+}
+
+/// Work-stealing coroutine scheduler
+///
+/// Each worker thread runs coroutines from its own local queue first, then from the queue pinned
+/// to it, then from the global queue, and finally steals half of the tasks of another worker.
+/// Workers with nothing to do are parked until new tasks are scheduled.
+pub struct Scheduler {
+    /// Number of worker threads
+    workers: usize,
+
+    /// Finished coroutines kept for reuse
+    pub(crate) pool: Pool,
+
+    /// Tasks scheduled from outside of the workers or overflowing a local queue
+    global_queue: Injector<CoroutineImpl>,
+
+    /// Tasks that must run on a particular worker, never stolen
+    pinned_queues: Vec<CachePadded<Injector<CoroutineImpl>>>,
+
+    /// Stealer handles of every worker local queue
+    stealers: Vec<Steal<CoroutineImpl>>,
+
+    /// I/O reactor of every worker, an idle worker sleeps in the poll of its reactor
+    reactors: Vec<CachePadded<Reactor>>,
+
+    /// Timers of the coroutines parked with a timeout, per worker
+    timers: Vec<CachePadded<Mutex<TimerWheel<Waiter>>>>,
+
+    /// Ids of the parked workers
+    idle: Mutex<Vec<usize>>,
+
+    /// Mirrors `idle.len()` so the schedule path can skip the lock
+    num_idle: AtomicUsize,
+
+    /// Number of workers currently trying to steal
+    searching: AtomicUsize,
+}
+
+impl Scheduler {
+    fn new(workers: usize) -> (Scheduler, Vec<Local<CoroutineImpl>>) {
+        let (stealers, locals) = (0..workers).map(|_| queue::local()).unzip();
+
+        let scheduler = Scheduler {
+            workers,
+            pool: Pool::new(),
+            global_queue: Injector::new(),
+            pinned_queues: (0..workers)
+                .map(|_| CachePadded::new(Injector::new()))
+                .collect(),
+            stealers,
+            reactors: (0..workers)
+                .map(|_| {
+                    let reactor = Reactor::new().expect("Failed to create the I/O reactor");
+
+                    CachePadded::new(reactor)
+                })
+                .collect(),
+            timers: (0..workers)
+                .map(|_| CachePadded::new(Mutex::new(TimerWheel::new())))
+                .collect(),
+            idle: Mutex::new(Vec::with_capacity(workers)),
+            num_idle: AtomicUsize::new(0),
+            searching: AtomicUsize::new(0),
+        };
+
+        (scheduler, locals)
+    }
+
+    #[cold]
+    fn start() -> &'static Scheduler {
+        let workers = config().get_workers();
+        let (scheduler, locals) = Scheduler::new(workers);
+        let scheduler: &'static Scheduler = Box::leak(Box::new(scheduler));
+
+        for (id, local) in locals.into_iter().enumerate() {
+            let (started_tx, started_rx) = mpsc::channel();
+
+            thread::Builder::new()
+                .name(format!("coroutine-worker-{}", id))
+                .spawn(move || {
+                    // Without it a coroutine stack overflow takes the process down, so a worker
+                    // only runs coroutines once its signal stack is set up
+                    let signal_stack = overflow::init_signal_stack();
+                    let failed = signal_stack.is_err();
+
+                    started_tx.send(signal_stack).ok();
+
+                    if !failed {
+                        Worker::new(id, local).run(scheduler);
+                    }
+                })
+                .expect("Failed to spawn coroutine worker thread");
+
+            if let Err(err) = started_rx.recv().expect("Coroutine worker thread exited") {
+                panic!(
+                    "Failed to set up the signal stack of worker {}: {}",
+                    id, err
+                );
+            }
+        }
+
+        scheduler
+    }
+
+    /// Schedule a ready coroutine
+    /// On a worker thread it goes to the worker local queue, otherwise to the global queue
+    pub fn schedule(&self, coroutine: CoroutineImpl) {
+        registry::set_state(&coroutine, State::Ready);
+
+        let worker = WORKER.get();
+
+        if worker.is_null() {
+            return self.schedule_global(coroutine);
+        }
+
+        let worker = unsafe { &*worker };
+
+        worker
+            .local
+            .borrow_mut()
+            .push_back(coroutine, &self.global_queue);
+
+        self.notify_local();
+    }
+
+    /// Schedule a coroutine on the global queue, any worker may run it
+    pub fn schedule_global(&self, coroutine: CoroutineImpl) {
+        registry::set_state(&coroutine, State::Ready);
+
+        self.global_queue.push(coroutine);
+
+        atomic::fence(Ordering::SeqCst);
+
+        self.wake_one();
+    }
+
+    /// Schedule a coroutine on the worker `id % workers`, it is never stolen by another worker
+    pub fn schedule_global_with_id(&self, coroutine: CoroutineImpl, id: usize) {
+        let id = id % self.workers;
+
+        registry::set_state(&coroutine, State::Ready);
+
+        self.pinned_queues[id].push(coroutine);
+
+        if self.unregister_idle(id) {
+            self.reactors[id].wake();
+        }
+    }
+
+    /// Get the reactor a descriptor is registered with
+    #[inline]
+    pub(crate) fn get_reactor(&self, fd: RawFd) -> &Reactor {
+        &self.reactors[fd as usize % self.workers]
+    }
+
+    /// Schedule the coroutine parked in `waiter` after `dur`, unless it's woken up before
+    ///
+    /// The timer goes to the wheel of the current worker, which fires it when it polls its
+    /// reactor. Outside of the workers the first worker is woken up to take the timer into account.
+    pub(crate) fn add_timeout(&self, dur: Duration, waiter: Waiter) -> TimeoutHandle<Waiter> {
+        let deadline = Instant::now() + dur;
+        let worker = WORKER.get();
+
+        let id = if worker.is_null() {
+            0
+        } else {
+            unsafe { (*worker).id }
+        };
+
+        let handle = self.timers[id].lock().unwrap().add(deadline, waiter);
+
+        if worker.is_null() && self.unregister_idle(id) {
+            self.reactors[id].wake();
+        }
+
+        handle
+    }
+
+    /// Schedule the coroutines whose timers expired, returns their number
+    fn fire_timers(&self, id: usize) -> usize {
+        let mut expired = Vec::new();
+
+        self.timers[id]
+            .lock()
+            .unwrap()
+            .advance(Instant::now(), &mut expired);
+
+        let mut woken = 0;
+
+        for waiter in expired {
+            if let Some(coroutine) = waiter.take() {
+                self.schedule(coroutine);
+
+                woken += 1;
+            }
+        }
+
+        woken
+    }
+
+    /// A task was pushed to a local queue, wake a worker to steal it unless one is already looking
+    #[inline]
+    fn notify_local(&self) {
+        atomic::fence(Ordering::SeqCst);
+
+        if self.searching.load(Ordering::SeqCst) == 0 {
+            self.wake_one();
+        }
+    }
+
+    fn wake_one(&self) {
+        if self.num_idle.load(Ordering::SeqCst) == 0 {
+            return;
+        }
+
+        let id = {
+            let mut idle = self.idle.lock().unwrap();
+            let id = idle.pop();
+
+            if id.is_some() {
+                self.num_idle.fetch_sub(1, Ordering::SeqCst);
+            }
+
+            id
+        };
+
+        if let Some(id) = id {
+            self.reactors[id].wake();
+        }
+    }
+
+    /// Returns true if the worker was idle
+    fn unregister_idle(&self, id: usize) -> bool {
+        let mut idle = self.idle.lock().unwrap();
+
+        match idle.iter().position(|&i| i == id) {
+            Some(pos) => {
+                idle.swap_remove(pos);
+
+                self.num_idle.fetch_sub(1, Ordering::SeqCst);
+
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns true if the worker `id` can find a task in any of the queues
+    fn has_work(&self, id: usize) -> bool {
+        !self.global_queue.is_empty()
+            || !self.pinned_queues[id].is_empty()
+            || self.stealers.iter().any(|s| !s.is_empty())
+    }
+
+    /// Put the worker to sleep until it is woken by a newly scheduled task or an I/O event
+    fn park_worker(&self, id: usize) {
+        if self.fire_timers(id) > 0 {
+            return;
+        }
+
+        self.pool.trim();
+
+        self.idle.lock().unwrap().push(id);
+        self.num_idle.fetch_add(1, Ordering::SeqCst);
+
+        atomic::fence(Ordering::SeqCst);
+
+        // Re-check the queues, a task may have been pushed before we were marked idle
+        let timeout = if self.has_work(id) {
+            Duration::ZERO
+        } else {
+            let next_timer = self.timers[id].lock().unwrap().next_timeout(Instant::now());
+
+            next_timer.map_or(config().get_io_poll_timeout(), |next| {
+                next.min(config().get_io_poll_timeout())
+            })
+        };
+
+        self.reactors[id].poll(Some(timeout));
+
+        self.unregister_idle(id);
+
+        self.fire_timers(id);
+    }
+}
+
+impl Worker {
+    fn new(id: usize, local: Local<CoroutineImpl>) -> Worker {
+        Worker {
+            id,
+            local: RefCell::new(local),
+            tick: Cell::new(0),
+            seed: Cell::new(id as u32 + 1),
+        }
+    }
+
+    /// The home loop of a worker thread
+    fn run(&self, scheduler: &'static Scheduler) -> ! {
+        WORKER.set(self as *const _);
+
+        loop {
+            match self.next_task(scheduler) {
+                Some(coroutine) => {
+                    run_coroutine(coroutine);
+
+                    scheduler.reactors[self.id].flush();
+                }
+                None => scheduler.park_worker(self.id),
+            }
+        }
+    }
+
+    fn next_task(&self, scheduler: &Scheduler) -> Option<CoroutineImpl> {
+        let tick = self.tick.get().wrapping_add(1);
+
+        self.tick.set(tick);
+
+        if tick % GLOBAL_POLL_INTERVAL == 0 {
+            // Don't let a busy worker starve the coroutines waiting for I/O or a timer
+            scheduler.reactors[self.id].poll(Some(Duration::ZERO));
+            scheduler.fire_timers(self.id);
+
+            let task = scheduler.pinned_queues[self.id]
+                .pop()
+                .or_else(|| scheduler.global_queue.pop());
+
+            if task.is_some() {
+                return task;
+            }
+        }
+
+        let task = self.local.borrow_mut().pop();
+
+        if task.is_some() {
+            return task;
+        }
+
+        let task = scheduler.pinned_queues[self.id].pop();
+
+        if task.is_some() {
+            return task;
+        }
+
+        let task = scheduler
+            .global_queue
+            .pop_batch_into(&mut self.local.borrow_mut(), GLOBAL_BATCH_SIZE);
+
+        if task.is_some() {
+            return task;
+        }
+
+        self.steal(scheduler)
+    }
+
+    fn steal(&self, scheduler: &Scheduler) -> Option<CoroutineImpl> {
+        let workers = scheduler.workers;
+
+        if workers == 1 {
+            return None;
+        }
+
+        // Don't let more than half of the workers search at the same time
+        if 2 * scheduler.searching.fetch_add(1, Ordering::SeqCst) >= workers {
+            scheduler.searching.fetch_sub(1, Ordering::SeqCst);
+
+            return None;
+        }
+
+        let start = self.next_seed() as usize % workers;
+        let mut local = self.local.borrow_mut();
+
+        let task = (0..workers)
+            .map(|i| (start + i) % workers)
+            .filter(|&victim| victim != self.id)
+            .find_map(|victim| scheduler.stealers[victim].steal_into(&mut local))
+            .or_else(|| {
+                // Catch anything pushed to the global queue while searching
+                scheduler
+                    .global_queue
+                    .pop_batch_into(&mut local, GLOBAL_BATCH_SIZE)
+            });
+
+        let last_searcher = scheduler.searching.fetch_sub(1, Ordering::SeqCst) == 1;
+
+        // The last searcher found work, so there may be more, let another worker look for it
+        if task.is_some() && last_searcher {
+            drop(local);
+
+            scheduler.wake_one();
+        }
+
+        task
+    }
+
+    #[inline]
+    fn next_seed(&self) -> u32 {
+        let mut x = self.seed.get();
+
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+
+        self.seed.set(x);
+
+        x
+    }
+}
@@ -0,0 +1,172 @@
+use std::{
+    marker::PhantomData,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+};
+
+use crate::{
+    builder::CoroutineBuilder,
+    scoped_join_handle::ScopedJoinHandle,
+    sync::{AtomicOption, blocker::Blocker},
+};
+
+/// A scope to spawn coroutines which may borrow from the stack of the caller of `scope`
+pub struct CoroutineScope<'scope, 'env: 'scope> {
+    data: Arc<ScopeData>,
+
+    // Invariant lifetimes, like `std::thread::Scope`
+    scope: PhantomData<&'scope mut &'scope ()>,
+    env: PhantomData<&'env mut &'env ()>,
+}
+
+/// State shared by a scope and its coroutines
+pub(crate) struct ScopeData {
+    /// Number of coroutines whose result is not dropped or taken yet
+    running: AtomicUsize,
+
+    /// A coroutine panicked and its handle was not joined
+    a_panicked: AtomicBool,
+
+    /// The context waiting for the coroutines at the end of the scope
+    to_wake: AtomicOption<Arc<Blocker>>,
+}
+
+/// Creates a scope for spawning coroutines which may borrow non-`'static` data
+///
+/// All the coroutines spawned in the scope are joined before `scope` returns. If one of them
+/// panicked and was not joined by hand, `scope` panics once all of them are done. While it waits,
+/// the caller is suspended if it's a coroutine and can't be cancelled: the coroutines may borrow
+/// from its stack.
+pub fn scope<'env, F, T>(f: F) -> T
+where
+    F: for<'scope> FnOnce(&'scope CoroutineScope<'scope, 'env>) -> T,
+{
+    let scope = CoroutineScope {
+        data: Arc::new(ScopeData {
+            running: AtomicUsize::new(0),
+            a_panicked: AtomicBool::new(false),
+            to_wake: AtomicOption::none(),
+        }),
+        scope: PhantomData,
+        env: PhantomData,
+    };
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| f(&scope)));
+
+    scope.data.wait();
+
+    match result {
+        Err(err) => panic::resume_unwind(err),
+        Ok(_) if scope.data.a_panicked.load(Ordering::Relaxed) => {
+            panic!("a scoped coroutine panicked")
+        }
+        Ok(result) => result,
+    }
+}
+
+impl<'scope> CoroutineScope<'scope, '_> {
+    /// Spawns a coroutine with the default configuration in the scope
+    ///
+    /// # Safety
+    ///
+    /// Same as `spawn`: the closure must not block the worker thread, nor hold thread local
+    /// references across yields.
+    pub unsafe fn spawn<F, T>(&'scope self, f: F) -> ScopedJoinHandle<'scope, T>
+    where
+        F: FnOnce() -> T + Send + 'scope,
+        T: Send + 'scope,
+    {
+        unsafe { CoroutineBuilder::new().spawn_scoped(self, f) }.expect("Failed to spawn coroutine")
+    }
+
+    pub(crate) fn data(&self) -> &Arc<ScopeData> {
+        &self.data
+    }
+}
+
+impl ScopeData {
+    pub(crate) fn increment(&self) {
+        self.running.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn decrement(&self, panicked: bool) {
+        if panicked {
+            self.a_panicked.store(true, Ordering::Relaxed);
+        }
+
+        if self.running.fetch_sub(1, Ordering::Release) == 1 {
+            if let Some(blocker) = self.to_wake.take() {
+                blocker.unpark();
+            }
+        }
+    }
+
+    /// Wait until all the coroutines of the scope are done, ignoring the cancel
+    fn wait(&self) {
+        while self.running.load(Ordering::Acquire) != 0 {
+            let blocker = Arc::new(Blocker::new(true));
+
+            // Register the blocker first, then re-check the count
+            self.to_wake.store(blocker.clone());
+
+            if self.running.load(Ordering::Acquire) != 0 {
+                blocker.park(None).ok();
+            } else {
+                self.to_wake.take();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{panic, time::Duration};
+
+    use super::*;
+    use crate::sleep;
+
+    #[test]
+    fn coroutines_borrow_from_the_parent_stack() {
+        let numbers: Vec<u64> = (1..=100).collect();
+        let mut total = 0;
+
+        scope(|s| {
+            let handles: Vec<_> = numbers
+                .chunks(10)
+                .map(|chunk| unsafe {
+                    s.spawn(move || {
+                        sleep(Duration::from_millis(1));
+
+                        chunk.iter().sum::<u64>()
+                    })
+                })
+                .collect();
+
+            total = handles.into_iter().map(|h| h.join().unwrap()).sum();
+        });
+
+        assert_eq!(total, 5050);
+    }
+
+    #[test]
+    fn unjoined_panic_propagates() {
+        let done = AtomicBool::new(false);
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            scope(|s| unsafe {
+                s.spawn(|| panic!("child"));
+                s.spawn(|| {
+                    sleep(Duration::from_millis(10));
+
+                    done.store(true, Ordering::Relaxed);
+                });
+            })
+        }));
+
+        assert!(result.is_err());
+        assert!(done.load(Ordering::Relaxed));
+    }
+}
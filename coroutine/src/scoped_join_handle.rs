@@ -0,0 +1,71 @@
+use std::{marker::PhantomData, sync::Arc, thread};
+
+use crate::{Coroutine, JoinError, JoinHandle, scope::ScopeData, sync::AtomicOption};
+
+/// JoinHandle for a coroutine spawned in a `scope`
+pub struct ScopedJoinHandle<'scope, T> {
+    handle: JoinHandle<()>,
+    packet: Arc<Packet<'scope, T>>,
+}
+
+/// Result of a scoped coroutine, shared by the coroutine and its handle
+///
+/// The scope counts the packets rather than the coroutines: the result may borrow from the scope,
+/// so the scope can only end once it is dropped or taken.
+pub(crate) struct Packet<'scope, T> {
+    scope: Arc<ScopeData>,
+    result: AtomicOption<thread::Result<T>>,
+    _marker: PhantomData<&'scope ()>,
+}
+
+impl<'scope, T> ScopedJoinHandle<'scope, T> {
+    pub(crate) fn new(handle: JoinHandle<()>, packet: Arc<Packet<'scope, T>>) -> Self {
+        ScopedJoinHandle { handle, packet }
+    }
+
+    /// Returns a reference to the underlying coroutine
+    pub fn coroutine(&self) -> &Coroutine {
+        self.handle.coroutine()
+    }
+
+    /// Return true if the coroutine is finished
+    pub fn is_done(&self) -> bool {
+        self.handle.is_done()
+    }
+
+    /// Join the coroutine, returning the result produced or why it didn't complete
+    pub fn join(self) -> Result<T, JoinError> {
+        self.handle.wait();
+
+        match self.packet.result.take() {
+            Some(Ok(t)) => Ok(t),
+            Some(Err(panic)) => Err(JoinError::from_panic(panic)),
+            None => Err(JoinError::Cancelled),
+        }
+    }
+}
+
+impl<T> Packet<'_, T> {
+    pub(crate) fn new(scope: Arc<ScopeData>) -> Self {
+        scope.increment();
+
+        Packet {
+            scope,
+            result: AtomicOption::none(),
+            _marker: PhantomData,
+        }
+    }
+
+    pub(crate) fn set(&self, result: thread::Result<T>) {
+        self.result.store(result);
+    }
+}
+
+impl<T> Drop for Packet<'_, T> {
+    fn drop(&mut self) {
+        // Drop the result before the scope may end, it can borrow from the scope
+        let unhandled_panic = matches!(self.result.take(), Some(Err(_)));
+
+        self.scope.decrement(unhandled_panic);
+    }
+}
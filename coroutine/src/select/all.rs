@@ -0,0 +1,70 @@
+use std::time::Instant;
+
+use crate::select::{Selectable, Token, or::earliest};
+
+/// Completes once both operations complete, each one completes as soon as it can
+#[derive(Debug)]
+pub struct All<A: Selectable, B: Selectable> {
+    left: A,
+    right: B,
+    left_output: Option<A::Output>,
+    right_output: Option<B::Output>,
+}
+
+impl<A: Selectable, B: Selectable> All<A, B> {
+    pub fn new(left: A, right: B) -> All<A, B> {
+        All {
+            left,
+            right,
+            left_output: None,
+            right_output: None,
+        }
+    }
+}
+
+impl<A: Selectable, B: Selectable> Selectable for All<A, B> {
+    type Output = (A::Output, B::Output);
+
+    fn try_select(&mut self) -> Option<Self::Output> {
+        if self.left_output.is_none() {
+            self.left_output = self.left.try_select();
+        }
+
+        if self.right_output.is_none() {
+            self.right_output = self.right.try_select();
+        }
+
+        if self.left_output.is_none() || self.right_output.is_none() {
+            return None;
+        }
+
+        self.left_output.take().zip(self.right_output.take())
+    }
+
+    fn watch(&mut self, token: &Token) {
+        if self.left_output.is_none() {
+            self.left.watch(token);
+        }
+
+        if self.right_output.is_none() {
+            self.right.watch(token);
+        }
+    }
+
+    fn unwatch(&mut self, token: &Token) {
+        if self.left_output.is_none() {
+            self.left.unwatch(token);
+        }
+
+        if self.right_output.is_none() {
+            self.right.unwatch(token);
+        }
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        let left = self.left_output.is_none().then(|| self.left.deadline());
+        let right = self.right_output.is_none().then(|| self.right.deadline());
+
+        earliest(left.flatten(), right.flatten())
+    }
+}
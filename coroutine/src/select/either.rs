@@ -0,0 +1,6 @@
+/// Output of the one of two operations which completed first
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Either<L, R> {
+    Left(L),
+    Right(R),
+}
@@ -0,0 +1,70 @@
+/// Waits for the first of several operations to complete and runs its branch
+///
+/// Each branch is `pattern = operation => body`, where the operation implements
+/// [`Selectable`](crate::select::Selectable): a reference to a channel receiver, the readiness of
+/// a socket, a [`timeout`](crate::select::timeout) or a `&mut Interval`. The running coroutine is
+/// registered on all the operations at once and suspended until one of them completes, the others
+/// are unregistered before the branch runs. When several operations can complete, the first
+/// branch wins. The branches are separated by commas.
+#[macro_export]
+macro_rules! select {
+    (@src $e:expr) => { $e };
+    (@src $e:expr, $($rest:expr),+) => {
+        $crate::select::Or::new($e, $crate::select!(@src $($rest),+))
+    };
+
+    (@pat [] $p:pat, last) => { $p };
+    (@pat [] $p:pat) => { $crate::select::Either::Left($p) };
+    (@pat [_ $($depth:tt)*] $p:pat $(, $last:ident)?) => {
+        $crate::select::Either::Right($crate::select!(@pat [$($depth)*] $p $(, $last)?))
+    };
+
+    (@build [$($arms:tt)*] [$($depth:tt)*] [$($src:expr),*]
+        $p:pat = $e:expr => $body:expr $(,)?) => {
+        match $crate::select::wait($crate::select!(@src $($src,)* $e)) {
+            $($arms)*
+            $crate::select!(@pat [$($depth)*] $p, last) => $body,
+        }
+    };
+    (@build [$($arms:tt)*] [$($depth:tt)*] [$($src:expr),*]
+        $p:pat = $e:expr => $body:expr, $($rest:tt)+) => {
+        $crate::select!(
+            @build
+            [$($arms)* $crate::select!(@pat [$($depth)*] $p) => $body,]
+            [$($depth)* _]
+            [$($src,)* $e]
+            $($rest)+
+        )
+    };
+
+    ($($branches:tt)+) => { $crate::select!(@build [] [] [] $($branches)+) };
+}
+
+/// Waits for all the operations to complete, returns their outputs in a tuple
+///
+/// The operations are the ones of [`select!`](crate::select!). Each of them completes as soon as
+/// it can, the running coroutine is suspended until all of them did.
+#[macro_export]
+macro_rules! join {
+    (@src $e:expr) => { $e };
+    (@src $e:expr, $($rest:expr),+) => {
+        $crate::select::All::new($e, $crate::join!(@src $($rest),+))
+    };
+
+    (@pat $name:ident) => { $name };
+    (@pat $name:ident $($rest:ident)+) => { ($name, $crate::join!(@pat $($rest)+)) };
+
+    // Every step names its output `output`, the names don't clash as they come from distinct
+    // expansions
+    (@build [$($names:ident)*] [$($src:expr),*] $e:expr $(,)?) => {{
+        let $crate::join!(@pat $($names)* output) =
+            $crate::select::wait($crate::join!(@src $($src,)* $e));
+
+        ($($names,)* output,)
+    }};
+    (@build [$($names:ident)*] [$($src:expr),*] $e:expr, $($rest:tt)+) => {
+        $crate::join!(@build [$($names)* output] [$($src,)* $e] $($rest)+)
+    };
+
+    ($($operations:tt)+) => { $crate::join!(@build [] [] $($operations)+) };
+}
@@ -0,0 +1,153 @@
+//! Waiting for several operations at once, see [`select!`](crate::select!) and
+//! [`join!`](crate::join!)
+//!
+//! Channel receivers, socket readiness, timeouts and intervals are [`Selectable`]. Other event
+//! sources can take part by implementing the trait and waking the [`Token`] they are watched with.
+
+mod all;
+mod either;
+mod macros;
+mod or;
+mod selectable;
+mod timeout;
+mod token;
+
+use std::time::Instant;
+
+pub use all::All;
+pub use either::Either;
+pub use or::Or;
+pub use selectable::Selectable;
+pub use timeout::{Timeout, timeout};
+pub use token::Token;
+
+use crate::{park::ParkError, sync::wait_node};
+
+/// Waits until the operation completes and returns its output
+///
+/// The running coroutine is suspended while it waits, the calling thread is blocked outside of
+/// coroutines. A cancelled coroutine unwinds once the operation is unwatched.
+pub fn wait<S: Selectable>(mut selectable: S) -> S::Output {
+    loop {
+        if let Some(output) = selectable.try_select() {
+            return output;
+        }
+
+        let token = Token::new();
+
+        selectable.watch(&token);
+
+        let timeout = selectable
+            .deadline()
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()));
+
+        let ret = token.park(timeout);
+
+        selectable.unwatch(&token);
+
+        if ret == Err(ParkError::Cancelled) {
+            wait_node::check_cancel();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::Write,
+        time::{Duration, Instant},
+    };
+
+    use super::timeout;
+    use crate::{
+        Interval,
+        net::UnixStream,
+        spawn,
+        sync::{mpsc, oneshot},
+    };
+
+    #[test]
+    fn select_takes_first_ready_branch() {
+        let handle = unsafe {
+            spawn(|| {
+                let (tx_a, rx_a) = mpsc::channel::<u32>();
+                let (tx_b, rx_b) = mpsc::channel::<&str>();
+
+                let sender = spawn(move || {
+                    crate::sleep(Duration::from_millis(10));
+
+                    tx_b.send("b").unwrap();
+                });
+
+                let received = crate::select! {
+                    a = &rx_a => format!("a {:?}", a),
+                    b = &rx_b => format!("b {:?}", b),
+                    _ = timeout(Duration::from_secs(5)) => "timeout".to_string(),
+                };
+
+                sender.join().unwrap();
+
+                // The losers were unregistered, the channel still works
+                tx_a.send(1).unwrap();
+
+                (received, rx_a.recv())
+            })
+        };
+
+        assert_eq!(handle.join().unwrap(), ("b Ok(\"b\")".to_string(), Ok(1)));
+    }
+
+    #[test]
+    fn select_times_out_and_watches_sockets() {
+        let handle = unsafe {
+            spawn(|| {
+                let (a, mut b) = UnixStream::pair().unwrap();
+                let mut interval = Interval::new(Duration::from_millis(5));
+
+                interval.tick();
+
+                let start = Instant::now();
+
+                let timed_out = crate::select! {
+                    _ = a.readable() => false,
+                    _ = timeout(Duration::from_millis(20)) => true,
+                };
+
+                let elapsed = start.elapsed();
+                let ticked = crate::select! {
+                    _ = a.readable() => false,
+                    _ = &mut interval => true,
+                };
+
+                b.write_all(b"ping").unwrap();
+
+                let readable = crate::select! {
+                    _ = a.readable() => true,
+                    _ = timeout(Duration::from_secs(5)) => false,
+                };
+
+                (timed_out, elapsed, ticked, readable)
+            })
+        };
+
+        let (timed_out, elapsed, ticked, readable) = handle.join().unwrap();
+
+        assert!(timed_out);
+        assert!(elapsed >= Duration::from_millis(20));
+        assert!(ticked);
+        assert!(readable);
+    }
+
+    #[test]
+    fn join_waits_for_all() {
+        let (tx_a, rx_a) = oneshot::channel();
+        let (tx_b, rx_b) = mpsc::channel();
+
+        let handle = unsafe { spawn(move || crate::join!(&rx_a, &rx_b, timeout(Duration::ZERO))) };
+
+        tx_b.send("b").unwrap();
+        tx_a.send(1).unwrap();
+
+        assert_eq!(handle.join().unwrap(), (Ok(1), Ok("b"), ()));
+    }
+}
@@ -0,0 +1,50 @@
+use std::time::Instant;
+
+use crate::select::{Either, Selectable, Token};
+
+/// Completes with the first of two operations which completes, the left one when both can
+#[derive(Debug)]
+pub struct Or<A, B> {
+    left: A,
+    right: B,
+}
+
+impl<A: Selectable, B: Selectable> Or<A, B> {
+    pub fn new(left: A, right: B) -> Or<A, B> {
+        Or { left, right }
+    }
+}
+
+impl<A: Selectable, B: Selectable> Selectable for Or<A, B> {
+    type Output = Either<A::Output, B::Output>;
+
+    fn try_select(&mut self) -> Option<Self::Output> {
+        if let Some(output) = self.left.try_select() {
+            return Some(Either::Left(output));
+        }
+
+        self.right.try_select().map(Either::Right)
+    }
+
+    fn watch(&mut self, token: &Token) {
+        self.left.watch(token);
+        self.right.watch(token);
+    }
+
+    fn unwatch(&mut self, token: &Token) {
+        self.left.unwatch(token);
+        self.right.unwatch(token);
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        earliest(self.left.deadline(), self.right.deadline())
+    }
+}
+
+/// The earliest of two optional deadlines
+pub(crate) fn earliest(a: Option<Instant>, b: Option<Instant>) -> Option<Instant> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
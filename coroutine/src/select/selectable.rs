@@ -0,0 +1,28 @@
+use std::time::Instant;
+
+use crate::select::Token;
+
+/// An operation `select!` and `join!` can wait for
+///
+/// The waiting loop first attempts the operation with `try_select`. When it can't complete yet,
+/// the operation is watched with a token which wakes the loop up, and unwatched after the wake up.
+/// The token is woken once for all the watched operations, so a source which passed its wake up to
+/// the token and whose operation is not completed afterwards must pass it on to its next waiter.
+pub trait Selectable {
+    /// Result of the completed operation
+    type Output;
+
+    /// Complete the operation if it can be done without waiting
+    fn try_select(&mut self) -> Option<Self::Output>;
+
+    /// Wake `token` once the operation may complete, right away if it already can
+    fn watch(&mut self, token: &Token);
+
+    /// Stop waking `token`
+    fn unwatch(&mut self, token: &Token);
+
+    /// Time at which the operation completes without being woken, if any
+    fn deadline(&self) -> Option<Instant> {
+        None
+    }
+}
@@ -0,0 +1,37 @@
+use std::time::{Duration, Instant};
+
+use crate::select::{Selectable, Token};
+
+/// An operation which completes at a deadline, the timeout branch of a `select!`
+#[derive(Debug, Clone, Copy)]
+pub struct Timeout {
+    deadline: Instant,
+}
+
+/// Creates an operation completing once `dur` elapses
+pub fn timeout(dur: Duration) -> Timeout {
+    Timeout::at(Instant::now() + dur)
+}
+
+impl Timeout {
+    /// Creates an operation completing at `deadline`
+    pub fn at(deadline: Instant) -> Timeout {
+        Timeout { deadline }
+    }
+}
+
+impl Selectable for Timeout {
+    type Output = ();
+
+    fn try_select(&mut self) -> Option<()> {
+        (Instant::now() >= self.deadline).then_some(())
+    }
+
+    fn watch(&mut self, _token: &Token) {}
+
+    fn unwatch(&mut self, _token: &Token) {}
+
+    fn deadline(&self) -> Option<Instant> {
+        Some(self.deadline)
+    }
+}
@@ -0,0 +1,44 @@
+use std::{fmt, sync::Arc, time::Duration};
+
+use crate::{park::ParkError, sync::wait_node::WaitNode};
+
+/// Wakes up a `select!` or a `join!` waiting for its operations
+///
+/// Waking it up is only a hint: the waiter attempts all its operations again and goes on waiting
+/// if none completes.
+#[derive(Clone)]
+pub struct Token {
+    node: Arc<WaitNode>,
+}
+
+impl Token {
+    pub(crate) fn new() -> Token {
+        Token {
+            node: WaitNode::new(),
+        }
+    }
+
+    /// Wake up the waiter
+    #[inline]
+    pub fn wake(&self) {
+        self.node.grant();
+    }
+
+    #[inline]
+    pub(crate) fn node(&self) -> &Arc<WaitNode> {
+        &self.node
+    }
+
+    #[inline]
+    pub(crate) fn park(&self, timeout: Option<Duration>) -> Result<(), ParkError> {
+        self.node.park(timeout)
+    }
+}
+
+impl fmt::Debug for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Token")
+            .field("woken", &self.node.is_granted())
+            .finish()
+    }
+}
@@ -0,0 +1,71 @@
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{is_coroutine, park::Park};
+
+/// Suspends the current coroutine for at least `dur`
+/// Outside of coroutines the current thread sleeps instead
+pub fn sleep(dur: Duration) {
+    if !is_coroutine() {
+        return thread::sleep(dur);
+    }
+
+    // Nobody unparks it, the park only ends with the timeout or a cancel
+    let park = Park::new();
+
+    park.park_timeout(Some(dur)).ok();
+}
+
+/// Suspends the current coroutine until `deadline`
+/// Returns right away if the deadline has already passed
+pub fn sleep_until(deadline: Instant) {
+    let dur = deadline.saturating_duration_since(Instant::now());
+
+    if !dur.is_zero() {
+        sleep(dur);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spawn;
+
+    #[test]
+    fn sleeping_coroutines_do_not_block_workers() {
+        let start = Instant::now();
+
+        let handles: Vec<_> = (0..200)
+            .map(|_| unsafe { spawn(|| sleep(Duration::from_millis(50))) })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let elapsed = start.elapsed();
+
+        assert!(elapsed >= Duration::from_millis(50));
+        assert!(elapsed < Duration::from_secs(2), "took {:?}", elapsed);
+    }
+
+    #[test]
+    fn interval_ticks_at_period() {
+        let handle = unsafe {
+            spawn(|| {
+                let mut interval = crate::Interval::new(Duration::from_millis(20));
+                let first = interval.tick();
+
+                for _ in 0..3 {
+                    interval.tick();
+                }
+
+                first.elapsed()
+            })
+        };
+
+        assert!(handle.join().unwrap() >= Duration::from_millis(60));
+    }
+}
@@ -1,11 +1,27 @@
-use std::thread::JoinHandle;
+use crate::{block_on::block_on, builder::CoroutineBuilder, join_handle::JoinHandle};
 
-use crate::builder::CoroutineBuilder;
-
-pub unsafe fn spawn<F, T>(f: F) -> JoinHandle<()>
+/// Spawns a new coroutine with the default configuration, returning a `JoinHandle` for it
+/// The coroutine is scheduled on the global queue and may run on any worker thread
+///
+/// # Safety
+/// The closure must not block the worker thread, nor hold thread local references across yields
+pub unsafe fn spawn<F, T>(f: F) -> JoinHandle<T>
 where
     F: FnOnce() -> T + Send + 'static,
     T: Send + 'static,
 {
-    spawn_coroutine_builder(f, CoroutineBuilder::new())
+    unsafe { CoroutineBuilder::new().spawn(f) }.expect("Failed to spawn coroutine")
+}
+
+/// Spawns a new coroutine driving `fut` to completion, returning a `JoinHandle` for its output
+/// The future is polled with `block_on`, the coroutine is parked while it's pending
+///
+/// # Safety
+/// Polling the future must not block the worker thread
+pub unsafe fn spawn_future<F>(fut: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    unsafe { spawn(move || block_on(fut)) }
 }
@@ -0,0 +1,101 @@
+use std::{
+    io,
+    panic::{self, AssertUnwindSafe},
+    sync::Arc,
+};
+
+use crate::{
+    blocking_pool::get_blocking_pool,
+    sync::{AtomicOption, blocker::Blocker},
+};
+
+/// Runs the blocking call `f` on a thread of the blocking pool and returns its result
+///
+/// The current coroutine is parked until the call is done, so that the worker keeps running the
+/// other coroutines meanwhile. Outside of coroutines the current thread is blocked instead. A panic
+/// of `f` is resumed in the caller. A cancelled coroutine unwinds right away, the call still runs
+/// to completion and its result is dropped.
+///
+/// Fails if the pool has no thread and can't start one, `f` isn't called then.
+pub fn spawn_blocking<F, T>(f: F) -> io::Result<T>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let blocker = Blocker::current();
+    let result = Arc::new(AtomicOption::none());
+
+    {
+        let blocker = blocker.clone();
+        let result = result.clone();
+
+        get_blocking_pool().execute(Box::new(move || {
+            result.store(panic::catch_unwind(AssertUnwindSafe(f)));
+
+            blocker.unpark();
+        }))?;
+    }
+
+    // A cancel unwinds the coroutine, unless the cancel is disabled and the wait goes on
+    let result = loop {
+        blocker.park(None).ok();
+
+        if let Some(result) = result.take() {
+            break result;
+        }
+    };
+
+    match result {
+        Ok(t) => Ok(t),
+        Err(panic) => panic::resume_unwind(panic),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use super::*;
+    use crate::spawn;
+
+    #[test]
+    fn blocking_calls_do_not_stall_workers() {
+        let start = Instant::now();
+
+        // Far more blocked calls than workers, they all sleep at the same time
+        let handles: Vec<_> = (0..64)
+            .map(|i| unsafe {
+                spawn(move || {
+                    spawn_blocking(move || {
+                        thread::sleep(Duration::from_millis(50));
+
+                        i
+                    })
+                    .unwrap()
+                })
+            })
+            .collect();
+
+        for (i, handle) in handles.into_iter().enumerate() {
+            assert_eq!(handle.join().unwrap(), i);
+        }
+
+        assert!(start.elapsed() < Duration::from_secs(2));
+        assert!(get_blocking_pool().threads() > 1);
+    }
+
+    #[test]
+    fn works_out_of_coroutines_and_resumes_panics() {
+        assert_eq!(spawn_blocking(|| 42).unwrap(), 42);
+
+        let result = panic::catch_unwind(|| spawn_blocking(|| panic!("blocking")));
+
+        assert_eq!(
+            result.unwrap_err().downcast_ref::<&str>(),
+            Some(&"blocking")
+        );
+    }
+}
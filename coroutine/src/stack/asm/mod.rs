@@ -1,3 +1,6 @@
+#[cfg(target_arch = "x86_64")]
+mod x86_64;
+
 /// Register contexts used in various architectures
 #[inline]
 pub(crate) fn align_down(sp: *mut usize) -> *mut usize {
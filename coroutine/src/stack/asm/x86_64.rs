@@ -0,0 +1,47 @@
+//! Context switch routines for the x86_64 System V ABI
+//! The register layout matches `Register`: rbx, rsp, rbp, _, r12, r13, r14, r15
+
+use core::arch::global_asm;
+
+global_asm!(
+    ".text",
+    ".global bootstrap_green_task",
+    ".type bootstrap_green_task,@function",
+    ".align 16",
+    "bootstrap_green_task:",
+    // Setup the function arguments
+    "    mov %r12, %rdi",
+    "    mov %r13, %rsi",
+    // Align the stack pointer and push the init function as the return address
+    "    and $-16, %rsp",
+    "    mov %r14, (%rsp)",
+    "    ret",
+    ".size bootstrap_green_task,.-bootstrap_green_task",
+    "",
+    ".text",
+    ".global swap_registers",
+    ".type swap_registers,@function",
+    ".align 16",
+    "swap_registers:",
+    // Save the callee saved registers of the current context into `out_regs`
+    "    mov %rbx, (0*8)(%rdi)",
+    "    mov %rsp, (1*8)(%rdi)",
+    "    mov %rbp, (2*8)(%rdi)",
+    "    mov %r12, (4*8)(%rdi)",
+    "    mov %r13, (5*8)(%rdi)",
+    "    mov %r14, (6*8)(%rdi)",
+    "    mov %r15, (7*8)(%rdi)",
+    // Load the registers of the context to resume from `in_regs`
+    "    mov (0*8)(%rsi), %rbx",
+    "    mov (1*8)(%rsi), %rsp",
+    "    mov (2*8)(%rsi), %rbp",
+    "    mov (4*8)(%rsi), %r12",
+    "    mov (5*8)(%rsi), %r13",
+    "    mov (6*8)(%rsi), %r14",
+    "    mov (7*8)(%rsi), %r15",
+    // Return into the resumed context
+    "    pop %rax",
+    "    jmp *%rax",
+    ".size swap_registers,.-swap_registers",
+    options(att_syntax)
+);
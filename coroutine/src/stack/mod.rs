@@ -1,8 +1,14 @@
-use std::{os::raw::c_void, ptr};
+use std::{io, os::raw::c_void, ptr};
 
-//pub(crate) use asm::{InitFn, align_down, mut_offset};
+pub(crate) use asm::InitFn;
 pub use sys_stack::SysStack;
-pub use unix::{page_size, x86_64::Register};
+pub use unix::{max_stack_size, page_size, x86_64::Register};
+pub(crate) use unix::{
+    overflow,
+    x86_64::{initialize_call_frame, swap_registers},
+};
+
+use crate::config::config;
 
 mod asm;
 mod stack_error;
@@ -10,10 +16,10 @@ mod sys_stack;
 mod unix;
 
 /// Generator stack
-/// This struct will not deallocate the memory
-/// `StackBox` will track and deallocate it
+/// The memory, including the guard pages, is unmapped when the stack is dropped
 pub struct Stack {
     buf: SysStack,
+    guard_pages: usize,
 }
 
 impl Stack {
@@ -21,8 +27,9 @@ impl Stack {
     pub fn new(size: usize) -> Stack {
         let track = (size & 1) != 0;
         let bytes = usize::max(size * std::mem::size_of::<usize>(), SysStack::min_size());
-        let buf = SysStack::allocate(bytes, true).expect("Failed to allocate sys stack");
-        let stack = Stack { buf };
+        let guard_pages = config().get_stack_guard_pages();
+        let buf = SysStack::allocate(bytes, guard_pages).expect("Failed to allocate sys stack");
+        let stack = Stack { buf, guard_pages };
 
         // If size is not `even` we do the full footprint test
         let count = if track {
@@ -69,6 +76,17 @@ impl Stack {
         cap - offset
     }
 
+    /// Fill the region used so far again, so that a reused stack measures its new usage only
+    /// Only meant for tracked stacks, the trimmed pages are faulted back in
+    pub(crate) fn reset_used_size(&self) {
+        let start = unsafe { self.begin().add(self.size() - self.get_used_size()) };
+        let end = self.end();
+
+        if start < end {
+            unsafe { ptr::write_bytes(start, 0xEE, end.offset_from(start) as usize) };
+        }
+    }
+
     /// Get the stack capacity
     #[inline]
     pub fn size(&self) -> usize {
@@ -87,9 +105,31 @@ impl Stack {
         self.buf.bottom as *mut _
     }
 
-    /// Get offset
+    /// Point to the low end of the guard pages below the stack
+    pub fn guard_begin(&self) -> *mut usize {
+        (self.buf.bottom as usize - page_size() * self.guard_pages) as *mut _
+    }
+
+    /// Get offset, stored in the highest word of the stack
     fn get_offset(&self) -> *mut usize {
-        unsafe { (self.buf.top as *mut usize).offset(1) }
+        unsafe { (self.buf.top as *mut usize).offset(-1) }
+    }
+
+    /// Release the memory of the used region of an idle stack back to the OS
+    /// The highest page is kept, it holds the offset word and is the first touched on reuse. The
+    /// trimmed pages read as zero, so a tracked stack keeps reporting them as used.
+    pub(crate) fn trim(&self) -> io::Result<()> {
+        let page_size = unix::page_size();
+        let used = self.get_used_size() * std::mem::size_of::<usize>();
+        let top = self.buf.top as usize;
+        let start = (top - used + page_size - 1) & !(page_size - 1);
+        let end = (top - 1) & !(page_size - 1);
+
+        if start >= end {
+            return Ok(());
+        }
+
+        unsafe { unix::trim_stack(start as *mut c_void, end - start) }
     }
 
     /// Deallocate the stack
@@ -98,10 +138,15 @@ impl Stack {
             return;
         }
 
-        let page_size = unix::page_size();
-        let guard = (self.buf.bottom as usize - page_size) as *mut c_void;
-        let size_with_guard = self.buf.len() + page_size;
+        let guard = self.guard_begin() as *mut c_void;
+        let size_with_guard = self.buf.top as usize - guard as usize;
 
         unsafe { unix::deallocate_stack(guard, size_with_guard) };
     }
 }
+
+impl Drop for Stack {
+    fn drop(&mut self) {
+        self.drop_stack();
+    }
+}
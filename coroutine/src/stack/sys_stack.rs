@@ -42,13 +42,12 @@ impl SysStack {
         unix::min_stack_size()
     }
 
-    /// Allocates a new stack of size: `size`
-    pub(crate) fn allocate(mut size: usize, protected: bool) -> Result<SysStack, StackError> {
+    /// Allocates a new stack of size: `size`, protected by `guard_pages` pages below it
+    pub(crate) fn allocate(mut size: usize, guard_pages: usize) -> Result<SysStack, StackError> {
         let page_size = unix::page_size();
         let min_stack_size = unix::min_stack_size();
         let max_stack_size = unix::max_stack_size();
-        let add_shift = i32::from(protected);
-        let add = page_size << add_shift;
+        let add = page_size * (1 + guard_pages);
 
         if size < min_stack_size {
             size = min_stack_size;
@@ -60,9 +59,9 @@ impl SysStack {
             if size <= max_stack_size {
                 let mut ret = unsafe { unix::allocate_stack(size) };
 
-                if protected {
+                if guard_pages > 0 {
                     if let Ok(stack) = ret {
-                        ret = unsafe { unix::protect_stack(&stack) };
+                        ret = unsafe { unix::protect_stack(&stack, guard_pages) };
                     }
                 }
 
@@ -6,8 +6,8 @@ use std::{
 
 use crate::stack::sys_stack::SysStack;
 use x86_64::{
-    __rlimit_resource_t, _SC_PAGESIZE, MAP_ANON, MAP_FAILED, MAP_PRIVATE, MAP_STACK, NULL,
-    PROT_NONE, PROT_READ, PROT_WRITE, off_t, rlimit, sigaction, sigset_t, size_t,
+    __rlimit_resource_t, _SC_PAGESIZE, MADV_DONTNEED, MAP_ANON, MAP_FAILED, MAP_PRIVATE, MAP_STACK,
+    NULL, PROT_NONE, PROT_READ, PROT_WRITE, off_t, rlimit, sigaction, sigset_t, size_t, stack_t,
 };
 
 pub mod overflow;
@@ -29,6 +29,8 @@ unsafe extern "C" {
 
     fn mprotect(addr: *mut c_void, len: size_t, prot: c_int) -> c_int;
 
+    fn madvise(addr: *mut c_void, len: size_t, advice: c_int) -> c_int;
+
     #[cfg_attr(
         all(target_os = "macos", target_arch = "x86"),
         link_name = "munmap$UNIX2003"
@@ -51,6 +53,9 @@ unsafe extern "C" {
 
     #[cfg_attr(target_os = "netbsd", link_name = "__sigprocmask14")]
     fn sigprocmask(how: c_int, set: *const sigset_t, oldset: *mut sigset_t) -> c_int;
+
+    #[cfg_attr(target_os = "netbsd", link_name = "__sigaltstack14")]
+    fn sigaltstack(ss: *const stack_t, old_ss: *mut stack_t) -> c_int;
 }
 
 pub unsafe fn allocate_stack(size: usize) -> io::Result<SysStack> {
@@ -63,36 +68,45 @@ pub unsafe fn allocate_stack(size: usize) -> io::Result<SysStack> {
         if ptr == MAP_FAILED {
             Err(io::Error::last_os_error())
         } else {
-            Ok(SysStack::new(
-                (ptr as usize + size) as *mut c_void,
-                ptr as *mut c_void,
-            ))
+            Ok(SysStack::new((ptr as usize + size) as *mut c_void, ptr))
         }
     }
 }
 
-pub unsafe fn protect_stack(stack: &SysStack) -> io::Result<SysStack> {
+pub unsafe fn protect_stack(stack: &SysStack, guard_pages: usize) -> io::Result<SysStack> {
     unsafe {
         let page_size = page_size();
+        let guard_size = page_size * guard_pages;
 
-        debug_assert!(stack.len() % page_size == 0 && stack.len() != 0);
+        debug_assert!(stack.len() % page_size == 0 && stack.len() > guard_size);
 
         let ret = {
             let bottom = stack.bottom();
 
-            mprotect(bottom, page_size, PROT_NONE)
+            mprotect(bottom, guard_size, PROT_NONE)
         };
 
         if ret != 0 {
             Err(io::Error::last_os_error())
         } else {
-            let bottom = (stack.bottom() as usize + page_size) as *mut c_void;
+            let bottom = (stack.bottom() as usize + guard_size) as *mut c_void;
 
             Ok(SysStack::new(stack.top(), bottom))
         }
     }
 }
 
+/// Release the physical pages backing a stack region, they read as zero when touched again
+pub unsafe fn trim_stack(ptr: *mut c_void, size: usize) -> io::Result<()> {
+    unsafe {
+        if madvise(ptr, size, MADV_DONTNEED) != 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        }
+    }
+}
+
 pub unsafe fn deallocate_stack(ptr: *mut c_void, size: usize) {
     unsafe {
         munmap(ptr, size);
@@ -1,22 +1,38 @@
 use core::ffi::c_int;
 use std::{
     backtrace::Backtrace,
+    io,
     mem::{self, MaybeUninit},
     process,
     ptr::null_mut,
     sync::{Mutex, Once},
-    thread::{self, yield_now},
 };
 
-use crate::stack::unix::{
-    sigaction, sigaddset, sigemptyset, sigprocmask,
-    x86_64::{SIG_UNBLOCK, sighandler_t, siginfo_t, sigset_t, ucontext_t},
+use crate::{
+    runtime::ContextStack,
+    stack::{
+        SysStack,
+        unix::{
+            sigaction, sigaddset, sigaltstack, sigemptyset, sigprocmask,
+            x86_64::{SIG_UNBLOCK, sighandler_t, siginfo_t, sigset_t, stack_t, ucontext_t},
+        },
+    },
+    yield_now::yield_now,
 };
 
 use super::x86_64::{SA_ONSTACK, SA_SIGINFO, SIGBUS, SIGSEGV};
 
+/// Size of the signal stack of a worker, the handler may capture a backtrace
+const SIGNAL_STACK_SIZE: usize = 128 * 1024;
+
 static SIG_ACTION: Mutex<MaybeUninit<sigaction>> = Mutex::new(MaybeUninit::uninit());
 
+/// Handles a fault in the guard pages of the running coroutine
+///
+/// The handler runs on the signal stack of the thread, the coroutine stack is unusable. It never
+/// returns: it records the overflow as the coroutine panic and switches back to the scheduler as if
+/// the coroutine returned, abandoning the frames left on its stack without running their
+/// destructors. The scheduler then discards the stack and reports the error to the join handle.
 unsafe extern "C" fn signal_handler(signum: c_int, info: *mut siginfo_t, ctx: *mut ucontext_t) {
     unsafe {
         let _ctx = &mut *ctx;
@@ -35,14 +51,11 @@ unsafe extern "C" fn signal_handler(signum: c_int, info: *mut siginfo_t, ctx: *m
             return;
         }
 
-        eprintln!(
-            "\nCoroutine in thread '{}' has overflowed it's stack\n",
-            thread::current().name().unwrap_or("<unknown>")
-        );
-
-        crate::runtime::ContextStack::current().top().err =
-            Some(Box::new(crate::error::Error::StackErr));
+        // Only async-signal-safe work here: the handler neither allocates nor frees, the resumer
+        // makes the `Error::StackErr` payload and the worker logs the overflow
+        ContextStack::current().top().overflowed = true;
 
+        // The handler never returns, the signal would stay blocked
         let mut sigset: sigset_t = mem::zeroed();
 
         sigemptyset(&mut sigset);
@@ -51,6 +64,7 @@ unsafe extern "C" fn signal_handler(signum: c_int, info: *mut siginfo_t, ctx: *m
 
         yield_now();
 
+        // An overflowed coroutine is done, it's never resumed
         process::abort();
     }
 }
@@ -61,7 +75,7 @@ unsafe fn init() {
         let mut action: sigaction = mem::zeroed();
 
         action.sa_flags = SA_SIGINFO | SA_ONSTACK;
-        action.sa_sigaction = signal_handler as sighandler_t;
+        action.sa_sigaction = signal_handler as *const () as sighandler_t;
 
         let mut old_action = SIG_ACTION.lock().unwrap();
 
@@ -76,3 +90,54 @@ pub fn init_once() {
 
     INIT_ONCE.call_once(|| unsafe { init() });
 }
+
+/// Give the current thread its own signal stack, the overflow handler can't run on the
+/// overflowed coroutine stack
+/// The stack is never released, it's meant for the worker threads which never exit
+pub(crate) fn init_signal_stack() -> io::Result<()> {
+    let stack = SysStack::allocate(SIGNAL_STACK_SIZE, 1).map_err(io::Error::other)?;
+
+    let ss = stack_t {
+        ss_sp: stack.bottom(),
+        ss_flags: 0,
+        ss_size: stack.len(),
+    };
+
+    if unsafe { sigaltstack(&ss, null_mut()) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::hint::black_box;
+
+    use crate::{CoroutineBuilder, JoinError, spawn};
+
+    fn recurse(depth: usize) -> usize {
+        let frame = black_box([depth as u8; 1024]);
+
+        if depth == 0 {
+            return frame[0] as usize;
+        }
+
+        recurse(depth - 1) + black_box(frame)[1] as usize
+    }
+
+    #[test]
+    fn overflow_terminates_the_coroutine_only() {
+        let handle = unsafe {
+            CoroutineBuilder::new()
+                .stack_size(0x1000)
+                .spawn(|| recurse(1 << 20))
+        }
+        .unwrap();
+
+        assert!(matches!(handle.join(), Err(JoinError::StackOverflow)));
+
+        // The workers keep running the other coroutines
+        assert_eq!(unsafe { spawn(|| recurse(4)) }.join().unwrap(), 10);
+    }
+}
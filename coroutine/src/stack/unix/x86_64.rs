@@ -20,7 +20,7 @@ pub type greg_t = i64;
 
 pub const _SC_PAGESIZE: c_int = 30;
 
-pub const NULL: *mut c_void = 0 as *mut c_void;
+pub const NULL: *mut c_void = core::ptr::null_mut();
 
 pub const MAP_STACK: c_int = 0x020000;
 pub const MAP_PRIVATE: c_int = 0x0002;
@@ -31,6 +31,8 @@ pub const PROT_READ: c_int = 1;
 pub const PROT_WRITE: c_int = 2;
 pub const PROT_NONE: c_int = 0;
 
+pub const MADV_DONTNEED: c_int = 4;
+
 pub const RLIMIT_STACK: __rlimit_resource_t = 3;
 pub const RLIM_INFINITY: rlim_t = 18_446_744_073_709_551_615u64; // u64::MAX
 
@@ -42,7 +44,6 @@ pub const SIG_UNBLOCK: c_int = 0x01;
 
 unsafe extern "sysv64" {
     pub fn bootstrap_green_task();
-    pub fn prefetch(data: *const usize);
     pub fn swap_registers(out_regs: *mut Register, in_regs: *const Register);
 }
 
@@ -165,14 +166,10 @@ impl Register {
         Register { gpr: [0; 8] }
     }
 
+    /// The saved stack pointer
     #[inline]
-    pub fn prefetch(&self) {
-        let ptr = self.gpr[1] as *const usize;
-
-        unsafe {
-            prefetch(ptr); // RSP
-            prefetch(ptr.add(8)); // RSP + 8
-        }
+    pub fn sp(&self) -> usize {
+        self.gpr[1]
     }
 }
 
@@ -207,7 +204,7 @@ pub fn initialize_call_frame(
 
     unsafe {
         // Leave enough space for RET
-        *mut_offset(sp, -2) = bootstrap_green_task as usize;
+        *mut_offset(sp, -2) = bootstrap_green_task as *const () as usize;
         *mut_offset(sp, -1) = 0;
     }
 }
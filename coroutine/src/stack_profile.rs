@@ -0,0 +1,93 @@
+//! Stack usage profile of the coroutines, by name
+//!
+//! Once enabled with `ConfigBuilder::stack_profiling`, every coroutine stack is filled with a
+//! pattern and the high-water mark is measured when the coroutine is done. The marks are
+//! aggregated by coroutine name, the unnamed coroutines share one entry. A builder with
+//! `auto_stack_size` sizes its coroutines from the profile of their name.
+
+use std::{
+    collections::HashMap,
+    sync::{Mutex, OnceLock},
+};
+
+use crate::{config::config, stack_usage::StackUsage};
+
+/// Name the usage of the unnamed coroutines is recorded under
+pub const UNNAMED: &str = "<unnamed>";
+
+/// `auto_stack_size` adds half of the highest observed usage as headroom
+const HEADROOM: usize = 2;
+
+fn profile() -> &'static Mutex<HashMap<String, StackUsage>> {
+    static PROFILE: OnceLock<Mutex<HashMap<String, StackUsage>>> = OnceLock::new();
+
+    PROFILE.get_or_init(Default::default)
+}
+
+/// Returns true if the stack usage is measured
+#[inline]
+pub fn is_enabled() -> bool {
+    config().is_stack_profiling_enabled()
+}
+
+/// Record the high-water mark of a coroutine that is done, in words
+pub(crate) fn record(name: Option<&str>, used: usize) {
+    profile()
+        .lock()
+        .unwrap()
+        .entry(name.unwrap_or(UNNAMED).to_string())
+        .or_insert_with(StackUsage::new)
+        .record(used);
+}
+
+/// Returns the stack usage of the coroutines named `name`
+pub fn usage(name: &str) -> Option<StackUsage> {
+    profile().lock().unwrap().get(name).cloned()
+}
+
+/// Returns the stack usage of every coroutine name, ordered by name
+pub fn snapshot() -> Vec<(String, StackUsage)> {
+    let mut usages: Vec<_> = profile()
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(name, usage)| (name.clone(), usage.clone()))
+        .collect();
+
+    usages.sort_by(|a, b| a.0.cmp(&b.0));
+
+    usages
+}
+
+/// Forget the measured usage
+pub fn clear() {
+    profile().lock().unwrap().clear();
+}
+
+/// Stack size in words for the coroutines named `name`, the highest usage plus headroom
+/// None until a coroutine of that name was measured
+pub(crate) fn suggested_stack_size(name: &str) -> Option<usize> {
+    let max = profile().lock().unwrap().get(name)?.max();
+
+    Some(max + max / HEADROOM)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn usage_is_aggregated_by_name() {
+        record(Some("profile-test"), 100);
+        record(Some("profile-test"), 300);
+        record(Some("profile-test"), 5000);
+
+        let usage = usage("profile-test").unwrap();
+
+        assert_eq!(usage.count(), 3);
+        assert_eq!(usage.max(), 5000);
+        assert_eq!(usage.histogram(), vec![(0x100, 1), (0x200, 1), (0x2000, 1)]);
+        assert_eq!(usage.percentile(50.0), 0x200);
+        assert_eq!(suggested_stack_size("profile-test"), Some(7500));
+    }
+}
@@ -0,0 +1,85 @@
+use std::fmt;
+
+/// Number of histogram buckets
+pub(crate) const BUCKETS: usize = 13;
+
+/// Upper bound in words of the first bucket, every next bucket doubles it
+const FIRST_BUCKET: usize = 0x100;
+
+/// Stack high-water marks observed for the coroutines of one name, in words
+///
+/// The histogram counts the coroutines by power of two buckets, from 256 words up. The last
+/// bucket also counts everything above it.
+#[derive(Clone, PartialEq, Eq)]
+pub struct StackUsage {
+    count: u64,
+    max: usize,
+    buckets: [u64; BUCKETS],
+}
+
+impl StackUsage {
+    pub(crate) fn new() -> StackUsage {
+        StackUsage {
+            count: 0,
+            max: 0,
+            buckets: [0; BUCKETS],
+        }
+    }
+
+    pub(crate) fn record(&mut self, used: usize) {
+        let bucket = (0..BUCKETS)
+            .find(|&bucket| used <= FIRST_BUCKET << bucket)
+            .unwrap_or(BUCKETS - 1);
+
+        self.count += 1;
+        self.max = self.max.max(used);
+        self.buckets[bucket] += 1;
+    }
+
+    /// Number of coroutines measured
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Highest stack usage measured
+    pub fn max(&self) -> usize {
+        self.max
+    }
+
+    /// Returns the non-empty buckets as their upper bound and their number of coroutines
+    pub fn histogram(&self) -> Vec<(usize, u64)> {
+        self.buckets
+            .iter()
+            .enumerate()
+            .filter(|&(_, &count)| count > 0)
+            .map(|(bucket, &count)| (FIRST_BUCKET << bucket, count))
+            .collect()
+    }
+
+    /// Upper bound of the bucket holding the `percentile`th coroutine, between 0 and 100
+    /// Returns zero if nothing was measured
+    pub fn percentile(&self, percentile: f64) -> usize {
+        let rank = (self.count as f64 * percentile.clamp(0.0, 100.0) / 100.0).ceil() as u64;
+        let mut seen = 0;
+
+        for (upper, count) in self.histogram() {
+            seen += count;
+
+            if seen >= rank.max(1) {
+                return upper;
+            }
+        }
+
+        0
+    }
+}
+
+impl fmt::Debug for StackUsage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StackUsage")
+            .field("count", &self.count)
+            .field("max", &self.max)
+            .field("histogram", &self.histogram())
+            .finish()
+    }
+}
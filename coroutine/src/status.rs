@@ -0,0 +1,102 @@
+use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+
+use crate::sync::AtomicCell;
+
+/// Scheduling state of a coroutine
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum State {
+    /// Queued to run
+    Ready = 0,
+    Running = 1,
+
+    /// Suspended until an event source wakes it up
+    Parked = 2,
+}
+
+/// What the registry shows about a coroutine, only updated while the registry is enabled
+pub(crate) struct Status {
+    state: AtomicU8,
+
+    /// The event source the coroutine is parked on
+    reason: AtomicCell<&'static str>,
+
+    /// Stack depth in words at the last suspension, and the deepest one seen
+    stack_depth: AtomicUsize,
+    max_stack_depth: AtomicUsize,
+}
+
+impl Status {
+    pub(crate) fn new() -> Status {
+        Status {
+            state: AtomicU8::new(State::Ready as u8),
+            reason: AtomicCell::new(""),
+            stack_depth: AtomicUsize::new(0),
+            max_stack_depth: AtomicUsize::new(0),
+        }
+    }
+
+    pub(crate) fn state(&self) -> State {
+        match self.state.load(Ordering::Acquire) {
+            0 => State::Ready,
+            1 => State::Running,
+            _ => State::Parked,
+        }
+    }
+
+    pub(crate) fn set_state(&self, state: State) {
+        self.state.store(state as u8, Ordering::Release);
+    }
+
+    pub(crate) fn reason(&self) -> &'static str {
+        self.reason.load()
+    }
+
+    /// Record the suspension of the coroutine on `reason`
+    pub(crate) fn park(&self, reason: &'static str, stack_depth: Option<usize>) {
+        self.reason.store(reason);
+
+        if let Some(depth) = stack_depth {
+            self.stack_depth.store(depth, Ordering::Relaxed);
+            self.max_stack_depth.fetch_max(depth, Ordering::Relaxed);
+        }
+
+        self.set_state(State::Parked);
+    }
+
+    /// Stack depth in words at the last suspension, and the deepest one seen
+    pub(crate) fn stack_depth(&self) -> (usize, usize) {
+        (
+            self.stack_depth.load(Ordering::Relaxed),
+            self.max_stack_depth.load(Ordering::Relaxed),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn park_records_the_reason_and_the_deepest_stack() {
+        let status = Status::new();
+
+        assert_eq!(status.state(), State::Ready);
+        assert_eq!(status.stack_depth(), (0, 0));
+
+        status.park("io read", Some(200));
+
+        assert_eq!(status.state(), State::Parked);
+        assert_eq!(status.reason(), "io read");
+        assert_eq!(status.stack_depth(), (200, 200));
+
+        status.set_state(State::Running);
+        assert_eq!(status.state(), State::Running);
+
+        // A suspension in a nested generator keeps the last known depth
+        status.park("park", Some(80));
+        status.park("park timeout", None);
+
+        assert_eq!(status.reason(), "park timeout");
+        assert_eq!(status.stack_depth(), (80, 200));
+    }
+}
@@ -1,13 +1,6 @@
-use core::{
-    cell::UnsafeCell,
-    fmt,
-    mem::{ManuallyDrop, MaybeUninit},
-};
-use std::sync::atomic::{self, Ordering};
+use core::{cell::UnsafeCell, fmt, mem::MaybeUninit};
 
-use super::{
-    AtomicUnit, atomic, atomic_is_lock_free, atomic_load, atomic_store, can_transmute, lock,
-};
+use super::{atomic_is_lock_free, atomic_load, atomic_store, atomic_swap};
 
 #[repr(transparent)]
 pub struct AtomicCell<T> {
@@ -15,7 +8,7 @@ pub struct AtomicCell<T> {
 }
 
 unsafe impl<T: Send> Send for AtomicCell<T> {}
-unsafe impl<T: Sync> Sync for AtomicCell<T> {}
+unsafe impl<T: Send> Sync for AtomicCell<T> {}
 
 impl<T> AtomicCell<T> {
     /// Creates a new atomic cell initialized with `value`
@@ -25,18 +18,6 @@ impl<T> AtomicCell<T> {
         }
     }
 
-    /// Consumes the atomic cell and returns the underlying value
-    pub fn into_inner(self) -> T {
-        let this = ManuallyDrop::new(self);
-
-        // SAFETY:
-        // - Passing `self` by value guarantees that no other threads are concurrently accessing
-        // the atomic data
-        // - The raw pointer passed in is valid because we got it from an owned value
-        // - `ManuallyDrop` prevents double dropping of `T`
-        unsafe { this.as_ptr().read() }
-    }
-
     /// Returns `true` if the operations on values of this type are lock-free
     pub const fn is_lock_free() -> bool {
         atomic_is_lock_free::<T>()
@@ -77,32 +58,6 @@ impl<T: Copy> AtomicCell<T> {
     }
 }
 
-impl<T: Copy + Eq> AtomicCell<T> {
-    /// If the current value equals `current`, stores `new` into the atomic cell
-    pub fn compare_exchange(&self, current: T, new: T) -> Result<T, T> {
-        unsafe { atomic_compare_exchange_weak(self.as_ptr(), current, new) }
-    }
-
-    /// Fetches the value and applies a function to it that returns an optional new value
-    /// Returns a `Result` of `Ok(previous_value)` if the function returned `Some(_)`, else
-    /// `Err(previous_value)`
-    pub fn fetch_update<F>(&self, mut f: F) -> Result<T, T>
-    where
-        F: FnMut(T) -> Option<T>,
-    {
-        let mut previous = self.load();
-
-        while let Some(next) = f(previous) {
-            match self.compare_exchange(previous, next) {
-                x @ Ok(_) => return x,
-                Err(next_previous) => previous = next_previous,
-            }
-        }
-
-        Err(previous)
-    }
-}
-
 /// `MaybeUninit` prevents `T` from being dropped, so we need to implement `Drop` for `AtomicCell`
 /// to avoid leaks of non-`Copy` types
 impl<T> Drop for AtomicCell<T> {
@@ -118,96 +73,6 @@ impl<T> Drop for AtomicCell<T> {
     }
 }
 
-impl AtomicCell<bool> {
-    /// Applies logical `and` to the current value and returns the previous value
-    #[inline]
-    pub fn fetch_and(&self, value: bool) -> bool {
-        atomic! {
-            bool, _a,
-            {
-                let a = unsafe { &*(self.as_ptr() as *const atomic::AtomicBool) };
-
-                a.fetch_and(value, Ordering::AcqRel)
-            },
-            {
-                let _guard = lock(self.as_ptr() as usize).write();
-                let val = unsafe { &mut *(self.as_ptr()) };
-                let old = *val;
-
-                *val &= value;
-
-                old
-            }
-        }
-    }
-
-    /// Applies logical `nand` to the current value and returns the previous value
-    #[inline]
-    pub fn fetch_nand(&self, value: bool) -> bool {
-        atomic! {
-            bool, _a,
-            {
-                let a = unsafe { &*(self.as_ptr() as *const atomic::AtomicBool) };
-
-                a.fetch_nand(value, Ordering::AcqRel)
-            },
-            {
-                let _guard = lock(self.as_ptr() as usize).write();
-                let val = unsafe { &mut *(self.as_ptr()) };
-                let old = *val;
-
-                *val = !(old & value);
-
-                old
-            }
-        }
-    }
-
-    /// Applies logical `or` to the current value and returns the previous value
-    #[inline]
-    pub fn fetch_or(&self, value: bool) -> bool {
-        atomic! {
-            bool, _a,
-            {
-                let a = unsafe { &*(self.as_ptr() as *const atomic::AtomicBool) };
-
-                a.fetch_or(value, Ordering::AcqRel)
-            },
-            {
-                let _guard = lock(self.as_ptr() as usize).write();
-                let val = unsafe { &mut *(self.as_ptr()) };
-                let old = *val;
-
-                *val |= value;
-
-                old
-            }
-        }
-    }
-
-    /// Applies logical `xor` to the current value and returns the previous value
-    #[inline]
-    pub fn fetch_xor(&self, value: bool) -> bool {
-        atomic! {
-            bool, _a,
-            {
-                let a = unsafe { &*(self.as_ptr() as *const atomic::AtomicBool) };
-
-                a.fetch_xor(value, Ordering::AcqRel)
-            },
-            {
-                let _guard = lock(self.as_ptr() as usize).write();
-                let val = unsafe { &mut *(self.as_ptr()) };
-                let old = *val;
-
-                *val ^= value;
-
-                old
-            }
-        }
-    }
-}
-
 impl<T: Default> Default for AtomicCell<T> {
     fn default() -> AtomicCell<T> {
         AtomicCell::new(T::default())
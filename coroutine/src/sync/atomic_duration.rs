@@ -0,0 +1,63 @@
+use std::{
+    fmt,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+/// Stored in place of `None`
+const NONE: u64 = u64::MAX;
+
+/// An atomic `Option<Duration>` with nanosecond precision
+/// Durations longer than about 584 years are clamped
+pub struct AtomicDuration {
+    nanos: AtomicU64,
+}
+
+impl AtomicDuration {
+    pub const fn new(dur: Option<Duration>) -> AtomicDuration {
+        AtomicDuration {
+            nanos: AtomicU64::new(to_nanos(dur)),
+        }
+    }
+
+    #[inline]
+    pub fn load(&self) -> Option<Duration> {
+        match self.nanos.load(Ordering::Acquire) {
+            NONE => None,
+            nanos => Some(Duration::from_nanos(nanos)),
+        }
+    }
+
+    #[inline]
+    pub fn store(&self, dur: Option<Duration>) {
+        self.nanos.store(to_nanos(dur), Ordering::Release);
+    }
+}
+
+impl Default for AtomicDuration {
+    fn default() -> Self {
+        AtomicDuration::new(None)
+    }
+}
+
+impl fmt::Debug for AtomicDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.load().fmt(f)
+    }
+}
+
+#[inline]
+const fn to_nanos(dur: Option<Duration>) -> u64 {
+    match dur {
+        None => NONE,
+        Some(dur) => {
+            let nanos = dur.as_nanos();
+
+            if nanos >= NONE as u128 {
+                NONE - 1
+            } else {
+                nanos as u64
+            }
+        }
+    }
+}
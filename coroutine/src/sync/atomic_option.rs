@@ -1,6 +1,7 @@
-use std::sync::Arc;
+use std::{fmt, sync::Arc};
 
 use super::{AtomicCell, blocker::Blocker};
+use crate::CoroutineImpl;
 
 pub struct AtomicOption<T> {
     inner: AtomicCell<Option<T>>,
@@ -15,4 +16,43 @@ impl<T> AtomicOption<T> {
             inner: AtomicCell::new(None),
         }
     }
+
+    pub const fn some(t: T) -> AtomicOption<T> {
+        AtomicOption {
+            inner: AtomicCell::new(Some(t)),
+        }
+    }
+
+    /// Store a value, dropping the previous one
+    #[inline]
+    pub fn store(&self, t: T) {
+        self.inner.store(Some(t));
+    }
+
+    /// Take the value out, leaving `None` in its place
+    #[inline]
+    pub fn take(&self) -> Option<T> {
+        self.inner.take()
+    }
+
+    /// Returns true if there is no value
+    /// The value may be replaced concurrently, so this is only a hint
+    #[inline]
+    pub fn is_none(&self) -> bool {
+        unsafe { (*self.inner.as_ptr()).is_none() }
+    }
+}
+
+impl<T> Default for AtomicOption<T> {
+    fn default() -> Self {
+        AtomicOption::none()
+    }
+}
+
+impl<T> fmt::Debug for AtomicOption<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AtomicOption")
+            .field("is_none", &self.is_none())
+            .finish()
+    }
 }
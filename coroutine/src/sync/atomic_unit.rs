@@ -13,15 +13,4 @@ impl AtomicUnit {
 
     #[inline]
     pub(crate) fn swap(&self, _val: (), _order: Ordering) {}
-
-    #[inline]
-    pub(crate) fn compare_exchange_weak(
-        &self,
-        _current: (),
-        _new: (),
-        _success: Ordering,
-        _failure: Ordering,
-    ) -> Result<(), ()> {
-        Ok(())
-    }
 }
@@ -16,24 +16,6 @@ impl Backoff {
         Backoff { step: Cell::new(0) }
     }
 
-    /// Resets the `Backoff`
-    #[inline]
-    pub fn reset(&self) {
-        self.step.set(0);
-    }
-
-    /// Backs off in a lock-free loop
-    #[inline]
-    pub fn spin(&self) {
-        for _ in 0..1 << self.step.get().min(SPIN_LIMIT) {
-            hint::spin_loop();
-        }
-
-        if self.step.get() <= SPIN_LIMIT {
-            self.step.set(self.step.get() + 1);
-        }
-    }
-
     /// Backs off in a blocking loop
     #[inline]
     pub fn snooze(&self) {
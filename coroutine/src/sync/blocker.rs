@@ -1,7 +1,13 @@
-use crate::{is_coroutine, park::Park};
+use std::{sync::Arc, task::Wake, time::Duration};
+
+use crate::{
+    is_coroutine,
+    park::{Park, ParkError},
+};
 
 use super::{parker::Parker, thread_park::ThreadPark};
 
+/// Blocks the current coroutine, or the current thread outside of coroutines
 #[derive(Debug)]
 pub struct Blocker {
     parker: Parker,
@@ -24,4 +30,40 @@ impl Blocker {
 
         Blocker { parker }
     }
+
+    /// Create a shared Blocker for the current context
+    #[inline]
+    pub fn current() -> Arc<Blocker> {
+        Arc::new(Blocker::new(false))
+    }
+
+    /// Block until `unpark` is called, returns right away if it was already called
+    /// Fails with `ParkError::Timeout` if `timeout` elapses first
+    #[inline]
+    pub fn park(&self, timeout: Option<Duration>) -> Result<(), ParkError> {
+        match self.parker {
+            Parker::Coroutine(ref park) => park.park_timeout(timeout),
+            Parker::Thread(ref thread_park) => thread_park.park_timeout(timeout),
+        }
+    }
+
+    /// Wake up the blocked context
+    #[inline]
+    pub fn unpark(&self) {
+        match self.parker {
+            Parker::Coroutine(ref park) => park.unpark(),
+            Parker::Thread(ref thread_park) => thread_park.unpark(),
+        }
+    }
+}
+
+/// A `Waker` over a blocker unparks the blocked context, used by `block_on`
+impl Wake for Blocker {
+    fn wake(self: Arc<Blocker>) {
+        self.unpark();
+    }
+
+    fn wake_by_ref(self: &Arc<Blocker>) {
+        self.unpark();
+    }
 }
@@ -0,0 +1,74 @@
+use std::{error::Error, fmt};
+
+/// Error returned by `Receiver::recv`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecvError {
+    /// All the senders are gone and the receiver got all the messages
+    Closed,
+
+    /// The receiver fell behind, the given number of messages was skipped
+    Lagged(u64),
+}
+
+/// Error returned by `Receiver::try_recv`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryRecvError {
+    /// No message is available yet
+    Empty,
+
+    /// All the senders are gone and the receiver got all the messages
+    Closed,
+
+    /// The receiver fell behind, the given number of messages was skipped
+    Lagged(u64),
+}
+
+/// Error returned by `Receiver::recv_timeout`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecvTimeoutError {
+    /// No message was sent before the timeout
+    Timeout,
+
+    /// All the senders are gone and the receiver got all the messages
+    Closed,
+
+    /// The receiver fell behind, the given number of messages was skipped
+    Lagged(u64),
+}
+
+impl fmt::Display for RecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            RecvError::Closed => f.write_str("Channel closed"),
+            RecvError::Lagged(n) => write!(f, "Receiver lagged behind by {} messages", n),
+        }
+    }
+}
+
+impl fmt::Display for TryRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            TryRecvError::Empty => f.write_str("Channel empty"),
+            TryRecvError::Closed => f.write_str("Channel closed"),
+            TryRecvError::Lagged(n) => write!(f, "Receiver lagged behind by {} messages", n),
+        }
+    }
+}
+
+impl fmt::Display for RecvTimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            RecvTimeoutError::Timeout => f.write_str("Timed out waiting on channel"),
+            RecvTimeoutError::Closed => f.write_str("Channel closed"),
+            RecvTimeoutError::Lagged(n) => {
+                write!(f, "Receiver lagged behind by {} messages", n)
+            }
+        }
+    }
+}
+
+impl Error for RecvError {}
+
+impl Error for TryRecvError {}
+
+impl Error for RecvTimeoutError {}
@@ -0,0 +1,88 @@
+//! Broadcast channels, every receiver gets a clone of every message
+//!
+//! The channel keeps the last `cap` messages. A receiver falling further behind skips the
+//! overwritten messages and is told how many it missed with a `Lagged` error. Waiting for a
+//! message suspends the running coroutine, or blocks the calling thread outside of coroutines.
+
+mod error;
+mod receiver;
+mod sender;
+mod shared;
+
+pub use error::{RecvError, RecvTimeoutError, TryRecvError};
+pub use receiver::Receiver;
+pub use sender::Sender;
+pub use std::sync::mpsc::SendError;
+
+use shared::Shared;
+
+/// Creates a broadcast channel keeping up to `cap` messages
+///
+/// # Panics
+///
+/// Panics if `cap` is zero.
+pub fn channel<T: Clone>(cap: usize) -> (Sender<T>, Receiver<T>) {
+    let shared = Shared::new(cap);
+    let receiver = Receiver::new(shared.clone(), 0);
+
+    (Sender::new(shared), receiver)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spawn;
+
+    #[test]
+    fn every_receiver_gets_every_message() {
+        let (tx, rx) = channel(16);
+
+        let receivers: Vec<_> = (0..4)
+            .map(|_| {
+                let mut rx = rx.clone();
+
+                unsafe {
+                    spawn(move || {
+                        let mut received = Vec::new();
+
+                        while let Ok(i) = rx.recv() {
+                            received.push(i);
+                        }
+
+                        received
+                    })
+                }
+            })
+            .collect();
+
+        drop(rx);
+
+        for i in 0..10 {
+            assert_eq!(tx.send(i), Ok(4));
+        }
+
+        drop(tx);
+
+        for receiver in receivers {
+            assert_eq!(receiver.join().unwrap(), (0..10).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn slow_receiver_lags() {
+        let (tx, mut rx) = channel(2);
+
+        for i in 0..5 {
+            tx.send(i).unwrap();
+        }
+
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Lagged(3)));
+        assert_eq!(rx.try_recv(), Ok(3));
+        assert_eq!(rx.try_recv(), Ok(4));
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+
+        drop(tx);
+
+        assert_eq!(rx.recv(), Err(RecvError::Closed));
+    }
+}
@@ -0,0 +1,87 @@
+use std::{
+    fmt,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use super::{RecvError, RecvTimeoutError, TryRecvError, shared::Shared};
+use crate::select::{Selectable, Token};
+
+/// The receiving half of a broadcast channel
+///
+/// A clone starts reading at the same message as the original.
+pub struct Receiver<T: Clone> {
+    shared: Arc<Shared<T>>,
+
+    /// Number of the next message to read
+    next: u64,
+}
+
+impl<T: Clone> Receiver<T> {
+    pub(super) fn new(shared: Arc<Shared<T>>, next: u64) -> Receiver<T> {
+        Receiver { shared, next }
+    }
+
+    /// Receives the next message, waiting until it is sent
+    /// Fails once all the senders are gone and every message was read
+    pub fn recv(&mut self) -> Result<T, RecvError> {
+        match self.shared.recv(&mut self.next, None) {
+            Ok(t) => Ok(t),
+            Err(RecvTimeoutError::Lagged(n)) => Err(RecvError::Lagged(n)),
+            Err(_) => Err(RecvError::Closed),
+        }
+    }
+
+    /// Attempts to receive the next message without waiting
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        self.shared.try_recv(&mut self.next)
+    }
+
+    /// Receives the next message, waiting for at most `timeout`
+    pub fn recv_timeout(&mut self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        self.shared
+            .recv(&mut self.next, Some(Instant::now() + timeout))
+    }
+}
+
+/// The next message, as a `select!` branch
+impl<T: Clone> Selectable for &mut Receiver<T> {
+    type Output = Result<T, RecvError>;
+
+    fn try_select(&mut self) -> Option<Result<T, RecvError>> {
+        match self.shared.try_recv(&mut self.next) {
+            Ok(t) => Some(Ok(t)),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Closed) => Some(Err(RecvError::Closed)),
+            Err(TryRecvError::Lagged(n)) => Some(Err(RecvError::Lagged(n))),
+        }
+    }
+
+    fn watch(&mut self, token: &Token) {
+        self.shared.watch(self.next, token);
+    }
+
+    fn unwatch(&mut self, token: &Token) {
+        self.shared.unwatch(token);
+    }
+}
+
+impl<T: Clone> Clone for Receiver<T> {
+    fn clone(&self) -> Receiver<T> {
+        let next = self.shared.add_receiver(Some(self.next));
+
+        Receiver::new(self.shared.clone(), next)
+    }
+}
+
+impl<T: Clone> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.shared.drop_receiver();
+    }
+}
+
+impl<T: Clone> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver").finish_non_exhaustive()
+    }
+}
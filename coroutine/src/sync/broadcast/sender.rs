@@ -0,0 +1,52 @@
+use std::{fmt, sync::Arc};
+
+use super::{Receiver, SendError, shared::Shared};
+
+/// The sending half of a broadcast channel, it can be cloned to send from many places
+pub struct Sender<T: Clone> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T: Clone> Sender<T> {
+    pub(super) fn new(shared: Arc<Shared<T>>) -> Sender<T> {
+        Sender { shared }
+    }
+
+    /// Sends a message to all the receivers, it never waits
+    /// Returns the number of receivers, fails and gives the message back if there are none
+    pub fn send(&self, t: T) -> Result<usize, SendError<T>> {
+        self.shared.send(t)
+    }
+
+    /// Creates a receiver which gets the messages sent from now on
+    pub fn subscribe(&self) -> Receiver<T> {
+        let next = self.shared.add_receiver(None);
+
+        Receiver::new(self.shared.clone(), next)
+    }
+
+    /// Returns the number of live receivers
+    pub fn receiver_count(&self) -> usize {
+        self.shared.receiver_count()
+    }
+}
+
+impl<T: Clone> Clone for Sender<T> {
+    fn clone(&self) -> Sender<T> {
+        self.shared.add_sender();
+
+        Sender::new(self.shared.clone())
+    }
+}
+
+impl<T: Clone> Drop for Sender<T> {
+    fn drop(&mut self) {
+        self.shared.drop_sender();
+    }
+}
+
+impl<T: Clone> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,208 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex, MutexGuard},
+    time::Instant,
+};
+
+use super::{RecvTimeoutError, SendError, TryRecvError};
+use crate::{
+    park::ParkError,
+    select::Token,
+    sync::wait_node::{self, WaitNode},
+};
+
+/// Ring of the last messages, shared by the senders and the receivers of a broadcast channel
+///
+/// The messages are numbered in sending order, each receiver keeps the number of the next one it
+/// reads. A sent message wakes up all the waiting receivers.
+pub(super) struct Shared<T> {
+    state: Mutex<State<T>>,
+    cap: usize,
+}
+
+struct State<T> {
+    buffer: VecDeque<T>,
+
+    /// Number of the oldest message in the buffer
+    head: u64,
+    senders: usize,
+    receivers: usize,
+    waiters: VecDeque<Arc<WaitNode>>,
+}
+
+impl<T> State<T> {
+    /// Number of the next message to be sent
+    #[inline]
+    fn tail(&self) -> u64 {
+        self.head + self.buffer.len() as u64
+    }
+}
+
+impl<T: Clone> Shared<T> {
+    /// Creates the channel with one sender and one receiver
+    pub(super) fn new(cap: usize) -> Arc<Shared<T>> {
+        assert!(cap > 0, "channel capacity must be positive");
+
+        Arc::new(Shared {
+            state: Mutex::new(State {
+                buffer: VecDeque::with_capacity(cap),
+                head: 0,
+                senders: 1,
+                receivers: 1,
+                waiters: VecDeque::new(),
+            }),
+            cap,
+        })
+    }
+
+    pub(super) fn add_sender(&self) {
+        self.lock().senders += 1;
+    }
+
+    pub(super) fn drop_sender(&self) {
+        let mut state = self.lock();
+
+        state.senders -= 1;
+
+        if state.senders == 0 {
+            wake_all(&mut state.waiters);
+        }
+    }
+
+    /// Add a receiver starting at `next`, or at the next sent message
+    pub(super) fn add_receiver(&self, next: Option<u64>) -> u64 {
+        let mut state = self.lock();
+
+        state.receivers += 1;
+
+        next.unwrap_or_else(|| state.tail())
+    }
+
+    pub(super) fn drop_receiver(&self) {
+        self.lock().receivers -= 1;
+    }
+
+    pub(super) fn receiver_count(&self) -> usize {
+        self.lock().receivers
+    }
+
+    /// Returns the number of receivers the message was sent to
+    pub(super) fn send(&self, t: T) -> Result<usize, SendError<T>> {
+        let mut state = self.lock();
+
+        if state.receivers == 0 {
+            return Err(SendError(t));
+        }
+
+        if state.buffer.len() == self.cap {
+            state.buffer.pop_front();
+            state.head += 1;
+        }
+
+        state.buffer.push_back(t);
+
+        wake_all(&mut state.waiters);
+
+        Ok(state.receivers)
+    }
+
+    /// Read the message numbered `next`, moving `next` past it
+    pub(super) fn try_recv(&self, next: &mut u64) -> Result<T, TryRecvError> {
+        Self::read(&self.lock(), next)
+    }
+
+    /// Read the message numbered `next`, waiting until it is sent or the deadline passes
+    pub(super) fn recv(
+        &self,
+        next: &mut u64,
+        deadline: Option<Instant>,
+    ) -> Result<T, RecvTimeoutError> {
+        loop {
+            let node = {
+                let mut state = self.lock();
+
+                match Self::read(&state, next) {
+                    Ok(t) => return Ok(t),
+                    Err(TryRecvError::Closed) => return Err(RecvTimeoutError::Closed),
+                    Err(TryRecvError::Lagged(n)) => return Err(RecvTimeoutError::Lagged(n)),
+                    Err(TryRecvError::Empty) => {}
+                }
+
+                if deadline.is_some_and(|deadline| deadline <= Instant::now()) {
+                    return Err(RecvTimeoutError::Timeout);
+                }
+
+                let node = WaitNode::new();
+
+                state.waiters.push_back(node.clone());
+
+                node
+            };
+
+            let timeout =
+                deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+
+            if let Err(err) = node.park(timeout) {
+                self.lock()
+                    .waiters
+                    .retain(|waiter| !Arc::ptr_eq(waiter, &node));
+
+                if err == ParkError::Cancelled {
+                    wait_node::check_cancel();
+                }
+            }
+        }
+    }
+
+    /// Wake `token` once the message numbered `next` can be read, or the senders are gone
+    pub(super) fn watch(&self, next: u64, token: &Token) {
+        let mut state = self.lock();
+
+        if next < state.tail() || state.senders == 0 {
+            return token.wake();
+        }
+
+        state.waiters.push_back(token.node().clone());
+    }
+
+    pub(super) fn unwatch(&self, token: &Token) {
+        self.lock()
+            .waiters
+            .retain(|waiter| !Arc::ptr_eq(waiter, token.node()));
+    }
+
+    fn read(state: &State<T>, next: &mut u64) -> Result<T, TryRecvError> {
+        if *next < state.head {
+            let skipped = state.head - *next;
+
+            *next = state.head;
+
+            return Err(TryRecvError::Lagged(skipped));
+        }
+
+        if *next < state.tail() {
+            let t = state.buffer[(*next - state.head) as usize].clone();
+
+            *next += 1;
+
+            return Ok(t);
+        }
+
+        if state.senders == 0 {
+            Err(TryRecvError::Closed)
+        } else {
+            Err(TryRecvError::Empty)
+        }
+    }
+
+    #[inline]
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        self.state.lock().unwrap()
+    }
+}
+
+fn wake_all(waiters: &mut VecDeque<Arc<WaitNode>>) {
+    for waiter in waiters.drain(..) {
+        waiter.grant();
+    }
+}
@@ -0,0 +1,253 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        Arc, Mutex, MutexGuard,
+        mpsc::{RecvError, RecvTimeoutError, SendError, TryRecvError, TrySendError},
+    },
+    time::Instant,
+};
+
+use super::wait_node::{self, WaitNode};
+use crate::{park::ParkError, select::Token};
+
+/// Queue shared by the senders and the receivers of a channel
+///
+/// Waiting senders and receivers are queued in order. A wake up is only a hint to retry: a sent
+/// message wakes the first receiver, a received one the first sender when the channel is bounded.
+/// Dropping the last sender or the last receiver wakes up the whole opposite side, which then
+/// sees the disconnect.
+pub(crate) struct Channel<T> {
+    state: Mutex<State<T>>,
+
+    /// Maximum number of queued messages, none for an unbounded channel
+    cap: Option<usize>,
+}
+
+struct State<T> {
+    queue: VecDeque<T>,
+    senders: usize,
+    receivers: usize,
+    send_waiters: VecDeque<Arc<WaitNode>>,
+    recv_waiters: VecDeque<Arc<WaitNode>>,
+}
+
+impl<T> Channel<T> {
+    /// Creates a channel with one sender and one receiver
+    pub(crate) fn new(cap: Option<usize>) -> Arc<Channel<T>> {
+        assert!(cap != Some(0), "channel capacity must be positive");
+
+        Arc::new(Channel {
+            state: Mutex::new(State {
+                queue: VecDeque::new(),
+                senders: 1,
+                receivers: 1,
+                send_waiters: VecDeque::new(),
+                recv_waiters: VecDeque::new(),
+            }),
+            cap,
+        })
+    }
+
+    pub(crate) fn add_sender(&self) {
+        self.lock().senders += 1;
+    }
+
+    pub(crate) fn drop_sender(&self) {
+        let mut state = self.lock();
+
+        state.senders -= 1;
+
+        if state.senders == 0 {
+            wake_all(&mut state.recv_waiters);
+        }
+    }
+
+    pub(crate) fn add_receiver(&self) {
+        self.lock().receivers += 1;
+    }
+
+    pub(crate) fn drop_receiver(&self) {
+        let mut state = self.lock();
+
+        state.receivers -= 1;
+
+        if state.receivers == 0 {
+            wake_all(&mut state.send_waiters);
+        }
+    }
+
+    pub(crate) fn try_send(&self, t: T) -> Result<(), TrySendError<T>> {
+        let mut state = self.lock();
+
+        if state.receivers == 0 {
+            return Err(TrySendError::Disconnected(t));
+        }
+
+        if self.cap.is_some_and(|cap| state.queue.len() >= cap) {
+            return Err(TrySendError::Full(t));
+        }
+
+        state.queue.push_back(t);
+
+        wake_one(&mut state.recv_waiters);
+
+        Ok(())
+    }
+
+    /// Send a message, waiting for room in a bounded channel
+    pub(crate) fn send(&self, t: T) -> Result<(), SendError<T>> {
+        loop {
+            let node = {
+                let mut state = self.lock();
+
+                if state.receivers == 0 {
+                    return Err(SendError(t));
+                }
+
+                if !self.cap.is_some_and(|cap| state.queue.len() >= cap) {
+                    state.queue.push_back(t);
+
+                    wake_one(&mut state.recv_waiters);
+
+                    return Ok(());
+                }
+
+                let node = WaitNode::new();
+
+                state.send_waiters.push_back(node.clone());
+
+                node
+            };
+
+            self.wait(&node, None, |state| &mut state.send_waiters);
+        }
+    }
+
+    pub(crate) fn try_recv(&self) -> Result<T, TryRecvError> {
+        let mut state = self.lock();
+
+        match state.queue.pop_front() {
+            Some(t) => {
+                wake_one(&mut state.send_waiters);
+
+                Ok(t)
+            }
+            None if state.senders == 0 => Err(TryRecvError::Disconnected),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+
+    /// Receive a message, waiting until one is sent or the deadline passes
+    /// The queued messages are still received after all the senders are gone
+    pub(crate) fn recv(&self, deadline: Option<Instant>) -> Result<T, RecvTimeoutError> {
+        loop {
+            let node = {
+                let mut state = self.lock();
+
+                if let Some(t) = state.queue.pop_front() {
+                    wake_one(&mut state.send_waiters);
+
+                    return Ok(t);
+                }
+
+                if state.senders == 0 {
+                    return Err(RecvTimeoutError::Disconnected);
+                }
+
+                if deadline.is_some_and(|deadline| deadline <= Instant::now()) {
+                    return Err(RecvTimeoutError::Timeout);
+                }
+
+                let node = WaitNode::new();
+
+                state.recv_waiters.push_back(node.clone());
+
+                node
+            };
+
+            self.wait(&node, deadline, |state| &mut state.recv_waiters);
+        }
+    }
+
+    /// Attempt to receive for a `select!`, the disconnect completes the receive too
+    pub(crate) fn select_recv(&self) -> Option<Result<T, RecvError>> {
+        match self.try_recv() {
+            Ok(t) => Some(Ok(t)),
+            Err(TryRecvError::Disconnected) => Some(Err(RecvError)),
+            Err(TryRecvError::Empty) => None,
+        }
+    }
+
+    /// Wake `token` on the next message, right away if one is queued or the senders are gone
+    pub(crate) fn watch_recv(&self, token: &Token) {
+        let mut state = self.lock();
+
+        if !state.queue.is_empty() || state.senders == 0 {
+            return token.wake();
+        }
+
+        state.recv_waiters.push_back(token.node().clone());
+    }
+
+    /// Stop waking `token`, a wake up it got from the channel goes to the next receiver
+    pub(crate) fn unwatch_recv(&self, token: &Token) {
+        let mut state = self.lock();
+        let len = state.recv_waiters.len();
+
+        state
+            .recv_waiters
+            .retain(|waiter| !Arc::ptr_eq(waiter, token.node()));
+
+        if state.recv_waiters.len() == len && token.node().is_granted() {
+            wake_one(&mut state.recv_waiters);
+        }
+    }
+
+    /// Park until woken up or the deadline passes, the caller retries then
+    /// A cancelled coroutine leaves the queue and unwinds, passing on a wake up it got meanwhile
+    fn wait(
+        &self,
+        node: &Arc<WaitNode>,
+        deadline: Option<Instant>,
+        waiters: fn(&mut State<T>) -> &mut VecDeque<Arc<WaitNode>>,
+    ) {
+        let timeout = deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+
+        let Err(err) = node.park(timeout) else {
+            return;
+        };
+
+        {
+            let mut state = self.lock();
+            let waiters = waiters(&mut state);
+
+            if !node.is_granted() {
+                waiters.retain(|waiter| !Arc::ptr_eq(waiter, node));
+            } else if err == ParkError::Cancelled {
+                wake_one(waiters);
+            }
+        }
+
+        if err == ParkError::Cancelled {
+            wait_node::check_cancel();
+        }
+    }
+
+    #[inline]
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        self.state.lock().unwrap()
+    }
+}
+
+#[inline]
+fn wake_one(waiters: &mut VecDeque<Arc<WaitNode>>) {
+    if let Some(waiter) = waiters.pop_front() {
+        waiter.grant();
+    }
+}
+
+fn wake_all(waiters: &mut VecDeque<Arc<WaitNode>>) {
+    for waiter in waiters.drain(..) {
+        waiter.grant();
+    }
+}
@@ -0,0 +1,238 @@
+use std::{
+    collections::VecDeque,
+    fmt,
+    sync::{self, Arc, LockResult, PoisonError},
+    time::{Duration, Instant},
+};
+
+use super::{
+    MutexGuard,
+    wait_node::{self, WaitNode},
+};
+use crate::park::ParkError;
+
+/// A condition variable which suspends the current coroutine while it waits
+///
+/// Outside of coroutines the current thread is blocked instead. The waiters are notified in
+/// order. Like the std condition variable, a wait may return without a notification.
+pub struct Condvar {
+    waiters: sync::Mutex<VecDeque<Arc<WaitNode>>>,
+}
+
+/// Tells whether a timed wait on a `Condvar` returned because of the timeout
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct WaitTimeoutResult(bool);
+
+impl WaitTimeoutResult {
+    /// Returns true if the wait timed out without a notification
+    pub fn timed_out(&self) -> bool {
+        self.0
+    }
+}
+
+impl Condvar {
+    /// Creates a new condition variable
+    pub const fn new() -> Condvar {
+        Condvar {
+            waiters: sync::Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Unlocks the mutex and waits for a notification, the mutex is locked again on return
+    pub fn wait<'a, T>(&self, guard: MutexGuard<'a, T>) -> LockResult<MutexGuard<'a, T>> {
+        match self.wait_inner(guard, None) {
+            Ok((guard, _)) => Ok(guard),
+            Err(err) => Err(PoisonError::new(err.into_inner().0)),
+        }
+    }
+
+    /// Waits until `condition` returns false
+    pub fn wait_while<'a, T, F>(
+        &self,
+        mut guard: MutexGuard<'a, T>,
+        mut condition: F,
+    ) -> LockResult<MutexGuard<'a, T>>
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        while condition(&mut *guard) {
+            guard = self.wait(guard)?;
+        }
+
+        Ok(guard)
+    }
+
+    /// Waits for a notification for at most `dur`
+    pub fn wait_timeout<'a, T>(
+        &self,
+        guard: MutexGuard<'a, T>,
+        dur: Duration,
+    ) -> LockResult<(MutexGuard<'a, T>, WaitTimeoutResult)> {
+        self.wait_inner(guard, Some(dur))
+    }
+
+    /// Waits until `condition` returns false, for at most `dur`
+    /// The result tells whether the condition still held when the timeout elapsed
+    pub fn wait_timeout_while<'a, T, F>(
+        &self,
+        mut guard: MutexGuard<'a, T>,
+        dur: Duration,
+        mut condition: F,
+    ) -> LockResult<(MutexGuard<'a, T>, WaitTimeoutResult)>
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        let deadline = Instant::now() + dur;
+
+        loop {
+            if !condition(&mut *guard) {
+                return Ok((guard, WaitTimeoutResult(false)));
+            }
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+
+            if remaining.is_zero() {
+                return Ok((guard, WaitTimeoutResult(true)));
+            }
+
+            guard = self.wait_timeout(guard, remaining)?.0;
+        }
+    }
+
+    /// Wakes up the first waiter
+    pub fn notify_one(&self) {
+        if let Some(waiter) = self.waiters.lock().unwrap().pop_front() {
+            waiter.grant();
+        }
+    }
+
+    /// Wakes up all the waiters
+    pub fn notify_all(&self) {
+        let waiters = std::mem::take(&mut *self.waiters.lock().unwrap());
+
+        for waiter in waiters {
+            waiter.grant();
+        }
+    }
+
+    fn wait_inner<'a, T>(
+        &self,
+        guard: MutexGuard<'a, T>,
+        timeout: Option<Duration>,
+    ) -> LockResult<(MutexGuard<'a, T>, WaitTimeoutResult)> {
+        let mutex = guard.mutex();
+        let node = WaitNode::new();
+
+        // Queue before unlocking, so that a notification sent right after the unlock is not lost
+        self.waiters.lock().unwrap().push_back(node.clone());
+
+        drop(guard);
+
+        let timed_out = match node.park(timeout) {
+            Ok(()) => false,
+            Err(err) => {
+                let notified = self.abandon(&node);
+
+                if err == ParkError::Cancelled {
+                    // A notification for the cancelled coroutine goes to the next waiter
+                    if notified {
+                        self.notify_one();
+                    }
+
+                    wait_node::check_cancel();
+                }
+
+                !notified
+            }
+        };
+
+        match mutex.lock() {
+            Ok(guard) => Ok((guard, WaitTimeoutResult(timed_out))),
+            Err(err) => Err(PoisonError::new((
+                err.into_inner(),
+                WaitTimeoutResult(timed_out),
+            ))),
+        }
+    }
+
+    /// Leave the queue after a failed wait, returns true if notified meanwhile
+    fn abandon(&self, node: &Arc<WaitNode>) -> bool {
+        let mut waiters = self.waiters.lock().unwrap();
+
+        if node.is_granted() {
+            return true;
+        }
+
+        waiters.retain(|waiter| !Arc::ptr_eq(waiter, node));
+
+        false
+    }
+}
+
+impl Default for Condvar {
+    fn default() -> Condvar {
+        Condvar::new()
+    }
+}
+
+impl fmt::Debug for Condvar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Condvar").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{spawn, sync::Mutex};
+
+    #[test]
+    fn notify_wakes_waiting_coroutine() {
+        let pair = Arc::new((Mutex::new(false), Condvar::new()));
+
+        let waiter = {
+            let pair = pair.clone();
+
+            unsafe {
+                spawn(move || {
+                    let (lock, cvar) = &*pair;
+
+                    *cvar
+                        .wait_while(lock.lock().unwrap(), |ready| !*ready)
+                        .unwrap()
+                })
+            }
+        };
+
+        let (lock, cvar) = &*pair;
+
+        *lock.lock().unwrap() = true;
+        cvar.notify_one();
+
+        assert!(waiter.join().unwrap());
+    }
+
+    #[test]
+    fn wait_timeout_elapses_without_notification() {
+        let handle = unsafe {
+            spawn(|| {
+                let lock = Mutex::new(());
+                let cvar = Condvar::new();
+
+                let start = Instant::now();
+                let (_guard, result) = cvar
+                    .wait_timeout(lock.lock().unwrap(), Duration::from_millis(20))
+                    .unwrap();
+
+                (result.timed_out(), start.elapsed())
+            })
+        };
+
+        let (timed_out, elapsed) = handle.join().unwrap();
+
+        assert!(timed_out);
+        assert!(elapsed >= Duration::from_millis(20));
+    }
+}
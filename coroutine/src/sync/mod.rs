@@ -1,20 +1,37 @@
 use std::{mem::MaybeUninit, ptr, sync::atomic::Ordering};
 
 mod atomic_cell;
+mod atomic_duration;
 mod atomic_macro;
 mod atomic_option;
 mod atomic_unit;
 mod backoff;
-pub mod blocker;
+pub(crate) mod blocker;
+pub mod broadcast;
+mod cache_padded;
+mod channel;
+mod condvar;
+pub mod mpmc;
+pub mod mpsc;
+mod mutex;
+pub mod oneshot;
 mod parker;
+mod poison;
+mod rw_lock;
 mod seq_lock;
-mod thread_park;
+pub(crate) mod thread_park;
+pub(crate) mod wait_node;
 
 pub(crate) use self::atomic_macro::atomic;
-pub use atomic_cell::AtomicCell;
-pub use atomic_option::AtomicOption;
-pub use atomic_unit::AtomicUnit;
-pub use backoff::Backoff;
+pub(crate) use atomic_cell::AtomicCell;
+pub(crate) use atomic_duration::AtomicDuration;
+pub(crate) use atomic_option::AtomicOption;
+pub(crate) use atomic_unit::AtomicUnit;
+pub(crate) use backoff::Backoff;
+pub(crate) use cache_padded::CachePadded;
+pub use condvar::{Condvar, WaitTimeoutResult};
+pub use mutex::{Mutex, MutexGuard};
+pub use rw_lock::{RwLock, RwLockReadGuard, RwLockWriteGuard};
 use seq_lock::SeqLock;
 
 #[allow(unused_imports)]
@@ -43,6 +60,7 @@ fn lock(addr: usize) -> &'static SeqLock {
     // The number of locks is a prime number because we want to make sure `addr % LEN` gets
     // dispersed across all locks
     const LEN: usize = 67;
+    #[allow(clippy::declare_interior_mutable_const)]
     const L: CachePadded<SeqLock> = CachePadded::new(SeqLock::new());
 
     static LOCKS: [CachePadded<SeqLock>; LEN] = [L; LEN];
@@ -105,6 +123,7 @@ where
 /// Atomically writes `value` to `dst`
 /// This operation uses the `Release` ordering. If possible, an atomic instruction is used or a
 /// global lock otherwise
+#[allow(clippy::unit_arg)]
 pub(crate) unsafe fn atomic_store<T>(dst: *mut T, value: T) {
     atomic! {
         T, a,
@@ -121,3 +140,27 @@ pub(crate) unsafe fn atomic_store<T>(dst: *mut T, value: T) {
         }
     }
 }
+
+/// Atomically swaps data at `dst` with `value`
+/// This operation uses the `AcqRel` ordering. If possible, an atomic instruction is used or a
+/// global lock otherwise
+#[allow(clippy::unit_arg)]
+pub(crate) unsafe fn atomic_swap<T>(dst: *mut T, value: T) -> T {
+    atomic! {
+        T, a,
+        {
+            a = unsafe { &*(dst as *const _ as *const _) };
+
+            let res = unsafe { core::mem::transmute_copy(&a.swap(core::mem::transmute_copy(&value), Ordering::AcqRel)) };
+
+            core::mem::forget(value);
+
+            res
+        },
+        {
+            let _guard = lock(dst as usize).write();
+
+            unsafe { ptr::replace(dst, value) }
+        }
+    }
+}
@@ -0,0 +1,67 @@
+//! Multi-producer, multi-consumer channels which suspend the running coroutine while they wait
+//!
+//! Both halves can be cloned, each message is received once by one of the receivers. Outside of
+//! coroutines the calling thread is blocked instead.
+
+mod receiver;
+mod sender;
+
+pub use receiver::Receiver;
+pub use sender::Sender;
+pub use std::sync::mpsc::{RecvError, RecvTimeoutError, SendError, TryRecvError, TrySendError};
+
+use super::channel::Channel;
+
+/// Creates an unbounded channel, sending never waits
+pub fn unbounded<T>() -> (Sender<T>, Receiver<T>) {
+    let channel = Channel::new(None);
+
+    (Sender::new(channel.clone()), Receiver::new(channel))
+}
+
+/// Creates a bounded channel holding up to `cap` messages, sending waits while it's full
+///
+/// # Panics
+///
+/// Panics if `cap` is zero.
+pub fn bounded<T>(cap: usize) -> (Sender<T>, Receiver<T>) {
+    let channel = Channel::new(Some(cap));
+
+    (Sender::new(channel.clone()), Receiver::new(channel))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spawn;
+
+    #[test]
+    fn every_message_is_received_once() {
+        let (tx, rx) = bounded(4);
+
+        let consumers: Vec<_> = (0..8)
+            .map(|_| {
+                let rx = rx.clone();
+
+                unsafe { spawn(move || rx.iter().collect::<Vec<usize>>()) }
+            })
+            .collect();
+
+        drop(rx);
+
+        for i in 0..1000 {
+            tx.send(i).unwrap();
+        }
+
+        drop(tx);
+
+        let mut received: Vec<_> = consumers
+            .into_iter()
+            .flat_map(|consumer| consumer.join().unwrap())
+            .collect();
+
+        received.sort_unstable();
+
+        assert_eq!(received, (0..1000).collect::<Vec<_>>());
+    }
+}
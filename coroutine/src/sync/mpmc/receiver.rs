@@ -0,0 +1,85 @@
+use std::{
+    fmt,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use super::{RecvError, RecvTimeoutError, TryRecvError};
+use crate::{
+    select::{Selectable, Token},
+    sync::channel::Channel,
+};
+
+/// The receiving half of a channel
+pub struct Receiver<T> {
+    channel: Arc<Channel<T>>,
+}
+
+impl<T> Receiver<T> {
+    pub(super) fn new(channel: Arc<Channel<T>>) -> Receiver<T> {
+        Receiver { channel }
+    }
+
+    /// Receives a message, waiting until one is sent
+    /// Fails once the channel is empty and all the senders are gone
+    pub fn recv(&self) -> Result<T, RecvError> {
+        self.channel.recv(None).map_err(|_| RecvError)
+    }
+
+    /// Attempts to receive a message without waiting
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        self.channel.try_recv()
+    }
+
+    /// Receives a message, waiting for at most `timeout`
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        self.channel.recv(Some(Instant::now() + timeout))
+    }
+
+    /// Returns an iterator over the messages, it ends when all the senders are gone
+    pub fn iter(&self) -> impl Iterator<Item = T> + '_ {
+        std::iter::from_fn(move || self.recv().ok())
+    }
+
+    /// Returns an iterator over the messages already queued
+    pub fn try_iter(&self) -> impl Iterator<Item = T> + '_ {
+        std::iter::from_fn(move || self.try_recv().ok())
+    }
+}
+
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Receiver<T> {
+        self.channel.add_receiver();
+
+        Receiver::new(self.channel.clone())
+    }
+}
+
+/// The next message, as a `select!` branch
+impl<T> Selectable for &Receiver<T> {
+    type Output = Result<T, RecvError>;
+
+    fn try_select(&mut self) -> Option<Result<T, RecvError>> {
+        self.channel.select_recv()
+    }
+
+    fn watch(&mut self, token: &Token) {
+        self.channel.watch_recv(token);
+    }
+
+    fn unwatch(&mut self, token: &Token) {
+        self.channel.unwatch_recv(token);
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.channel.drop_receiver();
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver").finish_non_exhaustive()
+    }
+}
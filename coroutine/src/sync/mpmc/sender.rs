@@ -0,0 +1,46 @@
+use std::{fmt, sync::Arc};
+
+use super::{SendError, TrySendError};
+use crate::sync::channel::Channel;
+
+/// The sending half of a channel
+pub struct Sender<T> {
+    channel: Arc<Channel<T>>,
+}
+
+impl<T> Sender<T> {
+    pub(super) fn new(channel: Arc<Channel<T>>) -> Sender<T> {
+        Sender { channel }
+    }
+
+    /// Sends a message, waiting for room while a bounded channel is full
+    /// Fails and gives the message back if all the receivers are gone
+    pub fn send(&self, t: T) -> Result<(), SendError<T>> {
+        self.channel.send(t)
+    }
+
+    /// Attempts to send a message without waiting
+    pub fn try_send(&self, t: T) -> Result<(), TrySendError<T>> {
+        self.channel.try_send(t)
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Sender<T> {
+        self.channel.add_sender();
+
+        Sender::new(self.channel.clone())
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        self.channel.drop_sender();
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender").finish_non_exhaustive()
+    }
+}
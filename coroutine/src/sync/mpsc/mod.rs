@@ -0,0 +1,88 @@
+//! Multi-producer, single-consumer channels which suspend the running coroutine while they wait
+//!
+//! Outside of coroutines the calling thread is blocked instead. The API follows `std::sync::mpsc`
+//! and reuses its error types.
+
+mod receiver;
+mod sender;
+mod sync_sender;
+
+pub use receiver::Receiver;
+pub use sender::Sender;
+pub use std::sync::mpsc::{RecvError, RecvTimeoutError, SendError, TryRecvError, TrySendError};
+pub use sync_sender::SyncSender;
+
+use super::channel::Channel;
+
+/// Creates an unbounded channel, sending never waits
+pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
+    let channel = Channel::new(None);
+
+    (Sender::new(channel.clone()), Receiver::new(channel))
+}
+
+/// Creates a bounded channel holding up to `bound` messages, sending waits while it's full
+///
+/// # Panics
+///
+/// Panics if `bound` is zero.
+pub fn sync_channel<T>(bound: usize) -> (SyncSender<T>, Receiver<T>) {
+    let channel = Channel::new(Some(bound));
+
+    (SyncSender::new(channel.clone()), Receiver::new(channel))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::spawn;
+
+    #[test]
+    fn bounded_channel_suspends_senders_until_received() {
+        let (tx, rx) = sync_channel(2);
+
+        let producers: Vec<_> = (0..10)
+            .map(|i| {
+                let tx = tx.clone();
+
+                unsafe { spawn(move || (0..100).for_each(|j| tx.send(i * 100 + j).unwrap())) }
+            })
+            .collect();
+
+        drop(tx);
+
+        let consumer = unsafe { spawn(move || rx.iter().sum::<usize>()) };
+
+        for producer in producers {
+            producer.join().unwrap();
+        }
+
+        assert_eq!(consumer.join().unwrap(), (0..1000).sum());
+    }
+
+    #[test]
+    fn receiver_sees_timeout_then_disconnect() {
+        let (tx, rx) = channel::<()>();
+
+        let handle = unsafe {
+            spawn(move || {
+                let timeout = rx.recv_timeout(Duration::from_millis(10));
+
+                drop(tx);
+
+                (timeout, rx.recv(), rx.try_recv())
+            })
+        };
+
+        assert_eq!(
+            handle.join().unwrap(),
+            (
+                Err(RecvTimeoutError::Timeout),
+                Err(RecvError),
+                Err(TryRecvError::Disconnected)
+            )
+        );
+    }
+}
@@ -0,0 +1,41 @@
+use std::{fmt, sync::Arc};
+
+use super::SendError;
+use crate::sync::channel::Channel;
+
+/// The sending half of an unbounded channel, it can be cloned to send from many places
+pub struct Sender<T> {
+    channel: Arc<Channel<T>>,
+}
+
+impl<T> Sender<T> {
+    pub(super) fn new(channel: Arc<Channel<T>>) -> Sender<T> {
+        Sender { channel }
+    }
+
+    /// Sends a message without waiting
+    /// Fails and gives the message back if the receiver is gone
+    pub fn send(&self, t: T) -> Result<(), SendError<T>> {
+        self.channel.send(t)
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Sender<T> {
+        self.channel.add_sender();
+
+        Sender::new(self.channel.clone())
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        self.channel.drop_sender();
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,46 @@
+use std::{fmt, sync::Arc};
+
+use super::{SendError, TrySendError};
+use crate::sync::channel::Channel;
+
+/// The sending half of a bounded channel, it can be cloned to send from many places
+pub struct SyncSender<T> {
+    channel: Arc<Channel<T>>,
+}
+
+impl<T> SyncSender<T> {
+    pub(super) fn new(channel: Arc<Channel<T>>) -> SyncSender<T> {
+        SyncSender { channel }
+    }
+
+    /// Sends a message, waiting for room while the channel is full
+    /// Fails and gives the message back if the receiver is gone
+    pub fn send(&self, t: T) -> Result<(), SendError<T>> {
+        self.channel.send(t)
+    }
+
+    /// Attempts to send a message without waiting
+    pub fn try_send(&self, t: T) -> Result<(), TrySendError<T>> {
+        self.channel.try_send(t)
+    }
+}
+
+impl<T> Clone for SyncSender<T> {
+    fn clone(&self) -> SyncSender<T> {
+        self.channel.add_sender();
+
+        SyncSender::new(self.channel.clone())
+    }
+}
+
+impl<T> Drop for SyncSender<T> {
+    fn drop(&mut self) {
+        self.channel.drop_sender();
+    }
+}
+
+impl<T> fmt::Debug for SyncSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SyncSender").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,310 @@
+use std::{
+    cell::UnsafeCell,
+    collections::VecDeque,
+    fmt,
+    ops::{Deref, DerefMut},
+    sync::{self, Arc, LockResult, PoisonError, TryLockError, TryLockResult},
+};
+
+use super::{
+    poison,
+    wait_node::{self, WaitNode},
+};
+
+/// A mutual exclusion lock which suspends the current coroutine when it is contended
+///
+/// Outside of coroutines the current thread is blocked instead. The waiters are served in order:
+/// unlocking hands the lock over to the first waiter, a new comer can't take it before them. Like
+/// the std mutex, the lock is poisoned when a holder panics.
+///
+/// A coroutine cancelled while it waits leaves the queue and unwinds without taking the lock.
+pub struct Mutex<T: ?Sized> {
+    state: sync::Mutex<LockState>,
+    poison: poison::Flag,
+    data: UnsafeCell<T>,
+}
+
+struct LockState {
+    locked: bool,
+
+    /// Waiters in arrival order, only non-empty while the lock is held
+    waiters: VecDeque<Arc<WaitNode>>,
+}
+
+unsafe impl<T: ?Sized + Send> Send for Mutex<T> {}
+unsafe impl<T: ?Sized + Send> Sync for Mutex<T> {}
+
+/// Access to the data of a locked `Mutex`, the lock is released when the guard is dropped
+#[must_use = "if unused the Mutex will immediately unlock"]
+pub struct MutexGuard<'a, T: ?Sized + 'a> {
+    mutex: &'a Mutex<T>,
+    poison: poison::Guard,
+}
+
+unsafe impl<T: ?Sized + Sync> Sync for MutexGuard<'_, T> {}
+
+impl<T> Mutex<T> {
+    /// Creates a new mutex in an unlocked state
+    pub const fn new(t: T) -> Mutex<T> {
+        Mutex {
+            state: sync::Mutex::new(LockState {
+                locked: false,
+                waiters: VecDeque::new(),
+            }),
+            poison: poison::Flag::new(),
+            data: UnsafeCell::new(t),
+        }
+    }
+
+    /// Consumes this mutex, returning the underlying data
+    pub fn into_inner(self) -> LockResult<T> {
+        let poisoned = self.poison.get();
+        let data = self.data.into_inner();
+
+        if poisoned {
+            Err(PoisonError::new(data))
+        } else {
+            Ok(data)
+        }
+    }
+}
+
+impl<T: ?Sized> Mutex<T> {
+    /// Acquires the mutex, suspending the current coroutine until it is able to do so
+    pub fn lock(&self) -> LockResult<MutexGuard<'_, T>> {
+        self.acquire();
+
+        MutexGuard::new(self)
+    }
+
+    /// Attempts to acquire the mutex without waiting
+    pub fn try_lock(&self) -> TryLockResult<MutexGuard<'_, T>> {
+        {
+            let mut state = self.state.lock().unwrap();
+
+            if state.locked {
+                return Err(TryLockError::WouldBlock);
+            }
+
+            state.locked = true;
+        }
+
+        Ok(MutexGuard::new(self)?)
+    }
+
+    /// Determines whether the mutex is poisoned
+    pub fn is_poisoned(&self) -> bool {
+        self.poison.get()
+    }
+
+    /// Clear the poisoned state of the mutex
+    pub fn clear_poison(&self) {
+        self.poison.clear();
+    }
+
+    /// Returns a mutable reference to the underlying data, no locking is needed
+    pub fn get_mut(&mut self) -> LockResult<&mut T> {
+        let poisoned = self.poison.get();
+        let data = self.data.get_mut();
+
+        if poisoned {
+            Err(PoisonError::new(data))
+        } else {
+            Ok(data)
+        }
+    }
+
+    fn acquire(&self) {
+        loop {
+            let node = {
+                let mut state = self.state.lock().unwrap();
+
+                if !state.locked {
+                    state.locked = true;
+
+                    return;
+                }
+
+                let node = WaitNode::new();
+
+                state.waiters.push_back(node.clone());
+
+                node
+            };
+
+            if node.park(None).is_ok() {
+                return;
+            }
+
+            self.abandon(&node);
+
+            wait_node::check_cancel();
+        }
+    }
+
+    /// Leave the queue after a failed wait, passing the lock on if it was granted meanwhile
+    fn abandon(&self, node: &Arc<WaitNode>) {
+        let mut state = self.state.lock().unwrap();
+
+        if node.is_granted() {
+            drop(state);
+
+            return self.release();
+        }
+
+        state.waiters.retain(|waiter| !Arc::ptr_eq(waiter, node));
+    }
+
+    /// Hand the lock over to the first waiter, or unlock it
+    fn release(&self) {
+        let mut state = self.state.lock().unwrap();
+
+        match state.waiters.pop_front() {
+            Some(waiter) => waiter.grant(),
+            None => state.locked = false,
+        }
+    }
+}
+
+impl<T: Default> Default for Mutex<T> {
+    fn default() -> Mutex<T> {
+        Mutex::new(T::default())
+    }
+}
+
+impl<T> From<T> for Mutex<T> {
+    fn from(t: T) -> Mutex<T> {
+        Mutex::new(t)
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for Mutex<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_struct("Mutex");
+
+        match self.try_lock() {
+            Ok(guard) => d.field("data", &&*guard),
+            Err(TryLockError::Poisoned(err)) => d.field("data", &&**err.get_ref()),
+            Err(TryLockError::WouldBlock) => d.field("data", &format_args!("<locked>")),
+        };
+
+        d.field("poisoned", &self.poison.get());
+        d.finish_non_exhaustive()
+    }
+}
+
+impl<'a, T: ?Sized> MutexGuard<'a, T> {
+    fn new(mutex: &'a Mutex<T>) -> LockResult<MutexGuard<'a, T>> {
+        let guard = MutexGuard {
+            mutex,
+            poison: mutex.poison.guard(),
+        };
+
+        if mutex.poison.get() {
+            Err(PoisonError::new(guard))
+        } else {
+            Ok(guard)
+        }
+    }
+
+    /// The mutex the guard locks, used by `Condvar` to relock it
+    pub(crate) fn mutex(&self) -> &'a Mutex<T> {
+        self.mutex
+    }
+}
+
+impl<T: ?Sized> Deref for MutexGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.mutex.data.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for MutexGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.mutex.data.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for MutexGuard<'_, T> {
+    #[inline]
+    fn drop(&mut self) {
+        self.mutex.poison.done(&self.poison);
+        self.mutex.release();
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for MutexGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized + fmt::Display> fmt::Display for MutexGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{panic, sync::Arc, time::Duration};
+
+    use super::*;
+    use crate::{sleep, spawn};
+
+    #[test]
+    fn contended_lock_suspends_coroutines() {
+        let mutex = Arc::new(Mutex::new(0));
+
+        let handles: Vec<_> = (0..50)
+            .map(|_| {
+                let mutex = mutex.clone();
+
+                unsafe {
+                    spawn(move || {
+                        for _ in 0..20 {
+                            let mut guard = mutex.lock().unwrap();
+                            let value = *guard;
+
+                            // Hold the lock across a suspension
+                            sleep(Duration::from_micros(10));
+
+                            *guard = value + 1;
+                        }
+                    })
+                }
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(*mutex.lock().unwrap(), 1000);
+    }
+
+    #[test]
+    fn panicking_holder_poisons() {
+        let mutex = Arc::new(Mutex::new(1));
+
+        let result = {
+            let mutex = mutex.clone();
+
+            panic::catch_unwind(panic::AssertUnwindSafe(move || {
+                let _guard = mutex.lock().unwrap();
+
+                panic!("poison");
+            }))
+        };
+
+        assert!(result.is_err());
+        assert!(mutex.is_poisoned());
+        assert!(matches!(mutex.try_lock(), Err(TryLockError::Poisoned(_))));
+
+        mutex.clear_poison();
+
+        assert_eq!(*mutex.try_lock().unwrap(), 1);
+    }
+}
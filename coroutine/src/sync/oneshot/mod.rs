@@ -0,0 +1,48 @@
+//! Channels which carry a single message, typically a reply to a request
+//!
+//! Waiting for the message suspends the running coroutine, or blocks the calling thread outside of
+//! coroutines.
+
+mod receiver;
+mod sender;
+
+pub use receiver::Receiver;
+pub use sender::Sender;
+pub use std::sync::mpsc::{RecvError, RecvTimeoutError, TryRecvError};
+
+use super::channel::Channel;
+
+/// Creates a channel for one message
+pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
+    let channel = Channel::new(Some(1));
+
+    (Sender::new(channel.clone()), Receiver::new(channel))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spawn;
+
+    #[test]
+    fn reply_reaches_waiting_coroutine() {
+        let (tx, rx) = channel();
+
+        let handle = unsafe { spawn(move || rx.recv()) };
+
+        tx.send(42).unwrap();
+
+        assert_eq!(handle.join().unwrap(), Ok(42));
+    }
+
+    #[test]
+    fn dropped_sender_disconnects() {
+        let (tx, rx) = channel::<()>();
+
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+
+        drop(tx);
+
+        assert_eq!(rx.recv(), Err(RecvError));
+    }
+}
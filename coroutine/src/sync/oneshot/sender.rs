@@ -0,0 +1,32 @@
+use std::{fmt, sync::Arc};
+
+use crate::sync::channel::Channel;
+
+/// The sending half of a oneshot channel
+pub struct Sender<T> {
+    channel: Arc<Channel<T>>,
+}
+
+impl<T> Sender<T> {
+    pub(super) fn new(channel: Arc<Channel<T>>) -> Sender<T> {
+        Sender { channel }
+    }
+
+    /// Sends the message, it never waits
+    /// Fails and gives the message back if the receiver is gone
+    pub fn send(self, t: T) -> Result<(), T> {
+        self.channel.send(t).map_err(|err| err.0)
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        self.channel.drop_sender();
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender").finish_non_exhaustive()
+    }
+}
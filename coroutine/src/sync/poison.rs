@@ -0,0 +1,47 @@
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+};
+
+/// Poison flag of a lock, set when a holder panics
+pub(crate) struct Flag {
+    failed: AtomicBool,
+}
+
+/// Remembers whether the holder was already panicking when it took the lock
+pub(crate) struct Guard {
+    panicking: bool,
+}
+
+impl Flag {
+    pub(crate) const fn new() -> Flag {
+        Flag {
+            failed: AtomicBool::new(false),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn guard(&self) -> Guard {
+        Guard {
+            panicking: thread::panicking(),
+        }
+    }
+
+    /// Poison the lock if the holder started panicking while it held the lock
+    #[inline]
+    pub(crate) fn done(&self, guard: &Guard) {
+        if !guard.panicking && thread::panicking() {
+            self.failed.store(true, Ordering::Relaxed);
+        }
+    }
+
+    #[inline]
+    pub(crate) fn get(&self) -> bool {
+        self.failed.load(Ordering::Relaxed)
+    }
+
+    #[inline]
+    pub(crate) fn clear(&self) {
+        self.failed.store(false, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,411 @@
+use std::{
+    cell::UnsafeCell,
+    collections::VecDeque,
+    fmt,
+    ops::{Deref, DerefMut},
+    sync::{self, Arc, LockResult, PoisonError, TryLockError, TryLockResult},
+};
+
+use super::{
+    poison,
+    wait_node::{self, WaitNode},
+};
+
+/// A reader-writer lock which suspends the current coroutine when it is contended
+///
+/// Outside of coroutines the current thread is blocked instead. The lock is fair to the writers:
+/// once a writer waits, new readers queue up behind it instead of sharing the lock with the
+/// current readers. Unlocking serves the queue in order, either the first writer or all the
+/// readers up to the next writer. Like the std lock, it's poisoned when a writer panics.
+pub struct RwLock<T: ?Sized> {
+    state: sync::Mutex<LockState>,
+    poison: poison::Flag,
+    data: UnsafeCell<T>,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+enum Access {
+    Read,
+    Write,
+}
+
+struct LockState {
+    /// Number of readers holding the lock
+    readers: usize,
+    writer: bool,
+
+    /// Waiters in arrival order, only non-empty while the lock is held
+    waiters: VecDeque<(Arc<WaitNode>, Access)>,
+}
+
+unsafe impl<T: ?Sized + Send> Send for RwLock<T> {}
+unsafe impl<T: ?Sized + Send + Sync> Sync for RwLock<T> {}
+
+/// Shared access to the data of a `RwLock`, the lock is released when the guard is dropped
+#[must_use = "if unused the RwLock will immediately unlock"]
+pub struct RwLockReadGuard<'a, T: ?Sized + 'a> {
+    lock: &'a RwLock<T>,
+}
+
+unsafe impl<T: ?Sized + Sync> Sync for RwLockReadGuard<'_, T> {}
+
+/// Exclusive access to the data of a `RwLock`, the lock is released when the guard is dropped
+#[must_use = "if unused the RwLock will immediately unlock"]
+pub struct RwLockWriteGuard<'a, T: ?Sized + 'a> {
+    lock: &'a RwLock<T>,
+    poison: poison::Guard,
+}
+
+unsafe impl<T: ?Sized + Sync> Sync for RwLockWriteGuard<'_, T> {}
+
+impl LockState {
+    fn can_read(&self) -> bool {
+        !self.writer && self.waiters.is_empty()
+    }
+
+    fn can_write(&self) -> bool {
+        !self.writer && self.readers == 0
+    }
+
+    fn take(&mut self, access: Access) {
+        match access {
+            Access::Read => self.readers += 1,
+            Access::Write => self.writer = true,
+        }
+    }
+
+    /// Hand the free lock over to the first writer, or to the readers queued before the next one
+    fn grant_next(&mut self) {
+        while let Some((_, access)) = self.waiters.front() {
+            if *access == Access::Write && self.readers > 0 {
+                return;
+            }
+
+            let (node, access) = self.waiters.pop_front().unwrap();
+
+            self.take(access);
+            node.grant();
+
+            if access == Access::Write {
+                return;
+            }
+        }
+    }
+}
+
+impl<T> RwLock<T> {
+    /// Creates a new reader-writer lock in an unlocked state
+    pub const fn new(t: T) -> RwLock<T> {
+        RwLock {
+            state: sync::Mutex::new(LockState {
+                readers: 0,
+                writer: false,
+                waiters: VecDeque::new(),
+            }),
+            poison: poison::Flag::new(),
+            data: UnsafeCell::new(t),
+        }
+    }
+
+    /// Consumes this lock, returning the underlying data
+    pub fn into_inner(self) -> LockResult<T> {
+        let poisoned = self.poison.get();
+        let data = self.data.into_inner();
+
+        if poisoned {
+            Err(PoisonError::new(data))
+        } else {
+            Ok(data)
+        }
+    }
+}
+
+impl<T: ?Sized> RwLock<T> {
+    /// Locks with shared read access, suspending the current coroutine until it can be acquired
+    pub fn read(&self) -> LockResult<RwLockReadGuard<'_, T>> {
+        self.acquire(Access::Read);
+
+        RwLockReadGuard::new(self)
+    }
+
+    /// Attempts to lock with shared read access without waiting
+    pub fn try_read(&self) -> TryLockResult<RwLockReadGuard<'_, T>> {
+        if !self.try_acquire(Access::Read) {
+            return Err(TryLockError::WouldBlock);
+        }
+
+        Ok(RwLockReadGuard::new(self)?)
+    }
+
+    /// Locks with exclusive write access, suspending the current coroutine until it can be
+    /// acquired
+    pub fn write(&self) -> LockResult<RwLockWriteGuard<'_, T>> {
+        self.acquire(Access::Write);
+
+        RwLockWriteGuard::new(self)
+    }
+
+    /// Attempts to lock with exclusive write access without waiting
+    pub fn try_write(&self) -> TryLockResult<RwLockWriteGuard<'_, T>> {
+        if !self.try_acquire(Access::Write) {
+            return Err(TryLockError::WouldBlock);
+        }
+
+        Ok(RwLockWriteGuard::new(self)?)
+    }
+
+    /// Determines whether the lock is poisoned
+    pub fn is_poisoned(&self) -> bool {
+        self.poison.get()
+    }
+
+    /// Clear the poisoned state of the lock
+    pub fn clear_poison(&self) {
+        self.poison.clear();
+    }
+
+    /// Returns a mutable reference to the underlying data, no locking is needed
+    pub fn get_mut(&mut self) -> LockResult<&mut T> {
+        let poisoned = self.poison.get();
+        let data = self.data.get_mut();
+
+        if poisoned {
+            Err(PoisonError::new(data))
+        } else {
+            Ok(data)
+        }
+    }
+
+    fn try_acquire(&self, access: Access) -> bool {
+        let mut state = self.state.lock().unwrap();
+
+        let free = match access {
+            Access::Read => state.can_read(),
+            Access::Write => state.can_write(),
+        };
+
+        if free {
+            state.take(access);
+        }
+
+        free
+    }
+
+    fn acquire(&self, access: Access) {
+        loop {
+            let node = {
+                let mut state = self.state.lock().unwrap();
+
+                let free = match access {
+                    Access::Read => state.can_read(),
+                    Access::Write => state.can_write(),
+                };
+
+                if free {
+                    state.take(access);
+
+                    return;
+                }
+
+                let node = WaitNode::new();
+
+                state.waiters.push_back((node.clone(), access));
+
+                node
+            };
+
+            if node.park(None).is_ok() {
+                return;
+            }
+
+            self.abandon(&node, access);
+
+            wait_node::check_cancel();
+        }
+    }
+
+    /// Leave the queue after a failed wait, passing the lock on if it was granted meanwhile
+    fn abandon(&self, node: &Arc<WaitNode>, access: Access) {
+        let mut state = self.state.lock().unwrap();
+
+        if node.is_granted() {
+            drop(state);
+
+            return self.release(access);
+        }
+
+        state
+            .waiters
+            .retain(|(waiter, _)| !Arc::ptr_eq(waiter, node));
+
+        // A writer leaving the front of the queue may unblock the readers behind it
+        if !state.writer {
+            state.grant_next();
+        }
+    }
+
+    fn release(&self, access: Access) {
+        let mut state = self.state.lock().unwrap();
+
+        match access {
+            Access::Read => state.readers -= 1,
+            Access::Write => state.writer = false,
+        }
+
+        if state.readers == 0 {
+            state.grant_next();
+        }
+    }
+}
+
+impl<T: Default> Default for RwLock<T> {
+    fn default() -> RwLock<T> {
+        RwLock::new(T::default())
+    }
+}
+
+impl<T> From<T> for RwLock<T> {
+    fn from(t: T) -> RwLock<T> {
+        RwLock::new(t)
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for RwLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_struct("RwLock");
+
+        match self.try_read() {
+            Ok(guard) => d.field("data", &&*guard),
+            Err(TryLockError::Poisoned(err)) => d.field("data", &&**err.get_ref()),
+            Err(TryLockError::WouldBlock) => d.field("data", &format_args!("<locked>")),
+        };
+
+        d.field("poisoned", &self.poison.get());
+        d.finish_non_exhaustive()
+    }
+}
+
+impl<'a, T: ?Sized> RwLockReadGuard<'a, T> {
+    fn new(lock: &'a RwLock<T>) -> LockResult<RwLockReadGuard<'a, T>> {
+        let guard = RwLockReadGuard { lock };
+
+        if lock.poison.get() {
+            Err(PoisonError::new(guard))
+        } else {
+            Ok(guard)
+        }
+    }
+}
+
+impl<T: ?Sized> Deref for RwLockReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for RwLockReadGuard<'_, T> {
+    #[inline]
+    fn drop(&mut self) {
+        self.lock.release(Access::Read);
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for RwLockReadGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized + fmt::Display> fmt::Display for RwLockReadGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+impl<'a, T: ?Sized> RwLockWriteGuard<'a, T> {
+    fn new(lock: &'a RwLock<T>) -> LockResult<RwLockWriteGuard<'a, T>> {
+        let guard = RwLockWriteGuard {
+            lock,
+            poison: lock.poison.guard(),
+        };
+
+        if lock.poison.get() {
+            Err(PoisonError::new(guard))
+        } else {
+            Ok(guard)
+        }
+    }
+}
+
+impl<T: ?Sized> Deref for RwLockWriteGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for RwLockWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for RwLockWriteGuard<'_, T> {
+    #[inline]
+    fn drop(&mut self) {
+        self.lock.poison.done(&self.poison);
+        self.lock.release(Access::Write);
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for RwLockWriteGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized + fmt::Display> fmt::Display for RwLockWriteGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use super::*;
+    use crate::{sleep, spawn};
+
+    #[test]
+    fn waiting_writer_is_not_starved_by_readers() {
+        let lock = Arc::new(RwLock::new(0));
+        let reader = lock.read().unwrap();
+
+        let writer = {
+            let lock = lock.clone();
+
+            unsafe { spawn(move || *lock.write().unwrap() += 1) }
+        };
+
+        // Let the writer queue up, new readers have to wait behind it
+        while lock.try_read().is_ok() {
+            sleep(Duration::from_millis(1));
+        }
+
+        let late_reader = {
+            let lock = lock.clone();
+
+            unsafe { spawn(move || *lock.read().unwrap()) }
+        };
+
+        drop(reader);
+
+        writer.join().unwrap();
+
+        assert_eq!(late_reader.join().unwrap(), 1);
+    }
+}
@@ -42,7 +42,7 @@ impl SeqLock {
         loop {
             let previous = self.state.swap(1, Ordering::Acquire);
 
-            if previous == 1 {
+            if previous != 1 {
                 atomic::fence(Ordering::Release);
 
                 return SeqLockWriteGuard {
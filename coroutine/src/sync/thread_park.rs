@@ -1,6 +1,6 @@
 use std::{
     sync::{Condvar, Mutex},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use crate::park::ParkError;
@@ -20,20 +20,23 @@ impl ThreadPark {
     }
 
     pub fn park_timeout(&self, dur: Option<Duration>) -> Result<(), ParkError> {
-        let mut result = Ok(());
+        let deadline = dur.map(|d| Instant::now() + d);
         let mut guard = self.lock.lock().unwrap();
 
-        while *guard == 0 && result.is_ok() {
-            match dur {
+        while *guard == 0 {
+            match deadline {
                 None => {
-                    self.cvar.wait(guard);
+                    guard = self.cvar.wait(guard).unwrap();
                 }
-                Some(d) => {
-                    let t = self.cvar.wait_timeout(guard, d).unwrap().1;
+                Some(deadline) => {
+                    // Spurious wakeups wait for the remaining time only
+                    let remaining = deadline.saturating_duration_since(Instant::now());
 
-                    if t.timed_out() {
-                        result = Err(ParkError::Timeout);
+                    if remaining.is_zero() {
+                        return Err(ParkError::Timeout);
                     }
+
+                    guard = self.cvar.wait_timeout(guard, remaining).unwrap().0;
                 }
             }
         }
@@ -41,7 +44,7 @@ impl ThreadPark {
         // Must clear the status
         *guard = 0;
 
-        result
+        Ok(())
     }
 
     pub fn unpark(&self) {
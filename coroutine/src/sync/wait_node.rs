@@ -0,0 +1,63 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use crate::{current_cancel_data, is_coroutine, park::ParkError, sync::blocker::Blocker};
+
+/// A coroutine or a thread queued on a lock or a condition variable
+///
+/// The waker hands the lock over with `grant` instead of letting the waiter race for it, so the
+/// waiters are served in order. The parking ignores the cancel, a cancelled waiter first takes
+/// itself out of the queue and then unwinds with `check_cancel`.
+pub(crate) struct WaitNode {
+    blocker: Blocker,
+    granted: AtomicBool,
+}
+
+impl WaitNode {
+    pub(crate) fn new() -> Arc<WaitNode> {
+        Arc::new(WaitNode {
+            blocker: Blocker::new(true),
+            granted: AtomicBool::new(false),
+        })
+    }
+
+    /// Wake up the waiter, passing it what it waited for
+    #[inline]
+    pub(crate) fn grant(&self) {
+        self.granted.store(true, Ordering::Release);
+        self.blocker.unpark();
+    }
+
+    #[inline]
+    pub(crate) fn is_granted(&self) -> bool {
+        self.granted.load(Ordering::Acquire)
+    }
+
+    /// Wait until granted, fails on a timeout or a cancel of the coroutine
+    /// A failed waiter must check `is_granted` under the lock of its queue, the grant may race
+    pub(crate) fn park(&self, timeout: Option<Duration>) -> Result<(), ParkError> {
+        let deadline = timeout.map(|dur| Instant::now() + dur);
+
+        while !self.is_granted() {
+            let remaining =
+                deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+
+            self.blocker.park(remaining)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Unwind the running coroutine if it was cancelled while it waited
+/// Returns if the cancel is disabled, the caller then carries on as after a spurious wakeup
+pub(crate) fn check_cancel() {
+    if is_coroutine() {
+        current_cancel_data().check_cancel();
+    }
+}
@@ -0,0 +1,6 @@
+mod timeout_handle;
+mod timer_wheel;
+
+pub use timeout_handle::TimeoutHandle;
+pub(crate) use timeout_handle::TimerEntry;
+pub(crate) use timer_wheel::TimerWheel;
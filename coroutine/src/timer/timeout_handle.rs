@@ -0,0 +1,83 @@
+use std::{
+    fmt,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use crate::sync::AtomicOption;
+
+/// A timer registered in a `TimerWheel`
+pub(crate) struct TimerEntry<T> {
+    /// Tick of the wheel at which the timer expires
+    pub(crate) tick: u64,
+
+    /// Handed to the wheel owner on expiry, taken by a cancel before that
+    data: AtomicOption<T>,
+
+    fired: AtomicBool,
+}
+
+impl<T> TimerEntry<T> {
+    pub(crate) fn new(tick: u64, data: T) -> TimerEntry<T> {
+        TimerEntry {
+            tick,
+            data: AtomicOption::some(data),
+            fired: AtomicBool::new(false),
+        }
+    }
+
+    /// The timer was neither cancelled nor fired yet
+    #[inline]
+    pub(crate) fn is_pending(&self) -> bool {
+        !self.data.is_none()
+    }
+
+    /// Take the data of an expired timer, `None` if it was cancelled
+    pub(crate) fn fire(&self) -> Option<T> {
+        let data = self.data.take();
+
+        // Sequentially consistent, a park re-checks the flag after it stored the coroutine
+        if data.is_some() {
+            self.fired.store(true, Ordering::SeqCst);
+        }
+
+        data
+    }
+}
+
+/// Handle to a timer, used to cancel it and to find out whether it expired
+///
+/// Dropping the handle doesn't cancel the timer.
+pub struct TimeoutHandle<T> {
+    entry: Arc<TimerEntry<T>>,
+}
+
+impl<T> TimeoutHandle<T> {
+    pub(crate) fn new(entry: Arc<TimerEntry<T>>) -> TimeoutHandle<T> {
+        TimeoutHandle { entry }
+    }
+
+    /// Cancel the timer, returns the data if the timer had not expired yet
+    /// The wheel drops its entry lazily, when the slot of the entry comes up
+    #[inline]
+    pub fn cancel(&self) -> Option<T> {
+        self.entry.data.take()
+    }
+
+    /// Returns true if the timer expired before it was cancelled
+    #[inline]
+    pub fn is_fired(&self) -> bool {
+        self.entry.fired.load(Ordering::SeqCst)
+    }
+}
+
+impl<T> fmt::Debug for TimeoutHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TimeoutHandle")
+            .field("tick", &self.entry.tick)
+            .field("fired", &self.is_fired())
+            .finish()
+    }
+}
@@ -0,0 +1,214 @@
+use std::{
+    mem,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use crate::timer::{TimeoutHandle, TimerEntry};
+
+/// Number of bits of a tick indexing the slots of a level
+const SLOT_BITS: u32 = 6;
+
+/// Number of slots of a level
+const SLOTS: usize = 1 << SLOT_BITS;
+
+/// Number of levels, the last one spans about 4.6 hours
+const LEVELS: usize = 4;
+
+/// Timers further away are parked in the last level and cascaded until they're in range
+const MAX_DELTA: u64 = (1 << (SLOT_BITS * LEVELS as u32)) - 1;
+
+/// Duration of a tick
+const TICK: Duration = Duration::from_millis(1);
+
+/// Hierarchical hashed timer wheel with a resolution of a millisecond
+///
+/// A timer is stored at the level whose slots are as long as the distance to its expiry: the
+/// first level has a slot per tick, every other level has slots as long as the whole level below
+/// it. When the wheel reaches the start of a slot of an upper level, the timers of the slot are
+/// cascaded to the levels below, until they end up in the first level and expire. Adding and
+/// cancelling are constant time, a cancelled timer stays in its slot until the slot comes up.
+///
+/// A timer never fires early, it fires at the first `advance` after its expiry.
+pub(crate) struct TimerWheel<T> {
+    /// Instant of the tick 0
+    start: Instant,
+
+    /// Current tick, every timer expiring at or before it has fired
+    tick: u64,
+
+    levels: [[Vec<Arc<TimerEntry<T>>>; SLOTS]; LEVELS],
+
+    /// Number of entries in the slots, including the cancelled ones
+    len: usize,
+}
+
+impl<T> TimerWheel<T> {
+    pub(crate) fn new() -> TimerWheel<T> {
+        TimerWheel {
+            start: Instant::now(),
+            tick: 0,
+            levels: std::array::from_fn(|_| std::array::from_fn(|_| Vec::new())),
+            len: 0,
+        }
+    }
+
+    /// Add a timer expiring at `deadline`, `data` is handed back by `advance` when it fires
+    pub(crate) fn add(&mut self, deadline: Instant, data: T) -> TimeoutHandle<T> {
+        // Round up, so that the timer doesn't fire before the deadline
+        let since_start = deadline.saturating_duration_since(self.start);
+        let tick = since_start.as_nanos().div_ceil(TICK.as_nanos()) as u64;
+
+        let entry = Arc::new(TimerEntry::new(tick, data));
+
+        self.insert(entry.clone());
+        self.len += 1;
+
+        TimeoutHandle::new(entry)
+    }
+
+    /// Fire the timers expired at `now`, pushing their data to `expired`
+    pub(crate) fn advance(&mut self, now: Instant, expired: &mut Vec<T>) {
+        let target =
+            (now.saturating_duration_since(self.start).as_nanos() / TICK.as_nanos()) as u64;
+
+        while self.tick < target {
+            if self.len == 0 {
+                self.tick = target;
+
+                break;
+            }
+
+            self.tick += 1;
+
+            // Cascade the upper slots starting at this tick, the highest level first
+            for level in (1..LEVELS).rev() {
+                let shift = SLOT_BITS * level as u32;
+
+                if self.tick & ((1 << shift) - 1) == 0 {
+                    let slot = (self.tick >> shift) as usize & (SLOTS - 1);
+
+                    for entry in mem::take(&mut self.levels[level][slot]) {
+                        if entry.tick <= self.tick {
+                            self.expire(&entry, expired);
+                        } else if entry.is_pending() {
+                            self.insert(entry);
+                        } else {
+                            self.len -= 1;
+                        }
+                    }
+                }
+            }
+
+            let slot = self.tick as usize & (SLOTS - 1);
+
+            for entry in mem::take(&mut self.levels[0][slot]) {
+                self.expire(&entry, expired);
+            }
+        }
+    }
+
+    /// Time from `now` until the next tick the wheel has work at, `None` if it's empty
+    ///
+    /// That's either the expiry of the next timer of the first level or the next cascade of an
+    /// upper level, so waiting for it doesn't miss any timer.
+    pub(crate) fn next_timeout(&self, now: Instant) -> Option<Duration> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let next = (0..LEVELS)
+            .filter_map(|level| {
+                let shift = SLOT_BITS * level as u32;
+                let base = self.tick >> shift;
+
+                (1..=SLOTS as u64)
+                    .find(|k| !self.levels[level][(base + k) as usize & (SLOTS - 1)].is_empty())
+                    .map(|k| (base + k) << shift)
+            })
+            .min()?;
+
+        let deadline = self.start + Duration::from_nanos(next * TICK.as_nanos() as u64);
+
+        Some(deadline.saturating_duration_since(now))
+    }
+
+    fn insert(&mut self, entry: Arc<TimerEntry<T>>) {
+        // A timer already due goes to the next tick
+        let tick = entry.tick.max(self.tick + 1);
+        let tick = tick.min(self.tick + MAX_DELTA);
+        let delta = tick - self.tick;
+
+        let level = ((u64::BITS - 1 - delta.leading_zeros()) / SLOT_BITS) as usize;
+        let slot = (tick >> (SLOT_BITS * level as u32)) as usize & (SLOTS - 1);
+
+        self.levels[level][slot].push(entry);
+    }
+
+    fn expire(&mut self, entry: &TimerEntry<T>, expired: &mut Vec<T>) {
+        self.len -= 1;
+
+        if let Some(data) = entry.fire() {
+            expired.push(data);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fired_at(wheel: &mut TimerWheel<u32>, ms: u64) -> Vec<u32> {
+        let mut expired = Vec::new();
+
+        wheel.advance(wheel.start + Duration::from_millis(ms), &mut expired);
+
+        expired
+    }
+
+    #[test]
+    fn fires_in_order_across_levels() {
+        let mut wheel = TimerWheel::new();
+        let start = wheel.start;
+
+        for (data, ms) in [(1, 5), (2, 100), (3, 5_000), (4, 300_000)] {
+            wheel.add(start + Duration::from_millis(ms), data);
+        }
+
+        assert_eq!(fired_at(&mut wheel, 4), [] as [u32; 0]);
+        assert_eq!(fired_at(&mut wheel, 5), [1]);
+        assert_eq!(fired_at(&mut wheel, 99), [] as [u32; 0]);
+        assert_eq!(fired_at(&mut wheel, 100), [2]);
+        assert_eq!(fired_at(&mut wheel, 4_999), [] as [u32; 0]);
+        assert_eq!(fired_at(&mut wheel, 5_000), [3]);
+        assert_eq!(fired_at(&mut wheel, 299_999), [] as [u32; 0]);
+        assert_eq!(fired_at(&mut wheel, 300_000), [4]);
+        assert_eq!(wheel.next_timeout(start), None);
+    }
+
+    #[test]
+    fn cancelled_timers_do_not_fire() {
+        let mut wheel = TimerWheel::new();
+        let start = wheel.start;
+
+        let handle = wheel.add(start + Duration::from_millis(10), 1);
+
+        wheel.add(start + Duration::from_millis(20), 2);
+
+        assert_eq!(handle.cancel(), Some(1));
+        assert_eq!(fired_at(&mut wheel, 30), [2]);
+        assert!(!handle.is_fired());
+    }
+
+    #[test]
+    fn next_timeout_does_not_overshoot() {
+        let mut wheel = TimerWheel::new();
+        let start = wheel.start;
+
+        wheel.add(start + Duration::from_millis(3_000), 1);
+
+        let timeout = wheel.next_timeout(start).unwrap();
+
+        assert!(timeout > Duration::ZERO && timeout <= Duration::from_millis(3_000));
+    }
+}
@@ -1,10 +1,12 @@
 //! Yield
 //! Generator yield implementation
-use std::any::Any;
-use std::sync::atomic;
+use std::{any::Any, mem, sync::atomic};
 
 use crate::{
-    event::EventResult,
+    current_cancel_data,
+    error::Error,
+    event::{EventResult, EventSource, EventSubscriber},
+    register_context::RegisterContext,
     runtime::{Context, ContextStack, is_generator},
 };
 
@@ -18,11 +20,12 @@ macro_rules! done {
 
 // WARN: Don't use this directly, use done!() macro instead
 // Would panic if used in none generator context
+#[doc(hidden)]
 #[inline]
 pub fn done<T>() -> T {
     assert!(is_generator(), "done is only possible in a generator");
 
-    std::panic::panic_any(crate::error::Error::Done);
+    std::panic::panic_any(Error::Done);
 }
 
 /// Switch back to parent context
@@ -37,7 +40,103 @@ pub fn yield_now() {
 pub fn raw_yield_now(env: &ContextStack, cur: &mut Context) {
     let parent = env.pop_context(cur as *mut _);
 
-    RegContext::swap(&mut cur.regs, &parent.regs);
+    RegisterContext::swap(&mut cur.regs, &parent.regs);
+}
+
+/// Yield `v` from the running generator and return the value passed in by the next `send`
+/// Panics with a type error if `T` or `A` don't match the generator types
+#[inline]
+pub fn yield_<A: Any, T: Any>(v: T) -> Option<A> {
+    yield_with(v);
+
+    atomic::compiler_fence(atomic::Ordering::Acquire);
+
+    get_yield()
+}
+
+/// Yield `v` from the running generator without taking the sent value
+#[inline]
+pub fn yield_with<T: Any>(v: T) {
+    let env = ContextStack::current();
+    let context = env.top();
+
+    raw_yield(&env, context, v);
+}
+
+/// Get the value passed in by the last `send` to the running generator
+#[inline]
+pub fn get_yield<A: Any>() -> Option<A> {
+    let context = ContextStack::current().top();
+
+    raw_get_yield(context)
+}
+
+#[inline]
+fn raw_yield<T: Any>(env: &ContextStack, context: &mut Context, v: T) {
+    if !context.is_generator() {
+        panic!("yield from none generator context");
+    }
+
+    context.set_ret(v);
+    context._ref -= 1;
+
+    raw_yield_now(env, context);
+
+    // The generator is being cancelled, unwind its stack
+    if context._ref != 1 {
+        std::panic::panic_any(Error::Cancel);
+    }
+}
+
+#[inline]
+fn raw_get_yield<A: Any>(context: &mut Context) -> Option<A> {
+    if !context.is_generator() {
+        panic!("get yield from none generator context");
+    }
+
+    context.get_para()
+}
+
+/// Suspend the running coroutine, handing `v` to the worker that resumed it
+/// Works from nested generators, the whole coroutine is suspended
+#[inline]
+pub(crate) fn coroutine_yield_with<T>(v: T) {
+    let env = ContextStack::current();
+    let context = env
+        .coroutine_ctx()
+        .expect("coroutine yield from none coroutine context");
+
+    // The coroutine is being cancelled, unwind its stack
+    if context._ref != 1 {
+        std::panic::panic_any(Error::Cancel);
+    }
+
+    context.coroutine_set_ret(v);
+    context._ref -= 1;
+
+    let parent = env.pop_context(context);
+    let top = unsafe { &mut *context.parent };
+
+    // Save the registers of the running top context, it may be a nested generator
+    RegisterContext::swap(&mut top.regs, &parent.regs);
+}
+
+/// Suspend the running coroutine on `resource`, which is handed the coroutine to resume it later
+#[inline]
+pub(crate) fn yield_with_event<T: EventSource>(resource: &T) {
+    let cancel = current_cancel_data();
+    let ptr: *const (dyn EventSource + '_) = resource;
+
+    // The resource lives on the coroutine stack, which is not touched until it resumes
+    let subscriber = EventSubscriber {
+        resource: unsafe {
+            mem::transmute::<*const (dyn EventSource + '_), *mut dyn EventSource>(ptr)
+        },
+    };
+
+    coroutine_yield_with(subscriber);
+
+    resource.yield_back(cancel);
 }
 
 #[inline]
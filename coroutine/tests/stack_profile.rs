@@ -0,0 +1,83 @@
+//! Stack profiling is a process wide setting, so it's tested in its own binary
+
+use std::{
+    hint::black_box,
+    thread,
+    time::{Duration, Instant},
+};
+
+use coroutine::{Config, CoroutineBuilder, StackUsage, stack_profile};
+
+/// Stack size classes of the pool around the default size
+const CLASSES: [usize; 6] = [0x400, 0x800, 0x1000, 0x2000, 0x4000, 0x8000];
+
+/// Uses about 0xc00 words of stack, more without optimizations
+fn use_stack() -> u8 {
+    let frame = black_box([1u8; 0xc00 * 8]);
+
+    black_box(frame)[0x800]
+}
+
+/// Waits for the `count`th profile of `name`
+///
+/// A join returns once the result is set, the stack is measured after that when the coroutine
+/// is released by its worker.
+fn profiled(name: &str, count: u64) -> StackUsage {
+    let deadline = Instant::now() + Duration::from_secs(5);
+
+    loop {
+        if let Some(usage) = stack_profile::usage(name).filter(|usage| usage.count() >= count) {
+            return usage;
+        }
+
+        assert!(
+            Instant::now() < deadline,
+            "{name} wasn't profiled {count} times"
+        );
+
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[test]
+fn auto_stack_size_picks_the_class_of_the_profile() {
+    Config::builder()
+        .stack_size(0x1000)
+        .stack_profiling(true)
+        .init()
+        .unwrap();
+
+    let measured = unsafe {
+        CoroutineBuilder::new()
+            .name("deep")
+            .stack_size(0x8000)
+            .spawn(use_stack)
+    }
+    .unwrap();
+
+    measured.join().unwrap();
+
+    let max = profiled("deep", 1).max();
+    let class = CLASSES
+        .into_iter()
+        .find(|&class| class >= max + max / 2)
+        .unwrap();
+
+    // Larger than the default class, which is the only one tracking without profiling
+    assert!(class > 0x1000);
+
+    let auto = unsafe {
+        CoroutineBuilder::new()
+            .name("deep")
+            .auto_stack_size()
+            .spawn(use_stack)
+    }
+    .unwrap();
+
+    // The size is odd while profiling, so that the stack of the coroutine is measured too
+    assert_eq!(auto.coroutine().stack_size(), class | 1);
+
+    auto.join().unwrap();
+
+    assert_eq!(profiled("deep", 2).count(), 2);
+}